@@ -14,11 +14,14 @@
 //!
 //! System:
 //!   anchor build                     Build graph
+//!   anchor build --report [--json]   Build graph, show the build report
 //!   anchor stats                     Show stats
+//!   anchor config show               Show effective config
 //!   anchor daemon [start|stop]       Manage daemon
 
-use anchor::cli::{self, read as cli_read, Cli, Commands};
-use anchor::graph::{build_graph, CodeGraph};
+use anchor::cli::{self, read as cli_read, Cli, Commands, ConfigAction};
+use anchor::config::{AnchorConfig, ANCHOR_DIR, REPORT_FILE};
+use anchor::graph::{build_graph_with_filter, CodeGraph, SettingsFingerprint, SourceFilter};
 use anchor::updater;
 use anyhow::Result;
 use clap::Parser;
@@ -35,7 +38,11 @@ fn main() {
 
 fn run(cli: Cli) -> Result<()> {
     let root = cli.root.canonicalize().unwrap_or(cli.root);
-    let cache_path = root.join(".anchor/graph.bin");
+    let config = AnchorConfig::for_project(&root)?;
+    let anchor_dir = root.join(ANCHOR_DIR);
+    let cache_path = config.resolve_cache_path(&anchor_dir);
+    let filter = || SourceFilter::for_project(&root, &config);
+    let settings = SettingsFingerprint::for_project(&root, &config)?;
 
     // No command = show help
     if cli.command.is_none() {
@@ -46,12 +53,12 @@ fn run(cli: Cli) -> Result<()> {
     match cli.command.unwrap() {
         // ─── Query Commands ───────────────────────────────────────
         Commands::Context { query, limit } => {
-            let graph = load_or_build_graph(&root, &cache_path)?;
+            let graph = load_or_build_graph(filter, &cache_path, settings)?;
             cli_read::context(&graph, &query, limit)
         }
 
         Commands::Search { query, pattern, limit } => {
-            let graph = load_or_build_graph(&root, &cache_path)?;
+            let graph = load_or_build_graph(filter, &cache_path, settings)?;
             cli_read::search(&graph, &query, pattern.as_deref(), limit)
         }
 
@@ -74,31 +81,43 @@ fn run(cli: Cli) -> Result<()> {
 
         // ─── Parallel Command ─────────────────────────────────────
         Commands::Plan { file } => {
-            cli::plan::execute(&root, &file)
+            cli::plan::execute(&root, &file, || load_or_build_graph(filter, &cache_path, settings))
         }
 
         // ─── System Commands ──────────────────────────────────────
-        Commands::Build => {
-            cli_read::build(&root, &cache_path)
-        }
+        Commands::Build { report, json } => cli_read::build(
+            &filter()?,
+            &cache_path,
+            settings,
+            &root.join(REPORT_FILE),
+            report,
+            json,
+        ),
+
+        Commands::Config { action } => match action {
+            ConfigAction::Show => {
+                print!("{}", config.to_toml()?);
+                Ok(())
+            }
+        },
 
         Commands::Map { scope } => {
-            let graph = load_or_build_graph(&root, &cache_path)?;
+            let graph = load_or_build_graph(filter, &cache_path, settings)?;
             cli_read::map(&graph, scope.as_deref())
         }
 
         Commands::Overview => {
-            let graph = load_or_build_graph(&root, &cache_path)?;
+            let graph = load_or_build_graph(filter, &cache_path, settings)?;
             cli_read::overview(&graph)
         }
 
         Commands::Files => {
-            let graph = load_or_build_graph(&root, &cache_path)?;
+            let graph = load_or_build_graph(filter, &cache_path, settings)?;
             cli_read::files(&graph)
         }
 
         Commands::Stats => {
-            let graph = load_or_build_graph(&root, &cache_path)?;
+            let graph = load_or_build_graph(filter, &cache_path, settings)?;
             cli_read::stats(&graph)
         }
 
@@ -144,22 +163,33 @@ fn uninstall() -> Result<()> {
     Ok(())
 }
 
-/// Load graph from cache or build if not exists
-fn load_or_build_graph(root: &Path, cache_path: &Path) -> Result<CodeGraph> {
+/// Load graph from cache, or build it if there is none or it was built
+/// with other settings
+fn load_or_build_graph(
+    filter: impl Fn() -> anchor::Result<SourceFilter>,
+    cache_path: &Path,
+    settings: SettingsFingerprint,
+) -> Result<CodeGraph> {
     if cache_path.exists() {
-        match CodeGraph::load(cache_path) {
-            Ok(graph) => return Ok(graph),
-            Err(e) => {
-                eprintln!("Warning: Failed to load cache, rebuilding: {}", e);
+        if SettingsFingerprint::saved(cache_path) != Some(settings) {
+            eprintln!("Config or queries changed since the cache was built, rebuilding");
+        } else {
+            match CodeGraph::load(cache_path) {
+                Ok(graph) => return Ok(graph),
+                Err(e) => {
+                    eprintln!("Warning: Failed to load cache, rebuilding: {}", e);
+                }
             }
         }
     }
 
     // Build and cache
-    let graph = build_graph(root);
+    let graph = build_graph_with_filter(&filter()?);
     if let Some(parent) = cache_path.parent() {
         let _ = std::fs::create_dir_all(parent);
     }
-    let _ = graph.save(cache_path);
+    if graph.save(cache_path).is_ok() {
+        let _ = settings.save(cache_path);
+    }
     Ok(graph)
 }
@@ -4,7 +4,7 @@
 //! - Read/Search: search, read, context
 //! - Write: write, edit (TODO: ACI-based)
 //! - Parallel: plan
-//! - System: build, stats, daemon
+//! - System: build, stats, config, daemon
 
 pub mod daemon;
 pub mod plan;
@@ -38,6 +38,7 @@ const HELP_TEXT: &str = "
 
 Start here:
   build                 Index codebase
+  build --report        Index + skipped files, syntax errors, coverage
   map                   Codebase map (modules + top symbols)
   map <scope>           Zoom into module
 
@@ -49,6 +50,7 @@ Query:
 Other:
   overview              Files + symbol counts
   stats                 Graph statistics
+  config show           Effective .anchor/config.toml
 
 Options:
   -r, --root <PATH>     Project root (default: .)
@@ -118,11 +120,25 @@ pub enum Commands {
 
     // ─── System ───────────────────────────────────────────────────
     /// Build/rebuild the code graph
-    Build,
+    Build {
+        /// Print skipped files, syntax errors, slow files and per-language coverage
+        #[arg(long)]
+        report: bool,
+
+        /// Print the build report as JSON instead
+        #[arg(long)]
+        json: bool,
+    },
 
     /// Show graph statistics
     Stats,
 
+    /// Inspect the project configuration
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
     // ─── Hidden Commands ─────────────────────────────────────────
     /// List all indexed files
     #[command(hide = true)]
@@ -148,6 +164,13 @@ pub enum Commands {
     Version,
 }
 
+/// Config subcommands.
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print the effective config, with defaults filled in
+    Show,
+}
+
 /// Print the ASCII banner (only for install/update)
 pub fn print_banner() {
     println!(
@@ -13,8 +13,13 @@ use crate::graph::CodeGraph;
 // use crate::write::{create_file, insert_after, replace_all, WriteError};  // TODO: Write operations not finalized
 use super::read as cli_read;
 
-/// Execute a plan file sequentially (fallback when no daemon)
-pub fn execute(root: &Path, file: &str) -> Result<()> {
+/// Execute a plan file sequentially (fallback when no daemon).
+/// `load_graph` is only called if the plan reads from the graph.
+pub fn execute(
+    root: &Path,
+    file: &str,
+    load_graph: impl FnOnce() -> Result<CodeGraph>,
+) -> Result<()> {
     let plan_path = if Path::new(file).is_absolute() {
         PathBuf::from(file)
     } else {
@@ -32,12 +37,7 @@ pub fn execute(root: &Path, file: &str) -> Result<()> {
         PlanOperation::Search { .. } | PlanOperation::Read { .. } | PlanOperation::Context { .. }
     ));
 
-    let graph = if has_reads {
-        let cache_path = root.join(".anchor/graph.bin");
-        CodeGraph::load(&cache_path).ok()
-    } else {
-        None
-    };
+    let graph = if has_reads { Some(load_graph()?) } else { None };
 
     println!("Executing plan: {} operations", plan.operations.len());
     println!();
@@ -171,7 +171,11 @@ pub enum PlanOperation {
 // ─── Parallel Execution (via daemon with locking) ──────────────
 
 /// Execute a plan file with parallel operations via daemon (with locking)
-pub fn execute_parallel(root: &Path, file: &str) -> Result<()> {
+pub fn execute_parallel(
+    root: &Path,
+    file: &str,
+    load_graph: impl FnOnce() -> Result<CodeGraph>,
+) -> Result<()> {
     let plan_path = if Path::new(file).is_absolute() {
         PathBuf::from(file)
     } else {
@@ -189,12 +193,7 @@ pub fn execute_parallel(root: &Path, file: &str) -> Result<()> {
         PlanOperation::Search { .. } | PlanOperation::Read { .. } | PlanOperation::Context { .. }
     ));
 
-    let graph = if has_reads {
-        let cache_path = root.join(".anchor/graph.bin");
-        CodeGraph::load(&cache_path).ok()
-    } else {
-        None
-    };
+    let graph = if has_reads { Some(load_graph()?) } else { None };
 
     println!(
         "Executing plan: {} operations (parallel with locking)",
@@ -273,23 +272,23 @@ fn execute_operation_via_daemon(_root: &Path, op: &PlanOperation, graph: Option<
             if let Some(g) = graph {
                 let _ = cli_read::search(g, query, pattern.as_deref(), limit.unwrap_or(20));
             }
-            return Ok(PlanResponse::Ok { data: serde_json::json!({"op": "search"}) });
+            Ok(PlanResponse::Ok { data: serde_json::json!({"op": "search"}) })
         }
         PlanOperation::Read { symbol } => {
             if let Some(g) = graph {
                 let _ = cli_read::read(g, symbol);
             }
-            return Ok(PlanResponse::Ok { data: serde_json::json!({"op": "read"}) });
+            Ok(PlanResponse::Ok { data: serde_json::json!({"op": "read"}) })
         }
         PlanOperation::Context { query, limit } => {
             if let Some(g) = graph {
                 let _ = cli_read::context(g, query, limit.unwrap_or(5));
             }
-            return Ok(PlanResponse::Ok { data: serde_json::json!({"op": "context"}) });
+            Ok(PlanResponse::Ok { data: serde_json::json!({"op": "context"}) })
         }
         // Write operations not finalized
         _ => {
-            return Ok(PlanResponse::Error { message: "Write operations not yet finalized".to_string() });
+            Ok(PlanResponse::Error { message: "Write operations not yet finalized".to_string() })
         }
     }
 }
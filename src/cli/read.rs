@@ -7,7 +7,7 @@ use anyhow::Result;
 use std::path::Path;
 use std::sync::Arc;
 
-use crate::graph::CodeGraph;
+use crate::graph::{BuildReport, CodeGraph, SettingsFingerprint, SourceFilter};
 use crate::graphql::{build_schema, execute};
 
 /// Search for symbols by name or pattern.
@@ -265,18 +265,99 @@ pub fn context(graph: &CodeGraph, query: &str, limit: usize) -> Result<()> {
     Ok(())
 }
 
-/// Build/rebuild the code graph
-pub fn build(root: &Path, cache_path: &Path) -> Result<()> {
-    println!("Building...");
-    let graph = crate::graph::build_graph(root);
+/// Build/rebuild the code graph, saving its settings fingerprint and the
+/// build report next to it
+pub fn build(
+    filter: &SourceFilter,
+    cache_path: &Path,
+    settings: SettingsFingerprint,
+    report_path: &Path,
+    report: bool,
+    json: bool,
+) -> Result<()> {
+    if !json {
+        println!("Building...");
+    }
+    let (graph, build_report) = crate::graph::build_graph_with_report(filter);
     std::fs::create_dir_all(cache_path.parent().unwrap())?;
     graph.save(cache_path)?;
+    settings.save(cache_path)?;
+    if let Some(parent) = report_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    build_report.save(report_path)?;
 
+    if json {
+        println!("{}", serde_json::to_string_pretty(&build_report.to_json())?);
+        return Ok(());
+    }
     let stats = graph.stats();
     println!("files:{} symbols:{} edges:{}", stats.file_count, stats.symbol_count, stats.total_edges);
+    if report {
+        print_report(&build_report, filter.root());
+    }
     Ok(())
 }
 
+/// Print a build report compactly, paths relative to the root
+fn print_report(report: &BuildReport, root: &Path) {
+    let rel = |path: &Path| {
+        path.strip_prefix(root)
+            .unwrap_or(path)
+            .display()
+            .to_string()
+    };
+
+    println!("time:{}ms", report.elapsed_ms);
+    println!();
+    println!("languages:");
+    for lang in report.coverage() {
+        println!(
+            "  {} files:{} indexed:{} skipped:{} errors:{} symbols:{}",
+            lang.language, lang.files, lang.indexed, lang.skipped, lang.with_errors, lang.symbols
+        );
+    }
+
+    let skipped: Vec<_> = report.skipped().collect();
+    if !skipped.is_empty() {
+        println!();
+        println!("skipped:");
+        for file in skipped {
+            println!(
+                "  {} {}",
+                rel(&file.path),
+                file.skipped.as_deref().unwrap_or("")
+            );
+        }
+    }
+
+    let with_errors = report.with_syntax_errors();
+    if !with_errors.is_empty() {
+        println!();
+        println!("syntax errors:");
+        for file in with_errors {
+            let lines: Vec<_> = file
+                .syntax_errors
+                .iter()
+                .take(5)
+                .map(|l| l.to_string())
+                .collect();
+            println!(
+                "  {} errors:{} lines:{}",
+                rel(&file.path),
+                file.syntax_errors.len(),
+                lines.join(",")
+            );
+        }
+    }
+
+    println!();
+    println!("slowest:");
+    for file in report.slowest(5) {
+        println!("  {} {}us", rel(&file.path), file.elapsed_us);
+    }
+}
+
 /// Get graph stats via GraphQL
 pub fn stats(graph: &CodeGraph) -> Result<()> {
     let schema = build_schema(Arc::new(graph.clone()));
@@ -394,7 +475,7 @@ pub fn map(graph: &CodeGraph, scope: Option<&str>) -> Result<()> {
 
             let callers = graph.dependents(&symbol.name).len();
             let callees = graph.dependencies(&symbol.name).len();
-            let short_module = dir.split('/').last().unwrap_or(&dir).to_string();
+            let short_module = dir.split('/').next_back().unwrap_or(&dir).to_string();
 
             modules.entry(dir.clone())
                 .or_default()
@@ -448,7 +529,7 @@ pub fn map(graph: &CodeGraph, scope: Option<&str>) -> Result<()> {
     // Top level view: modules with counts
     let module_line: Vec<String> = modules.iter()
         .map(|(dir, symbols)| {
-            let short_dir = dir.split('/').last().unwrap_or(dir);
+            let short_dir = dir.split('/').next_back().unwrap_or(dir);
             format!("{}({}s)", short_dir, symbols.len())
         })
         .collect();
@@ -471,7 +552,7 @@ pub fn map(graph: &CodeGraph, scope: Option<&str>) -> Result<()> {
 
     // Top connected: symbols with most relationships (deduplicated by name)
     let mut by_connections = all_symbols.clone();
-    by_connections.sort_by(|a, b| (b.2 + b.3).cmp(&(a.2 + a.3)));
+    by_connections.sort_by_key(|b| std::cmp::Reverse(b.2 + b.3));
 
     let mut seen: HashSet<String> = HashSet::new();
     let mut top: Vec<String> = Vec::new();
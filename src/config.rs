@@ -1,32 +1,87 @@
 //! Configuration for Anchor.
 //!
-//! Loads from `.anchor/config.toml` or uses sensible defaults.
+//! Loads from `.anchor/config.toml` or uses sensible defaults. Unknown
+//! keys and invalid values are reported as errors rather than ignored.
+//!
+//! ```toml
+//! [project]
+//! root = "."
+//! languages = ["rust", "python"]
+//! include = ["src/**"]
+//! exclude = ["**/generated/**"]
+//!
+//! [graph]
+//! cache_path = ".anchor/graph.bin"
+//! max_snippet_lines = 50
+//!
+//! [watcher]
+//! debounce_ms = 200
+//!
+//! [language.python]
+//! extensions = ["pyi"]
+//! max_snippet_lines = 20
+//! ```
 
+use globset::Glob;
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 
+use crate::error::{AnchorError, Result};
+use crate::parser::{ExtractOptions, HostFormat, SupportedLanguage};
+
+/// Anchor's per-project directory, relative to the project root.
+pub const ANCHOR_DIR: &str = ".anchor";
+
+/// Location of the config file, relative to the project root.
+pub const CONFIG_FILE: &str = ".anchor/config.toml";
+
+/// Report of the last full build (skipped files, syntax errors, timings),
+/// relative to the project root.
+pub const REPORT_FILE: &str = ".anchor/build-report.json";
+
+/// Directory of tag query overrides (`<language>.scm`), relative to the
+/// project root.
+pub const QUERIES_DIR: &str = ".anchor/queries";
+
 /// Top-level Anchor configuration.
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct AnchorConfig {
     #[serde(default)]
     pub project: ProjectConfig,
     #[serde(default)]
     pub graph: GraphConfig,
+    #[serde(default)]
+    pub watcher: WatcherConfig,
+    /// Per-language overrides, keyed by language name (`[language.python]`).
+    #[serde(default)]
+    pub language: BTreeMap<String, LanguageConfig>,
 }
 
 /// Project-level settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct ProjectConfig {
     /// Root directory to scan (relative to .anchor/).
     #[serde(default = "default_root")]
     pub root: String,
-    /// Languages to parse.
+    /// Languages to parse, and host formats (`vue`, `svelte`, `html`,
+    /// `markdown`, `notebook`) whose embedded code is parsed.
     #[serde(default = "default_languages")]
     pub languages: Vec<String>,
+    /// Globs (relative to root) a file must match to be indexed.
+    /// Empty means every file is a candidate.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs (relative to root) that are never indexed.
+    #[serde(default)]
+    pub exclude: Vec<String>,
 }
 
 /// Graph engine settings.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
 pub struct GraphConfig {
     /// Path for the persisted graph cache.
     #[serde(default = "default_cache_path")]
@@ -36,17 +91,38 @@ pub struct GraphConfig {
     pub max_snippet_lines: usize,
 }
 
+/// File watcher settings.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WatcherConfig {
+    /// How long to wait for file events to settle before updating.
+    #[serde(default = "default_debounce_ms")]
+    pub debounce_ms: u64,
+}
+
+/// Overrides for a single language.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct LanguageConfig {
+    /// Extra file extensions (without the dot) parsed as this language.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub extensions: Vec<String>,
+    /// Snippet line limit for this language (overrides `graph`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_snippet_lines: Option<usize>,
+}
+
 fn default_root() -> String {
     ".".to_string()
 }
 
 fn default_languages() -> Vec<String> {
-    vec![
-        "rust".to_string(),
-        "python".to_string(),
-        "typescript".to_string(),
-        "javascript".to_string(),
-    ]
+    SupportedLanguage::ALL
+        .iter()
+        .map(|l| l.config_name())
+        .chain(HostFormat::ALL.iter().map(|f| f.name()))
+        .map(str::to_string)
+        .collect()
 }
 
 fn default_cache_path() -> String {
@@ -54,7 +130,11 @@ fn default_cache_path() -> String {
 }
 
 fn default_max_snippet_lines() -> usize {
-    10
+    ExtractOptions::default().max_snippet_lines
+}
+
+fn default_debounce_ms() -> u64 {
+    200
 }
 
 impl Default for ProjectConfig {
@@ -62,6 +142,8 @@ impl Default for ProjectConfig {
         Self {
             root: default_root(),
             languages: default_languages(),
+            include: Vec::new(),
+            exclude: Vec::new(),
         }
     }
 }
@@ -75,19 +157,115 @@ impl Default for GraphConfig {
     }
 }
 
+impl Default for WatcherConfig {
+    fn default() -> Self {
+        Self {
+            debounce_ms: default_debounce_ms(),
+        }
+    }
+}
+
 impl AnchorConfig {
-    /// Load config from a TOML file, falling back to defaults.
-    pub fn load(path: &Path) -> Self {
-        match std::fs::read_to_string(path) {
-            Ok(contents) => toml::from_str(&contents).unwrap_or_default(),
-            Err(_) => Self::default(),
+    /// Load config from a TOML file. A missing file yields the defaults;
+    /// a malformed file, unknown key or invalid value is an error.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = match std::fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Self::default()),
+            Err(e) => return Err(e.into()),
+        };
+        let config: Self = toml::from_str(&contents)
+            .map_err(|e| AnchorError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        config
+            .validate()
+            .map_err(|e| AnchorError::ConfigError(format!("{}: {}", path.display(), e)))?;
+        Ok(config)
+    }
+
+    /// Load `.anchor/config.toml` for a project root.
+    pub fn for_project(root: &Path) -> Result<Self> {
+        Self::load(&root.join(CONFIG_FILE))
+    }
+
+    /// Check values that deserialize fine but make no sense.
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        for name in &self.project.languages {
+            if SupportedLanguage::from_name(name).is_none() && HostFormat::from_name(name).is_none()
+            {
+                return Err(format!("unknown language `{}`", name));
+            }
+        }
+        for name in self.language.keys() {
+            if SupportedLanguage::from_name(name).is_none() {
+                return Err(format!("unknown language `{}`", name));
+            }
+        }
+        for glob in self.project.include.iter().chain(&self.project.exclude) {
+            Glob::new(glob).map_err(|e| format!("invalid glob `{}`: {}", glob, e))?;
         }
+        let snippet_limits = std::iter::once(Some(self.graph.max_snippet_lines))
+            .chain(self.language.values().map(|l| l.max_snippet_lines));
+        if snippet_limits.flatten().any(|n| n == 0) {
+            return Err("max_snippet_lines must be at least 1".to_string());
+        }
+        Ok(())
+    }
+
+    /// Render the effective config (defaults filled in) as TOML.
+    pub fn to_toml(&self) -> Result<String> {
+        toml::to_string_pretty(self).map_err(|e| AnchorError::SerializeError(e.to_string()))
+    }
+
+    /// Languages enabled by `project.languages`.
+    pub fn enabled_languages(&self) -> Vec<SupportedLanguage> {
+        self.project
+            .languages
+            .iter()
+            .filter_map(|name| SupportedLanguage::from_name(name))
+            .collect()
+    }
+
+    /// Host formats enabled by `project.languages`.
+    pub fn enabled_host_formats(&self) -> Vec<HostFormat> {
+        self.project
+            .languages
+            .iter()
+            .filter_map(|name| HostFormat::from_name(name))
+            .collect()
+    }
+
+    /// Extra extension -> language mappings from `[language.*]` tables.
+    pub fn extension_overrides(&self) -> Vec<(String, SupportedLanguage)> {
+        self.language
+            .iter()
+            .filter_map(|(name, lc)| Some((SupportedLanguage::from_name(name)?, lc)))
+            .flat_map(|(lang, lc)| {
+                lc.extensions
+                    .iter()
+                    .map(move |ext| (ext.trim_start_matches('.').to_string(), lang))
+            })
+            .collect()
+    }
+
+    /// Extraction options for a language, applying per-language overrides.
+    pub fn extract_options(&self, lang: SupportedLanguage) -> ExtractOptions {
+        let max_snippet_lines = self
+            .language
+            .iter()
+            .find(|(name, _)| SupportedLanguage::from_name(name) == Some(lang))
+            .and_then(|(_, lc)| lc.max_snippet_lines)
+            .unwrap_or(self.graph.max_snippet_lines);
+        ExtractOptions { max_snippet_lines }
     }
 
     /// Resolve the project root relative to the config file's parent directory.
+    /// `.` components are dropped so the default root is the parent itself.
     pub fn resolve_root(&self, anchor_dir: &Path) -> PathBuf {
         let parent = anchor_dir.parent().unwrap_or(anchor_dir);
-        parent.join(&self.project.root)
+        Path::new(&self.project.root)
+            .components()
+            .filter(|c| !matches!(c, std::path::Component::CurDir))
+            .fold(parent.to_path_buf(), |path, c| path.join(c))
     }
 
     /// Resolve the graph cache path relative to the anchor directory's parent.
@@ -96,3 +274,72 @@ impl AnchorConfig {
         parent.join(&self.graph.cache_path)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn load_str(contents: &str) -> Result<AnchorConfig> {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("config.toml");
+        std::fs::write(&path, contents).unwrap();
+        AnchorConfig::load(&path)
+    }
+
+    #[test]
+    fn test_missing_file_is_default() {
+        let config = AnchorConfig::load(Path::new("/nonexistent/config.toml")).unwrap();
+        assert_eq!(
+            config.enabled_languages().len(),
+            SupportedLanguage::ALL.len()
+        );
+        assert_eq!(config.enabled_host_formats().len(), HostFormat::ALL.len());
+        assert_eq!(config.watcher.debounce_ms, 200);
+    }
+
+    #[test]
+    fn test_unknown_key_is_error() {
+        let err = load_str("[graph]\ncache_pth = \"x.bin\"\n").unwrap_err();
+        assert!(matches!(err, AnchorError::ConfigError(_)));
+        assert!(err.to_string().contains("cache_pth"));
+    }
+
+    #[test]
+    fn test_unknown_language_is_error() {
+        let err = load_str("[project]\nlanguages = [\"cobol\"]\n").unwrap_err();
+        assert!(err.to_string().contains("cobol"));
+    }
+
+    #[test]
+    fn test_language_overrides() {
+        let config = load_str(
+            "[graph]\nmax_snippet_lines = 30\n\n[language.python]\nextensions = [\".pyi\"]\nmax_snippet_lines = 5\n",
+        )
+        .unwrap();
+        assert_eq!(
+            config
+                .extract_options(SupportedLanguage::Python)
+                .max_snippet_lines,
+            5
+        );
+        assert_eq!(
+            config
+                .extract_options(SupportedLanguage::Rust)
+                .max_snippet_lines,
+            30
+        );
+        assert_eq!(
+            config.extension_overrides(),
+            vec![("pyi".to_string(), SupportedLanguage::Python)]
+        );
+    }
+
+    #[test]
+    fn test_show_roundtrips() {
+        let config = load_str("[project]\nlanguages = [\"rust\"]\n").unwrap();
+        let shown = config.to_toml().unwrap();
+        let reparsed: AnchorConfig = toml::from_str(&shown).unwrap();
+        assert_eq!(reparsed.project.languages, vec!["rust"]);
+    }
+}
@@ -46,7 +46,7 @@ pub fn start_daemon(root: &Path) -> Result<()> {
 
     // Build initial graph
     info!(root = %root.display(), "building initial graph");
-    let graph = build_graph(&root);
+    let graph = build_graph(&root)?;
     let graph = Arc::new(RwLock::new(graph));
 
     // Create lock manager
@@ -366,7 +366,10 @@ fn process_request(
 
         // ─── System ────────────────────────────────────────────
         Request::Rebuild => {
-            let new_graph = build_graph(root);
+            let new_graph = match build_graph(root) {
+                Ok(graph) => graph,
+                Err(e) => return Response::error(format!("rebuild failed: {}", e)),
+            };
             let mut g = match graph.write() {
                 Ok(g) => g,
                 Err(e) => return Response::error(format!("lock error: {}", e)),
@@ -57,6 +57,22 @@ pub enum AnchorError {
     #[error("Parser init failed for {0}: {1}")]
     ParserInitError(PathBuf, String),
 
+    /// Invalid `.anchor/config.toml` (syntax, unknown key, bad value).
+    #[error("Invalid config: {0}")]
+    ConfigError(String),
+
+    /// A tag query override in `.anchor/queries/` failed to compile.
+    #[error("Invalid query {0}: {1}")]
+    QueryError(PathBuf, String),
+
+    /// A saved graph was written in another on-disk format (or none).
+    #[error("Graph cache {0} uses another format version (expected {2}); rebuild it")]
+    CacheVersionMismatch(PathBuf, Option<u32>, u32),
+
+    /// The file watcher couldn't be set up.
+    #[error("File watcher error: {0}")]
+    WatchError(#[from] notify::Error),
+
     /// tree-sitter returned None from parse (e.g., timeout or cancellation).
     #[error("tree-sitter parse failed for: {0}")]
     TreeSitterParseFailed(PathBuf),
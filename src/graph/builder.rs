@@ -1,79 +1,202 @@
 //! Graph builder — scans a directory and builds the code graph.
 //!
-//! Walks source files respecting .gitignore, parses each with tree-sitter,
-//! and assembles the complete code graph with all relationships.
+//! Walks source files accepted by the project's `SourceFilter` (.gitignore,
+//! .anchorignore, config globs), parses each with tree-sitter, and
+//! assembles the complete code graph with all relationships.
 
-use ignore::WalkBuilder;
 use rayon::prelude::*;
 use std::fs;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::sync::Mutex;
+use std::time::Instant;
+use tracing::warn;
 
 use super::engine::CodeGraph;
-use super::types::FileExtractions;
-use crate::parser::{extract_file, SupportedLanguage};
+use super::filter::SourceFilter;
+use super::report::{BuildReport, FileReport};
+use super::types::{FileExtractions, GraphUpdate};
+use crate::config::AnchorConfig;
+use crate::error::AnchorError;
+use crate::parser::{extract_embedded, extract_file_with, SupportedLanguage, TreeCache};
 
 /// Build a code graph from all source files in a directory.
 ///
-/// Respects .gitignore, walks recursively, parses all supported
-/// language files, and returns a fully connected CodeGraph.
-pub fn build_graph(root: &Path) -> CodeGraph {
-    let files: Vec<_> = WalkBuilder::new(root)
-        .hidden(true)
-        .git_ignore(true)
-        .git_global(true)
-        .git_exclude(true)
-        .build()
-        .filter_map(|entry| entry.ok())
-        .filter(|entry| entry.file_type().is_some_and(|ft| ft.is_file()))
-        .filter(|entry| SupportedLanguage::from_path(entry.path()).is_some())
-        .map(|entry| entry.into_path())
+/// Respects .gitignore, .anchorignore and `.anchor/config.toml` (scan
+/// root, languages, include/exclude globs, snippet limits), walks
+/// recursively, parses all enabled language files, and returns a fully
+/// connected CodeGraph. Fails if the config or a query override is invalid.
+pub fn build_graph(root: &Path) -> crate::error::Result<CodeGraph> {
+    Ok(build_graph_with_filter(&project_filter(root)?))
+}
+
+/// Compile the source filter for a project root from its config and
+/// query overrides.
+pub fn project_filter(root: &Path) -> crate::error::Result<SourceFilter> {
+    let config = AnchorConfig::for_project(root)?;
+    SourceFilter::for_project(root, &config)
+}
+
+/// Build a code graph from the files accepted by `filter`.
+pub fn build_graph_with_filter(filter: &SourceFilter) -> CodeGraph {
+    build_graph_with_report(filter).0
+}
+
+/// Build a code graph from the files accepted by `filter`, reporting
+/// per file how extraction went: time taken, symbols found, syntax errors,
+/// and why any file was skipped.
+pub fn build_graph_with_report(filter: &SourceFilter) -> (CodeGraph, BuildReport) {
+    let started = Instant::now();
+    let files = filter.source_files();
+
+    // `collect` keeps the (sorted) order of `files`
+    let outcomes: Vec<_> = files
+        .par_iter()
+        .map(|path| {
+            let started = Instant::now();
+            let extraction = extract_path(path, filter, None);
+            (path, started.elapsed(), extraction)
+        })
         .collect();
+
+    let mut extractions = Vec::with_capacity(outcomes.len());
+    let mut report = BuildReport::default();
+    for (path, elapsed, extraction) in outcomes {
+        let language = filter
+            .language_for(path)
+            .map(|lang| lang.config_name())
+            .or_else(|| filter.host_for(path).map(|format| format.name()))
+            .unwrap_or_default();
+        let mut file = FileReport {
+            path: path.clone(),
+            language: language.to_string(),
+            elapsed_us: elapsed.as_micros() as u64,
+            symbols: 0,
+            syntax_errors: Vec::new(),
+            skipped: None,
+        };
+        match extraction {
+            Ok(extraction) => {
+                file.symbols = extraction.symbols.len();
+                file.syntax_errors = extraction.syntax_errors.clone();
+                extractions.push(extraction);
+            }
+            Err(e) => {
+                warn!(file = %path.display(), error = %e, "skipping file");
+                file.skipped = Some(e.to_string());
+            }
+        }
+        report.files.push(file);
+    }
+
+    let mut graph = CodeGraph::new();
+    graph.build_from_extractions(extractions);
+    report.elapsed_ms = started.elapsed().as_millis() as u64;
+
+    (graph, report)
+}
+
+/// Read and extract a set of files in parallel, incrementally from their
+/// trees in `trees`.
+///
+/// Returns the successful extractions and the paths that could not be
+/// read or parsed. Languages and snippet limits come from the filter's
+/// config. Needs no access to the graph, so incremental updates run it
+/// before taking the graph lock.
+pub fn extract_files(
+    files: &[PathBuf],
+    filter: &SourceFilter,
+    trees: &TreeCache,
+) -> (Vec<FileExtractions>, Vec<PathBuf>) {
     let extractions: Mutex<Vec<FileExtractions>> = Mutex::new(Vec::with_capacity(files.len()));
+    let failed: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
 
-    files.par_iter().for_each(|file_path| {
-        if let Ok(source) = fs::read_to_string(file_path) {
-            if let Ok(extraction) = extract_file(file_path, &source) {
+    files.par_iter().for_each(
+        |file_path| match extract_path(file_path, filter, Some(trees)) {
+            Ok(extraction) => {
                 if let Ok(mut exts) = extractions.lock() {
                     exts.push(extraction);
                 }
             }
-        }
-    });
+            Err(_) => {
+                if let Ok(mut failed) = failed.lock() {
+                    failed.push(file_path.clone());
+                }
+            }
+        },
+    );
 
-    let extractions = extractions.into_inner().unwrap_or_default();
+    // Deterministic order regardless of thread scheduling
+    let mut extractions = extractions.into_inner().unwrap_or_default();
+    extractions.sort_by(|a, b| a.file_path.cmp(&b.file_path));
+    let mut failed = failed.into_inner().unwrap_or_default();
+    failed.sort();
 
-    let mut graph = CodeGraph::new();
-    graph.build_from_extractions(extractions);
+    (extractions, failed)
+}
 
-    graph
+/// Read and extract one file using the language, query and options from
+/// `filter`, incrementally when its last parse is in `trees`; host formats
+/// (Vue, Markdown, ...) extract their embedded code.
+fn extract_path(
+    path: &Path,
+    filter: &SourceFilter,
+    trees: Option<&TreeCache>,
+) -> crate::error::Result<FileExtractions> {
+    let Some(lang) = filter.language_for(path) else {
+        let format = filter
+            .host_for(path)
+            .ok_or_else(|| AnchorError::UnsupportedLanguage(path.to_path_buf()))?;
+        let source = fs::read_to_string(path)?;
+        return extract_embedded(path, &source, format, |lang| {
+            filter.language_enabled(lang).then(|| {
+                (
+                    filter.queries().get(lang),
+                    filter.config().extract_options(lang),
+                )
+            })
+        });
+    };
+    let source = fs::read_to_string(path)?;
+    let lang = lang.refine_for_source(path, &source);
+    let query = filter.queries().get(lang);
+    let options = filter.config().extract_options(lang);
+    match trees {
+        Some(trees) => trees.extract(path, &source, query, &options),
+        None => extract_file_with(path, &source, query, &options),
+    }
 }
 
+/// Re-extract a single file, incrementally from its tree in `trees`, and
+/// update the graph, including calls into it from other files. If the
+/// file can't be read or parsed it is removed from the graph and the
+/// error returned.
 pub fn rebuild_file(
     graph: &mut CodeGraph,
     file_path: &Path,
+    filter: &SourceFilter,
+    trees: &TreeCache,
 ) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
-    graph.remove_file(file_path);
-    let source = fs::read_to_string(file_path)?;
-    let extraction = extract_file(file_path, &source)?;
-    graph.build_from_extractions(vec![extraction]);
+    let extraction = match extract_path(file_path, filter, Some(trees)) {
+        Ok(extraction) => extraction,
+        Err(e) => {
+            graph.remove_file(file_path);
+            return Err(e.into());
+        }
+    };
+    graph.apply_update(GraphUpdate {
+        changed: vec![extraction],
+        ..GraphUpdate::default()
+    });
     Ok(())
 }
 
 /// Get statistics about what files would be parsed in a directory.
-pub fn scan_stats(root: &Path) -> ScanStats {
+pub fn scan_stats(root: &Path) -> crate::error::Result<ScanStats> {
     let mut stats = ScanStats::default();
+    let filter = project_filter(root)?;
 
-    for entry in WalkBuilder::new(root)
-        .hidden(true)
-        .git_ignore(true)
-        .git_global(true)
-        .git_exclude(true)
-        .build()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_some_and(|ft| ft.is_file()))
-    {
-        if let Some(lang) = SupportedLanguage::from_path(entry.path()) {
+    for path in filter.source_files() {
+        if let Some(lang) = filter.language_for(&path) {
             stats.total_files += 1;
             match lang {
                 SupportedLanguage::Rust => stats.rust_files += 1,
@@ -85,7 +208,7 @@ pub fn scan_stats(root: &Path) -> ScanStats {
         }
     }
 
-    stats
+    Ok(stats)
 }
 
 #[derive(Debug, Clone, Default)]
@@ -106,3 +229,290 @@ impl std::fmt::Display for ScanStats {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::test_support::{assert_matches_fresh_build, snapshot};
+    use tempfile::tempdir;
+
+    /// Minimal xorshift PRNG so the property test is reproducible
+    /// without an extra dependency.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            self.0
+        }
+
+        fn below(&mut self, n: usize) -> usize {
+            (self.next() % n as u64) as usize
+        }
+    }
+
+    /// Languages the property test mixes: Rust calls by name, TypeScript
+    /// and Python also import modules by relative path, and Go types
+    /// implement interfaces through methods declared in any file.
+    const EXTENSIONS: &[&str] = &["rs", "ts", "py", "go"];
+
+    /// A random file drawing function and module names from small shared
+    /// pools, so the same names are defined, called and imported across
+    /// files, including modules that don't exist (yet).
+    fn random_source(rng: &mut Rng, ext: &str) -> String {
+        let mut src = String::new();
+        for _ in 0..rng.below(3) {
+            let (module, name) = (rng.below(8), rng.below(6));
+            match ext {
+                "ts" => src.push_str(&format!("import {{ f{} }} from './m{}';\n", name, module)),
+                "py" => src.push_str(&format!("from .m{} import f{}\n", module, name)),
+                _ => {}
+            }
+        }
+        if ext == "go" {
+            src.push_str("package main\n\n");
+            for _ in 0..rng.below(4) {
+                let (ty, method) = (rng.below(3), rng.below(3));
+                let item = match rng.below(3) {
+                    0 => format!("type T{} struct{{}}\n\n", ty),
+                    1 => format!(
+                        "type I{} interface {{\n\tM{}()\n\tM{}()\n}}\n\n",
+                        ty,
+                        method,
+                        rng.below(3)
+                    ),
+                    _ => format!("func (t *T{}) M{}() {{}}\n\n", ty, method),
+                };
+                src.push_str(&item);
+            }
+        }
+        for _ in 0..1 + rng.below(4) {
+            let name = format!("f{}", rng.below(6));
+            let calls: Vec<String> = (0..rng.below(4))
+                .map(|_| format!("f{}()", rng.below(6)))
+                .collect();
+            let src_fn = match ext {
+                "ts" => format!(
+                    "export function {}() {{\n{}\n}}\n\n",
+                    name,
+                    statements(&calls, ";")
+                ),
+                "py" => format!("def {}():\n    pass\n{}\n\n", name, statements(&calls, "")),
+                "go" => format!("func {}() {{\n{}\n}}\n\n", name, statements(&calls, "")),
+                _ => format!("fn {}() {{\n{}\n}}\n\n", name, statements(&calls, ";")),
+            };
+            src.push_str(&src_fn);
+        }
+        src
+    }
+
+    fn statements(calls: &[String], terminator: &str) -> String {
+        calls
+            .iter()
+            .map(|call| format!("    {}{}", call, terminator))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn test_incremental_updates_match_fresh_build() {
+        for seed in 1..=20u64 {
+            let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+            let dir = tempdir().unwrap();
+            let root = dir.path();
+            let mut files: Vec<PathBuf> = Vec::new();
+            let mut next_file = 0;
+            // New files take the next module number, in a random language
+            let mut new_path = |rng: &mut Rng| {
+                let path = root.join(format!(
+                    "m{}.{}",
+                    next_file,
+                    EXTENSIONS[rng.below(EXTENSIONS.len())]
+                ));
+                next_file += 1;
+                path
+            };
+            let ext = |path: &Path| path.extension().unwrap().to_string_lossy().to_string();
+
+            for _ in 0..3 {
+                let path = new_path(&mut rng);
+                fs::write(&path, random_source(&mut rng, &ext(&path))).unwrap();
+                files.push(path);
+            }
+            let mut graph = build_graph(root).unwrap();
+            let filter = project_filter(root).unwrap();
+            let trees = TreeCache::default();
+
+            for step in 0..12 {
+                let mut update = GraphUpdate::default();
+                match rng.below(4) {
+                    // Modify
+                    0 if !files.is_empty() => {
+                        let path = files[rng.below(files.len())].clone();
+                        fs::write(&path, random_source(&mut rng, &ext(&path))).unwrap();
+                        update.changed = extract_files(&[path], &filter, &trees).0;
+                    }
+                    // Delete
+                    1 if files.len() > 1 => {
+                        let path = files.remove(rng.below(files.len()));
+                        fs::remove_file(&path).unwrap();
+                        update.removed.push(path);
+                    }
+                    // Rename (keeping the language)
+                    2 if !files.is_empty() => {
+                        let i = rng.below(files.len());
+                        let renamed = new_path(&mut rng).with_extension(ext(&files[i]));
+                        fs::rename(&files[i], &renamed).unwrap();
+                        let old = std::mem::replace(&mut files[i], renamed.clone());
+                        let ext = extract_files(&[renamed], &filter, &trees).0.remove(0);
+                        update.renamed.push((old, ext));
+                    }
+                    // Add
+                    _ => {
+                        let path = new_path(&mut rng);
+                        fs::write(&path, random_source(&mut rng, &ext(&path))).unwrap();
+                        files.push(path.clone());
+                        update.changed = extract_files(&[path], &filter, &trees).0;
+                    }
+                }
+                graph.apply_update(update);
+
+                assert_eq!(
+                    snapshot(&graph),
+                    snapshot(&build_graph(root).unwrap()),
+                    "seed {} diverged at step {}",
+                    seed,
+                    step
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_rebuild_file_relinks_callers_in_other_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("main.rs"), "fn main() {\n    login();\n}\n").unwrap();
+        fs::write(root.join("auth.rs"), "fn helper() {}\n").unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        assert!(graph.search("main", 1)[0].calls.is_empty());
+
+        // login appears in another file: main's call now resolves
+        fs::write(root.join("auth.rs"), "fn helper() {}\n\nfn login() {}\n").unwrap();
+        rebuild_file(&mut graph, &root.join("auth.rs"), &filter, &trees).unwrap();
+        assert_eq!(graph.search("main", 1)[0].calls[0].name, "login");
+
+        // login moves lines: the edge follows the new node
+        fs::write(root.join("auth.rs"), "\n\nfn login() {}\n").unwrap();
+        rebuild_file(&mut graph, &root.join("auth.rs"), &filter, &trees).unwrap();
+        let calls = &graph.search("main", 1)[0].calls;
+        assert_eq!(calls.len(), 1);
+        assert_eq!(calls[0].line, 3);
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_query_override_replaces_bundled_rules() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("app.py"),
+            "class Service:\n    def run(self):\n        pass\n",
+        )
+        .unwrap();
+        assert_eq!(build_graph(root).unwrap().search("run", 1).len(), 1);
+
+        fs::create_dir_all(root.join(".anchor/queries")).unwrap();
+        fs::write(
+            root.join(".anchor/queries/python.scm"),
+            "; anchor: replace\n\
+             (class_definition name: (identifier) @name) @definition.class",
+        )
+        .unwrap();
+        let graph = build_graph(root).unwrap();
+        assert!(graph.search("run", 1).is_empty());
+        assert_eq!(graph.search("Service", 1).len(), 1);
+    }
+
+    #[test]
+    fn test_invalid_settings_fail_the_build() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("app.py"), "def run():\n    pass\n").unwrap();
+        fs::create_dir_all(root.join(".anchor/queries")).unwrap();
+
+        fs::write(
+            root.join(".anchor/config.toml"),
+            "[project]\nlanguages = [\"cobol\"]\n",
+        )
+        .unwrap();
+        assert!(matches!(
+            build_graph(root),
+            Err(AnchorError::ConfigError(_))
+        ));
+
+        fs::write(root.join(".anchor/config.toml"), "").unwrap();
+        fs::write(
+            root.join(".anchor/queries/python.scm"),
+            "(no_such_node) @name",
+        )
+        .unwrap();
+        assert!(matches!(
+            build_graph(root),
+            Err(AnchorError::QueryError(..))
+        ));
+    }
+
+    #[test]
+    fn test_build_report() {
+        use crate::graph::report::BuildReport;
+
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("ok.rs"), "fn fine() {}\nfn also_fine() {}\n").unwrap();
+        fs::write(
+            root.join("broken.rs"),
+            "fn good() {}\n\nfn bad( {\n    let x = ;\n}\n",
+        )
+        .unwrap();
+        fs::write(root.join("latin1.py"), b"def caf\xe9():\n    pass\n").unwrap();
+
+        let filter = project_filter(root).unwrap();
+        let (graph, report) = build_graph_with_report(&filter);
+        assert_eq!(report.files.len(), 3);
+        assert_eq!(graph.stats().file_count, 2);
+
+        let skipped: Vec<_> = report.skipped().collect();
+        assert_eq!(skipped.len(), 1);
+        assert_eq!(skipped[0].path, root.join("latin1.py"));
+        assert!(skipped[0].skipped.as_deref().unwrap().contains("UTF-8"));
+
+        let with_errors = report.with_syntax_errors();
+        assert_eq!(with_errors.len(), 1);
+        assert_eq!(with_errors[0].path, root.join("broken.rs"));
+        assert!(with_errors[0]
+            .syntax_errors
+            .iter()
+            .all(|&line| (3..=4).contains(&line)));
+        assert!(!graph.search("good", 1).is_empty());
+
+        let coverage = report.coverage();
+        let rust = coverage.iter().find(|c| c.language == "rust").unwrap();
+        assert_eq!((rust.files, rust.indexed, rust.with_errors), (2, 2, 1));
+        let python = coverage.iter().find(|c| c.language == "python").unwrap();
+        assert_eq!((python.files, python.indexed, python.skipped), (1, 0, 1));
+
+        let path = root.join("report.json");
+        report.save(&path).unwrap();
+        let saved: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        assert_eq!(saved["languages"].as_array().unwrap().len(), 2);
+        let loaded = BuildReport::load(&path).unwrap();
+        assert_eq!(loaded.files.len(), 3);
+        assert_eq!(loaded.coverage(), coverage);
+    }
+}
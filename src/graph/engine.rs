@@ -3,15 +3,19 @@
 //! Uses petgraph to store code relationships and provides
 //! query methods for searching and traversing the graph.
 
-use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::graph::{DiGraph, EdgeIndex, NodeIndex};
 use petgraph::visit::EdgeRef;
 use petgraph::Direction;
 use serde::{Deserialize, Serialize};
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
 use std::path::{Path, PathBuf};
 use tracing::{debug, info};
 
 use super::types::*;
+use crate::parser::linking::{is_type_kind, LinkRules};
+
+/// A symbol's identity within its file: (kind, name, occurrence ordinal).
+type SymbolKey = (NodeKind, String, u32);
 
 /// The main code graph — holds all nodes, edges, and indexes for fast lookup.
 #[derive(Clone)]
@@ -24,6 +28,20 @@ pub struct CodeGraph {
     symbol_index: HashMap<String, Vec<NodeIndex>>,
     /// Index: (file_path, symbol_name) -> node index (for unique symbol resolution).
     qualified_index: HashMap<(PathBuf, String), NodeIndex>,
+    /// Index: stable node id -> node index (live nodes only).
+    id_index: HashMap<NodeId, NodeIndex>,
+    /// Extracted calls per file, kept so call edges can be re-resolved
+    /// when the symbols they point at change.
+    calls: HashMap<PathBuf, Vec<ExtractedCall>>,
+    /// Index: callee name -> files containing a call to it.
+    callee_index: HashMap<String, HashSet<PathBuf>>,
+    /// Relations per file, kept (like calls) so their edges can be
+    /// re-resolved when what they point at changes.
+    relations: HashMap<PathBuf, Vec<ExtractedRelation>>,
+    /// Index: relation target -> files with a relation to it.
+    relation_index: HashMap<RelationTarget, HashSet<PathBuf>>,
+    /// Index: directory -> indexed files directly inside it.
+    dir_index: HashMap<PathBuf, BTreeSet<PathBuf>>,
 }
 
 impl CodeGraph {
@@ -34,6 +52,12 @@ impl CodeGraph {
             file_index: HashMap::new(),
             symbol_index: HashMap::new(),
             qualified_index: HashMap::new(),
+            id_index: HashMap::new(),
+            calls: HashMap::new(),
+            callee_index: HashMap::new(),
+            relations: HashMap::new(),
+            relation_index: HashMap::new(),
+            dir_index: HashMap::new(),
         }
     }
 
@@ -56,16 +80,16 @@ impl CodeGraph {
             // Un-remove if it was soft-deleted
             if let Some(node) = self.graph.node_weight_mut(idx) {
                 node.removed = false;
+                self.id_index.insert(node.id, idx);
             }
+            self.index_dir_entry(&path);
             return idx;
         }
-        let data = NodeData::new_file(path.clone());
-        let idx = self.graph.add_node(data);
-        self.file_index.insert(path, idx);
-        idx
+        self.insert_node(NodeData::new_file(path))
     }
 
-    /// Add a symbol node to the graph. Returns the node index.
+    /// Add a symbol node to the graph, taking the next free ordinal for
+    /// its (file, kind, name) identity. Returns the node index.
     pub fn add_symbol(
         &mut self,
         name: String,
@@ -75,23 +99,74 @@ impl CodeGraph {
         line_end: usize,
         code_snippet: String,
     ) -> NodeIndex {
-        let data = NodeData::new_symbol(
-            name.clone(),
-            kind,
-            file_path.clone(),
-            line_start,
-            line_end,
-            code_snippet,
-        );
+        let mut data =
+            NodeData::new_symbol(name, kind, file_path, line_start, line_end, code_snippet);
+
+        let mut ordinal = 0;
+        while self.id_index.contains_key(&data.id) {
+            ordinal += 1;
+            data.id = NodeId::for_symbol(&data.file_path, data.kind, &data.name, ordinal);
+        }
+
+        self.insert_node(data)
+    }
+
+    /// Add an extracted symbol node whose ordinal is its position among
+    /// the same-named symbols of its file's extraction, so its id doesn't
+    /// depend on which nodes of the file happen to be live.
+    fn add_extracted_symbol(&mut self, mut data: NodeData, ordinal: u32) -> NodeIndex {
+        data.id = NodeId::for_symbol(&data.file_path, data.kind, &data.name, ordinal);
+        self.insert_node(data)
+    }
+
+    /// Insert a node as-is (keeping its id) and register it in the indexes.
+    /// Removed nodes are stored but not indexed.
+    pub(crate) fn insert_node(&mut self, data: NodeData) -> NodeIndex {
+        let removed = data.removed;
+        let id = data.id;
+        let key = (data.file_path.clone(), data.name.clone());
+        let is_file = data.kind == NodeKind::File;
         let idx = self.graph.add_node(data);
 
-        // Update indexes
-        self.symbol_index.entry(name.clone()).or_default().push(idx);
-        self.qualified_index.insert((file_path, name), idx);
+        if removed {
+            return idx;
+        }
+        if is_file {
+            self.index_dir_entry(&key.0);
+            self.file_index.insert(key.0, idx);
+        } else {
+            self.symbol_index
+                .entry(key.1.clone())
+                .or_default()
+                .push(idx);
+            // First definition wins, so a class keeps its name over a
+            // same-named constructor or overload that follows it
+            self.qualified_index.entry(key).or_insert(idx);
+        }
+        self.id_index.insert(id, idx);
 
         idx
     }
 
+    fn index_dir_entry(&mut self, file: &Path) {
+        if let Some(dir) = file.parent() {
+            self.dir_index
+                .entry(dir.to_path_buf())
+                .or_default()
+                .insert(file.to_path_buf());
+        }
+    }
+
+    /// Look up a live node by its stable id.
+    pub fn node_by_id(&self, id: NodeId) -> Option<&NodeData> {
+        self.id_index.get(&id).map(|&idx| &self.graph[idx])
+    }
+
+    /// Resolve a stable id to the node's current index.
+    pub fn index_of(&self, id: NodeId) -> Option<NodeIndex> {
+        self.id_index.get(&id).copied()
+    }
+
     // ─── Edge Operations ────────────────────────────────────────
 
     /// Add an edge between two nodes.
@@ -115,6 +190,30 @@ impl CodeGraph {
             }
         }
 
+        // Qualified names (`com.acme.UserService#findById`) match in full
+        // or by a trailing `.`/`#`-separated suffix
+        if results.is_empty() {
+            if let Some((_, member)) = query.rsplit_once(['.', '#', ':']) {
+                let matches = |qualified: &str| {
+                    qualified
+                        .strip_suffix(query)
+                        .is_some_and(|rest| rest.is_empty() || rest.ends_with(['.', '#', ':']))
+                };
+                for &idx in self.symbol_index.get(member).into_iter().flatten() {
+                    if results.len() >= limit {
+                        break;
+                    }
+                    if self.graph[idx]
+                        .qualified_name
+                        .as_deref()
+                        .is_some_and(matches)
+                    {
+                        results.extend(self.build_search_result(idx));
+                    }
+                }
+            }
+        }
+
         // If no exact match, fuzzy search
         if results.is_empty() {
             let mut scored: Vec<(usize, NodeIndex)> = self
@@ -222,10 +321,12 @@ impl CodeGraph {
                         symbol_indexes.push(edge.target());
                         let node = &self.graph[edge.target()];
                         result.symbols.push(SymbolInfo {
+                            id: node.id,
                             name: node.name.clone(),
                             kind: node.kind,
                             file: node.file_path.clone(),
                             line: node.line_start,
+                            range: node.range,
                             code: node.code_snippet.clone(),
                         });
                     }
@@ -330,10 +431,12 @@ impl CodeGraph {
 
             if node.kind != NodeKind::File && result.symbols.len() < MAX_SYMBOLS {
                 result.symbols.push(SymbolInfo {
+                    id: node.id,
                     name: node.name.clone(),
                     kind: node.kind,
                     file: node.file_path.clone(),
                     line: node.line_start,
+                    range: node.range,
                     code: node.code_snippet.clone(),
                 });
             }
@@ -406,16 +509,7 @@ impl CodeGraph {
                     if !self.is_live(source_idx) {
                         continue;
                     }
-                    let source = &self.graph[source_idx];
-                    let edge_data = edge.weight();
-
-                    deps.push(DependencyInfo {
-                        symbol: source.name.clone(),
-                        kind: source.kind,
-                        file: source.file_path.clone(),
-                        line: source.line_start,
-                        relationship: edge_data.kind,
-                    });
+                    deps.push(self.dependency_info(source_idx, edge.weight()));
                 }
             }
         }
@@ -423,6 +517,19 @@ impl CodeGraph {
         deps
     }
 
+    /// What depends on the node with `id` through edges of one of `kinds`.
+    /// Unlike `dependents`, other symbols sharing its name don't count.
+    pub fn dependents_of(&self, id: NodeId, kinds: &[EdgeKind]) -> Vec<DependencyInfo> {
+        let Some(idx) = self.index_of(id).filter(|&idx| self.is_live(idx)) else {
+            return Vec::new();
+        };
+        self.graph
+            .edges_directed(idx, Direction::Incoming)
+            .filter(|edge| kinds.contains(&edge.weight().kind) && self.is_live(edge.source()))
+            .map(|edge| self.dependency_info(edge.source(), edge.weight()))
+            .collect()
+    }
+
     /// Find what a given symbol depends on (what it calls, what it references).
     pub fn dependencies(&self, symbol_name: &str) -> Vec<DependencyInfo> {
         let mut deps = Vec::new();
@@ -437,16 +544,7 @@ impl CodeGraph {
                     if !self.is_live(target_idx) {
                         continue;
                     }
-                    let target = &self.graph[target_idx];
-                    let edge_data = edge.weight();
-
-                    deps.push(DependencyInfo {
-                        symbol: target.name.clone(),
-                        kind: target.kind,
-                        file: target.file_path.clone(),
-                        line: target.line_start,
-                        relationship: edge_data.kind,
-                    });
+                    deps.push(self.dependency_info(target_idx, edge.weight()));
                 }
             }
         }
@@ -454,6 +552,22 @@ impl CodeGraph {
         deps
     }
 
+    /// The node at `idx`, related by `edge`.
+    fn dependency_info(&self, idx: NodeIndex, edge: &EdgeData) -> DependencyInfo {
+        let node = &self.graph[idx];
+        DependencyInfo {
+            id: node.id,
+            symbol: node.name.clone(),
+            kind: node.kind,
+            file: node.file_path.clone(),
+            line: node.line_start,
+            range: node.range,
+            relationship: edge.kind,
+            call_sites: edge.call_sites.clone(),
+            reference_sites: edge.reference_sites.clone(),
+        }
+    }
+
     /// Get all symbols defined in a specific file.
     pub fn symbols_in_file(&self, path: &Path) -> Vec<&NodeData> {
         if let Some(&file_idx) = self.file_index.get(path) {
@@ -536,9 +650,11 @@ impl CodeGraph {
             .map(|e| {
                 let target = &self.graph[e.target()];
                 SymbolRef {
+                    id: target.id,
                     name: target.name.clone(),
                     file: target.file_path.clone(),
                     line: target.line_start,
+                    range: target.range,
                 }
             })
             .collect();
@@ -551,9 +667,11 @@ impl CodeGraph {
             .map(|e| {
                 let source = &self.graph[e.source()];
                 SymbolRef {
+                    id: source.id,
                     name: source.name.clone(),
                     file: source.file_path.clone(),
                     line: source.line_start,
+                    range: source.range,
                 }
             })
             .collect();
@@ -573,11 +691,16 @@ impl CodeGraph {
         };
 
         Some(SearchResult {
+            id: node.id,
             symbol: node.name.clone(),
+            qualified_name: node.qualified_name.clone(),
+            metadata: node.metadata.clone(),
             kind: node.kind,
             file: node.file_path.clone(),
             line_start: node.line_start,
             line_end: node.line_end,
+            range: node.range,
+            name_range: node.name_range,
             code: node.code_snippet.clone(),
             calls,
             called_by,
@@ -590,37 +713,66 @@ impl CodeGraph {
     /// Build the graph from a set of file extractions.
     /// This is the main entry point for populating the graph.
     pub fn build_from_extractions(&mut self, extractions: Vec<FileExtractions>) {
+        self.ingest(extractions);
+        self.link_implicit_interfaces(None);
+    }
+
+    /// Add the nodes and edges for a set of extractions. Structural
+    /// `Implements` edges are left to the caller.
+    fn ingest(&mut self, extractions: Vec<FileExtractions>) {
         debug!(
             file_count = extractions.len(),
             "ingesting extractions into graph"
         );
         // Phase 1: Add all file nodes and symbol nodes
+        let mut symbol_nodes: Vec<Vec<NodeIndex>> = Vec::with_capacity(extractions.len());
         for extraction in &extractions {
             let file_idx = self.add_file(extraction.file_path.clone());
 
+            // Occurrences so far of each (kind, name) in this extraction
+            let mut seen: HashMap<(NodeKind, &str), u32> = HashMap::new();
+            let mut next_ordinal = |kind, name| {
+                let count = seen.entry((kind, name)).or_insert(0);
+                *count += 1;
+                *count - 1
+            };
+
+            let mut nodes = Vec::with_capacity(extraction.symbols.len());
             for symbol in &extraction.symbols {
-                let sym_idx = self.add_symbol(
-                    symbol.name.clone(),
-                    symbol.kind,
-                    extraction.file_path.clone(),
-                    symbol.line_start,
-                    symbol.line_end,
-                    symbol.code_snippet.clone(),
+                let sym_idx = self.add_extracted_symbol(
+                    NodeData::new_symbol(
+                        symbol.name.clone(),
+                        symbol.kind,
+                        extraction.file_path.clone(),
+                        symbol.line_start,
+                        symbol.line_end,
+                        symbol.code_snippet.clone(),
+                    ),
+                    next_ordinal(symbol.kind, symbol.name.as_str()),
                 );
+                self.graph[sym_idx].qualified_name = symbol.qualified_name.clone();
+                self.graph[sym_idx].metadata = symbol.metadata.clone();
+                self.graph[sym_idx].range = symbol.range;
+                self.graph[sym_idx].name_range = symbol.name_range;
 
                 // File DEFINES Symbol
                 self.add_edge(file_idx, sym_idx, EdgeKind::Defines);
+                nodes.push(sym_idx);
             }
+            symbol_nodes.push(nodes);
 
             // Add import nodes
-            for import in &extraction.imports {
-                let import_idx = self.add_symbol(
-                    import.path.clone(),
-                    NodeKind::Import,
-                    extraction.file_path.clone(),
-                    import.line,
-                    import.line,
-                    String::new(),
+            for import in distinct_imports(&extraction.imports) {
+                let import_idx = self.add_extracted_symbol(
+                    NodeData::new_symbol(
+                        import.path.clone(),
+                        NodeKind::Import,
+                        extraction.file_path.clone(),
+                        import.line,
+                        import.line,
+                        String::new(),
+                    ),
+                    next_ordinal(NodeKind::Import, import.path.as_str()),
                 );
                 self.add_edge(file_idx, import_idx, EdgeKind::Imports);
             }
@@ -629,37 +781,79 @@ impl CodeGraph {
         // Phase 2: Resolve cross-references (calls)
         for extraction in &extractions {
             for call in &extraction.calls {
-                // Find the caller node
-                let caller_key = (extraction.file_path.clone(), call.caller.clone());
-                let callee_nodes = self.symbol_index.get(&call.callee).cloned();
-
-                if let Some(&caller_idx) = self.qualified_index.get(&caller_key) {
-                    if let Some(callee_indexes) = callee_nodes {
-                        // Connect to the first matching callee
-                        // (in v0, we take the first match — later versions can be smarter)
-                        if let Some(&callee_idx) = callee_indexes.first() {
-                            self.add_edge(caller_idx, callee_idx, EdgeKind::Calls);
-                        }
-                    }
-                }
+                self.link_call(&extraction.file_path, call);
             }
+            self.record_calls(&extraction.file_path, extraction.calls.clone());
         }
 
-        // Phase 3: Resolve contains relationships (parent -> child)
-        for extraction in &extractions {
-            for symbol in &extraction.symbols {
-                if let Some(ref parent_name) = symbol.parent {
-                    let child_key = (extraction.file_path.clone(), symbol.name.clone());
-                    let parent_key = (extraction.file_path.clone(), parent_name.clone());
-
-                    if let (Some(&parent_idx), Some(&child_idx)) = (
-                        self.qualified_index.get(&parent_key),
-                        self.qualified_index.get(&child_key),
-                    ) {
-                        self.add_edge(parent_idx, child_idx, EdgeKind::Contains);
-                    }
+        // Phase 3: Resolve contains relationships (parent -> child). A
+        // parent that isn't in the same file (a Go receiver type declared
+        // elsewhere in the package) becomes a relation.
+        let mut relations: Vec<Vec<ExtractedRelation>> = Vec::with_capacity(extractions.len());
+        for (extraction, nodes) in extractions.iter().zip(&symbol_nodes) {
+            let mut file_relations = extraction.relations.clone();
+            let mut by_name: HashMap<&str, Vec<usize>> = HashMap::new();
+            for (i, symbol) in extraction.symbols.iter().enumerate() {
+                by_name.entry(symbol.name.as_str()).or_default().push(i);
+            }
+            for (i, symbol) in extraction.symbols.iter().enumerate() {
+                let Some(ref parent_name) = symbol.parent else {
+                    continue;
+                };
+                // Prefer a parent whose lines enclose the child, then a type;
+                // one elsewhere in the file must be a type or module
+                let encloses = |p: &ExtractedSymbol| {
+                    p.line_start <= symbol.line_start && p.line_end >= symbol.line_end
+                };
+                let parent = by_name
+                    .get(parent_name.as_str())
+                    .into_iter()
+                    .flatten()
+                    .copied()
+                    .filter(|&j| j != i)
+                    .filter(|&j| {
+                        let p = &extraction.symbols[j];
+                        encloses(p) || is_type_kind(p.kind) || p.kind == NodeKind::Module
+                    })
+                    .min_by_key(|&j| {
+                        let p = &extraction.symbols[j];
+                        (!encloses(p), !is_type_kind(p.kind), j)
+                    });
+                match parent {
+                    Some(j) => self.add_edge(nodes[j], nodes[i], EdgeKind::Contains),
+                    None => file_relations.push(ExtractedRelation {
+                        kind: EdgeKind::Contains,
+                        source: symbol.name.clone(),
+                        target: RelationTarget::Symbol(parent_name.clone()),
+                        line: symbol.line_start,
+                        range: SourceRange::default(),
+                        reference: None,
+                    }),
                 }
             }
+            file_relations.extend(
+                distinct_imports(&extraction.imports)
+                    .into_iter()
+                    .filter_map(|import| {
+                        Some(ExtractedRelation {
+                            kind: EdgeKind::Imports,
+                            source: import.path.clone(),
+                            target: import.target()?,
+                            line: import.line,
+                            range: SourceRange::default(),
+                            reference: None,
+                        })
+                    }),
+            );
+            relations.push(file_relations);
+        }
+
+        // Phase 4: Resolve relations (possibly into other files)
+        for (extraction, file_relations) in extractions.iter().zip(relations) {
+            for relation in &file_relations {
+                self.link_relation(&extraction.file_path, relation, false);
+            }
+            self.record_relations(&extraction.file_path, file_relations);
         }
     }
 
@@ -669,10 +863,12 @@ impl CodeGraph {
     pub fn remove_file(&mut self, path: &Path) {
         if let Some(&file_idx) = self.file_index.get(path) {
             debug!(file = %path.display(), "removing file from graph");
-            // Collect ALL child nodes (DEFINES + IMPORTS edges from file)
+            // Collect ALL child nodes (DEFINES + IMPORTS edges from file),
+            // not the symbols of other files it exports
             let child_nodes: Vec<NodeIndex> = self
                 .graph
                 .edges_directed(file_idx, Direction::Outgoing)
+                .filter(|e| matches!(e.weight().kind, EdgeKind::Defines | EdgeKind::Imports))
                 .map(|e| e.target())
                 .collect();
 
@@ -682,6 +878,9 @@ impl CodeGraph {
                     let name = node.name.clone();
                     let file = node.file_path.clone();
                     node.removed = true;
+                    if self.id_index.get(&node.id) == Some(&node_idx) {
+                        self.id_index.remove(&node.id);
+                    }
 
                     // Remove from symbol_index
                     if let Some(indexes) = self.symbol_index.get_mut(&name) {
@@ -699,54 +898,720 @@ impl CodeGraph {
             // Soft-delete the file node itself
             if let Some(file_node) = self.graph.node_weight_mut(file_idx) {
                 file_node.removed = true;
+                self.id_index.remove(&file_node.id);
             }
             self.file_index.remove(path);
+            if let Some(files) = path.parent().and_then(|dir| self.dir_index.get_mut(dir)) {
+                files.remove(path);
+            }
+            self.record_calls(path, Vec::new());
+            self.record_relations(path, Vec::new());
+
+            // Drop every edge touching a removed node. Descending order so
+            // petgraph's swap-remove never moves an edge we still need.
+            let mut dead_edges: Vec<_> = child_nodes
+                .iter()
+                .chain(std::iter::once(&file_idx))
+                .flat_map(|&n| {
+                    self.graph
+                        .edges_directed(n, Direction::Outgoing)
+                        .chain(self.graph.edges_directed(n, Direction::Incoming))
+                        .map(|e| e.id())
+                        .collect::<Vec<_>>()
+                })
+                .collect();
+            dead_edges.sort();
+            dead_edges.dedup();
+            for edge in dead_edges.into_iter().rev() {
+                self.graph.remove_edge(edge);
+            }
         }
     }
 
-    /// Rebuild the graph from scratch, removing all soft-deleted nodes.
-    /// Call this periodically or after many incremental updates to reclaim memory.
-    pub fn compact(&mut self) {
-        info!("compacting graph — rebuilding without soft-deleted nodes");
-        // Collect all live extractions
-        let mut live_files: HashMap<PathBuf, Vec<NodeIndex>> = HashMap::new();
-        for idx in self.graph.node_indices() {
+    // ─── Incremental Updates ────────────────────────────────────
+
+    /// Apply a batch of file changes in one pass.
+    ///
+    /// Everything here is in-memory work on pre-extracted data, so callers
+    /// can do the slow parsing first and hold a write lock only for this.
+    pub fn apply_update(&mut self, update: GraphUpdate) {
+        debug!(
+            removed = update.removed.len(),
+            changed = update.changed.len(),
+            renamed = update.renamed.len(),
+            "applying graph update"
+        );
+
+        // Names defined by touched files, before and after: calls to these
+        // from untouched files may now resolve differently.
+        let mut touched_files: HashSet<PathBuf> = update.removed.iter().cloned().collect();
+        touched_files.extend(update.renamed.iter().map(|(old, _)| old.clone()));
+        touched_files.extend(update.changed.iter().map(|e| e.file_path.clone()));
+        touched_files.extend(update.renamed.iter().map(|(_, e)| e.file_path.clone()));
+        let mut touched_names: HashSet<String> = touched_files
+            .iter()
+            .flat_map(|p| self.file_signature(p).into_iter().map(|(_, name)| name))
+            .collect();
+        for ext in update
+            .changed
+            .iter()
+            .chain(update.renamed.iter().map(|(_, e)| e))
+        {
+            touched_names.extend(ext.signature().into_iter().map(|(_, name)| name));
+        }
+
+        // Types whose method sets may have changed: those defined by touched
+        // files and the receivers of methods declared in them
+        let implicit_interfaces = touched_files
+            .iter()
+            .any(|f| LinkRules::for_path(f).implicit_interfaces);
+        let mut touched_types = touched_names.clone();
+        if implicit_interfaces {
+            for relation in touched_files
+                .iter()
+                .filter_map(|f| self.relations.get(f))
+                .flatten()
+            {
+                if let (EdgeKind::Contains, RelationTarget::Symbol(name)) =
+                    (relation.kind, &relation.target)
+                {
+                    touched_types.insert(name.clone());
+                }
+            }
+            for ext in update
+                .changed
+                .iter()
+                .chain(update.renamed.iter().map(|(_, e)| e))
+            {
+                touched_types.extend(ext.symbols.iter().filter_map(|s| s.parent.clone()));
+            }
+        }
+
+        // Remember symbol ids of renamed files before they're removed
+        let carried_ids: Vec<(PathBuf, HashMap<SymbolKey, NodeId>)> = update
+            .renamed
+            .iter()
+            .map(|(old, ext)| (ext.file_path.clone(), self.file_symbol_ids(old)))
+            .collect();
+
+        for path in &update.removed {
+            self.remove_file(path);
+        }
+        for (old, _) in &update.renamed {
+            self.remove_file(old);
+        }
+        for ext in update
+            .changed
+            .iter()
+            .chain(update.renamed.iter().map(|(_, e)| e))
+        {
+            self.remove_file(&ext.file_path);
+        }
+
+        let mut extractions = update.changed;
+        extractions.extend(update.renamed.into_iter().map(|(_, e)| e));
+        self.ingest(extractions);
+
+        for (new_path, old_ids) in carried_ids {
+            for (key, new_idx) in self.file_symbol_indexes(&new_path) {
+                if let Some(&old_id) = old_ids.get(&key) {
+                    self.reassign_id(new_idx, old_id);
+                }
+            }
+        }
+
+        self.relink_inbound_calls(&touched_names, &touched_files);
+
+        let mut touched_targets: HashSet<RelationTarget> = touched_names
+            .into_iter()
+            .map(RelationTarget::Symbol)
+            .collect();
+        for file in &touched_files {
+            touched_targets.insert(RelationTarget::Path(file.clone()));
+            if let Some(dir) = file.parent() {
+                touched_targets.insert(RelationTarget::Path(dir.to_path_buf()));
+            }
+        }
+        self.relink_inbound_relations(&touched_targets, &touched_files);
+
+        if implicit_interfaces {
+            self.link_implicit_interfaces(Some(&touched_types));
+        }
+    }
+
+    /// Re-resolve calls from untouched files to any of `names`, so the
+    /// graph ends up as if it had been built from scratch.
+    fn relink_inbound_calls(&mut self, names: &HashSet<String>, skip_files: &HashSet<PathBuf>) {
+        let mut files: Vec<PathBuf> = names
+            .iter()
+            .filter_map(|name| self.callee_index.get(name))
+            .flatten()
+            .filter(|file| !skip_files.contains(*file))
+            .cloned()
+            .collect();
+        files.sort();
+        files.dedup();
+
+        for file in files {
+            let calls: Vec<ExtractedCall> = self.calls[&file]
+                .iter()
+                .filter(|c| names.contains(&c.callee))
+                .cloned()
+                .collect();
+
+            // Drop the old edges for these (caller, callee) pairs...
+            for call in &calls {
+                let Some(caller_idx) = self.symbol_at(&file, &call.caller, call.line) else {
+                    continue;
+                };
+                let stale: Vec<_> = self
+                    .graph
+                    .edges_directed(caller_idx, Direction::Outgoing)
+                    .filter(|e| {
+                        e.weight().kind == EdgeKind::Calls
+                            && self.graph[e.target()].name == call.callee
+                    })
+                    .map(|e| e.id())
+                    .collect();
+                for edge in stale.into_iter().rev() {
+                    self.graph.remove_edge(edge);
+                }
+            }
+            // ...and resolve them again against the current symbols
+            for call in &calls {
+                self.link_call(&file, call);
+            }
+        }
+    }
+
+    /// Record one extracted call on the `Calls` edge between its caller
+    /// and callee, if both ends resolve. Calling a type by name
+    /// (`User()`) constructs it.
+    fn link_call(&mut self, file: &Path, call: &ExtractedCall) {
+        let Some(caller_idx) = self.symbol_at(file, &call.caller, call.line) else {
+            return;
+        };
+        let Some(callee_idx) = self.resolve_callee(file, caller_idx, &call.callee) else {
+            return;
+        };
+        let mut site = call.site();
+        if site.style == CallStyle::Direct
+            && matches!(
+                self.graph[callee_idx].kind,
+                NodeKind::Class | NodeKind::Struct
+            )
+        {
+            site.style = CallStyle::Constructor;
+        }
+
+        let edge = self.shared_edge(caller_idx, callee_idx, EdgeKind::Calls);
+        let sites = &mut self.graph[edge].call_sites;
+        let at = sites.partition_point(|s| {
+            (s.line, s.range.start.byte) <= (site.line, site.range.start.byte)
+        });
+        sites.insert(at, site);
+    }
+
+    /// The edge of `kind` from `from` to `to`, added if there is none yet.
+    /// Calls and usages between the same pair share one edge.
+    fn shared_edge(&mut self, from: NodeIndex, to: NodeIndex, kind: EdgeKind) -> EdgeIndex {
+        let existing = self
+            .graph
+            .edges_connecting(from, to)
+            .find(|e| e.weight().kind == kind)
+            .map(|e| e.id());
+        existing.unwrap_or_else(|| self.graph.add_edge(from, to, EdgeData::new(kind)))
+    }
+
+    /// Pick the node a call by name refers to: a symbol in the caller's own
+    /// file if there is one, a member of the caller's own type over others
+    /// (`self.step()`), otherwise the first by (file, line). Depends only on
+    /// the live symbols, never on insertion order, so incremental updates
+    /// and fresh builds agree.
+    fn resolve_callee(
+        &self,
+        caller_file: &Path,
+        caller_idx: NodeIndex,
+        callee: &str,
+    ) -> Option<NodeIndex> {
+        let owner = member_owner(&self.graph[caller_idx]);
+        self.symbol_index
+            .get(callee)?
+            .iter()
+            .copied()
+            .filter(|&idx| self.is_live(idx))
+            .min_by_key(|&idx| {
+                let node = &self.graph[idx];
+                let same_owner = owner.is_some() && member_owner(node) == owner;
+                (
+                    node.file_path != caller_file,
+                    !same_owner,
+                    &node.file_path,
+                    node.line_start,
+                    node.kind,
+                )
+            })
+    }
+
+    /// Replace the recorded calls of a file, keeping `callee_index` in sync.
+    fn record_calls(&mut self, file: &Path, calls: Vec<ExtractedCall>) {
+        if let Some(old) = self.calls.remove(file) {
+            for call in old {
+                if let Some(files) = self.callee_index.get_mut(&call.callee) {
+                    files.remove(file);
+                    if files.is_empty() {
+                        self.callee_index.remove(&call.callee);
+                    }
+                }
+            }
+        }
+        if calls.is_empty() {
+            return;
+        }
+        for call in &calls {
+            self.callee_index
+                .entry(call.callee.clone())
+                .or_default()
+                .insert(file.to_path_buf());
+        }
+        self.calls.insert(file.to_path_buf(), calls);
+    }
+
+    /// Recorded calls per file (for persistence).
+    pub(crate) fn recorded_calls(&self) -> impl Iterator<Item = (&PathBuf, &Vec<ExtractedCall>)> {
+        self.calls.iter()
+    }
+
+    /// Restore recorded calls for a file (for persistence).
+    pub(crate) fn restore_calls(&mut self, file: &Path, calls: Vec<ExtractedCall>) {
+        self.record_calls(file, calls);
+    }
+
+    /// Re-resolve relations from untouched files that point at any of
+    /// `targets`, the same way `relink_inbound_calls` does for calls.
+    fn relink_inbound_relations(
+        &mut self,
+        targets: &HashSet<RelationTarget>,
+        skip_files: &HashSet<PathBuf>,
+    ) {
+        let mut files: Vec<PathBuf> = targets
+            .iter()
+            .filter_map(|target| self.relation_index.get(target))
+            .flatten()
+            .filter(|file| !skip_files.contains(*file))
+            .cloned()
+            .collect();
+        files.sort();
+        files.dedup();
+
+        for file in files {
+            let relations: Vec<ExtractedRelation> = self.relations[&file]
+                .iter()
+                .filter(|r| {
+                    index_keys(&r.target)
+                        .iter()
+                        .any(|key| targets.contains(key))
+                })
+                .cloned()
+                .collect();
+            for relation in &relations {
+                self.unlink_relation(&file, relation);
+            }
+            for relation in &relations {
+                self.link_relation(&file, relation, true);
+            }
+        }
+    }
+
+    /// Add the edges for one relation, if its source and target resolve.
+    /// When relinking, edges within the (unchanged) file are kept as they
+    /// are, so only targets in other files are added.
+    fn link_relation(&mut self, file: &Path, relation: &ExtractedRelation, relinking: bool) {
+        let Some(source_idx) = self.relation_source(file, relation) else {
+            return;
+        };
+        for target_idx in self.resolve_relation(file, relation, source_idx) {
+            if relinking && self.graph[target_idx].file_path == file {
+                continue;
+            }
+            if let Some(site) = relation.site() {
+                let edge = self.shared_edge(source_idx, target_idx, relation.kind);
+                let sites = &mut self.graph[edge].reference_sites;
+                let at = sites.partition_point(|s| {
+                    (s.line, s.range.start.byte) <= (site.line, site.range.start.byte)
+                });
+                sites.insert(at, site);
+                continue;
+            }
+            match relation_edge_kind(relation.kind, &self.graph[target_idx]) {
+                EdgeKind::Contains => self.add_edge(target_idx, source_idx, EdgeKind::Contains),
+                kind => self.add_edge(source_idx, target_idx, kind),
+            }
+        }
+    }
+
+    /// Remove the edges a relation added to other files (see `link_relation`).
+    fn unlink_relation(&mut self, file: &Path, relation: &ExtractedRelation) {
+        let Some(source_idx) = self.relation_source(file, relation) else {
+            return;
+        };
+        let direction = if relation.kind == EdgeKind::Contains {
+            Direction::Incoming
+        } else {
+            Direction::Outgoing
+        };
+        let mut stale: Vec<_> = self
+            .graph
+            .edges_directed(source_idx, direction)
+            .filter(|e| {
+                let other = if direction == Direction::Incoming {
+                    e.source()
+                } else {
+                    e.target()
+                };
+                let other = &self.graph[other];
+                e.weight().kind == relation_edge_kind(relation.kind, other)
+                    && other.file_path != file
+                    && relation_matches(&relation.target, other)
+            })
+            .map(|e| e.id())
+            .collect();
+        stale.sort();
+        for edge in stale.into_iter().rev() {
+            self.graph.remove_edge(edge);
+        }
+    }
+
+    /// The node a relation starts from: the innermost symbol with the
+    /// relation's source name whose lines contain the relation, or the
+    /// file when it has none (`Exports`, top-level references).
+    fn relation_source(&self, file: &Path, relation: &ExtractedRelation) -> Option<NodeIndex> {
+        if relation.source.is_empty() {
+            return self.file_index.get(file).copied();
+        }
+        self.symbol_at(file, &relation.source, relation.line)
+    }
+
+    /// The innermost symbol of `file` named `name` whose lines contain
+    /// `line`, falling back to the first one of that name.
+    fn symbol_at(&self, file: &Path, name: &str, line: usize) -> Option<NodeIndex> {
+        self.symbol_index
+            .get(name)
+            .into_iter()
+            .flatten()
+            .copied()
+            .filter(|&idx| {
+                let node = &self.graph[idx];
+                !node.removed
+                    && node.file_path == file
+                    && node.line_start <= line
+                    && line <= node.line_end
+            })
+            .min_by_key(|&idx| {
+                let node = &self.graph[idx];
+                (node.line_end - node.line_start, idx)
+            })
+            .or_else(|| {
+                self.qualified_index
+                    .get(&(file.to_path_buf(), name.to_string()))
+                    .copied()
+            })
+    }
+
+    /// Nodes a relation points at. A symbol target resolves to one node
+    /// the source language's `LinkRules` accept, preferring the source's
+    /// file, then its directory, then (file, line).
+    /// A path target resolves to that file or every file in that directory.
+    fn resolve_relation(
+        &self,
+        file: &Path,
+        relation: &ExtractedRelation,
+        source_idx: NodeIndex,
+    ) -> Vec<NodeIndex> {
+        match &relation.target {
+            RelationTarget::Symbol(name) => {
+                let dir = file.parent();
+                let source = &self.graph[source_idx];
+                let rules = LinkRules::for_path(file);
+                self.symbol_index
+                    .get(name)
+                    .into_iter()
+                    .flatten()
+                    .copied()
+                    .filter(|&idx| idx != source_idx && self.is_live(idx))
+                    .filter(|&idx| rules.accepts(relation, source, &self.graph[idx]))
+                    .min_by_key(|&idx| {
+                        let node = &self.graph[idx];
+                        (
+                            node.file_path != file,
+                            node.file_path.parent() != dir,
+                            node.kind == NodeKind::Module,
+                            &node.file_path,
+                            node.line_start,
+                            node.kind,
+                        )
+                    })
+                    .into_iter()
+                    .collect()
+            }
+            RelationTarget::Path(path) => self.path_nodes(path),
+            RelationTarget::FirstOf(paths) => paths
+                .iter()
+                .map(|path| self.path_nodes(path))
+                .find(|nodes| !nodes.is_empty())
+                .unwrap_or_default(),
+        }
+    }
+
+    /// The file node at `path`, or the file nodes directly inside it.
+    fn path_nodes(&self, path: &Path) -> Vec<NodeIndex> {
+        match self.file_index.get(path) {
+            Some(&idx) => vec![idx],
+            None => self
+                .dir_index
+                .get(path)
+                .into_iter()
+                .flatten()
+                .filter_map(|f| self.file_index.get(f).copied())
+                .collect(),
+        }
+    }
+
+    /// Replace the recorded relations of a file, keeping `relation_index`
+    /// in sync.
+    fn record_relations(&mut self, file: &Path, relations: Vec<ExtractedRelation>) {
+        if let Some(old) = self.relations.remove(file) {
+            for key in old.iter().flat_map(|relation| index_keys(&relation.target)) {
+                if let Some(files) = self.relation_index.get_mut(&key) {
+                    files.remove(file);
+                    if files.is_empty() {
+                        self.relation_index.remove(&key);
+                    }
+                }
+            }
+        }
+        if relations.is_empty() {
+            return;
+        }
+        for key in relations
+            .iter()
+            .flat_map(|relation| index_keys(&relation.target))
+        {
+            self.relation_index
+                .entry(key)
+                .or_default()
+                .insert(file.to_path_buf());
+        }
+        self.relations.insert(file.to_path_buf(), relations);
+    }
+
+    /// Recorded relations per file (for persistence).
+    pub(crate) fn recorded_relations(
+        &self,
+    ) -> impl Iterator<Item = (&PathBuf, &Vec<ExtractedRelation>)> {
+        self.relations.iter()
+    }
+
+    /// Restore recorded relations for a file (for persistence).
+    pub(crate) fn restore_relations(&mut self, file: &Path, relations: Vec<ExtractedRelation>) {
+        self.record_relations(file, relations);
+    }
+
+    /// Go interfaces are satisfied implicitly: recompute `Implements` from
+    /// every Go struct or named type whose methods (by name) cover all of
+    /// an interface's methods. Empty interfaces are skipped.
+    ///
+    /// With `names`, only the types and interfaces of those names are
+    /// rechecked, against the interfaces and types sharing a method name
+    /// with them; without, the whole graph is.
+    fn link_implicit_interfaces(&mut self, names: Option<&HashSet<String>>) {
+        let implicit = |node: &NodeData| {
+            !node.removed && LinkRules::for_path(&node.file_path).implicit_interfaces
+        };
+        let candidates: Vec<NodeIndex> = match names {
+            Some(names) => names
+                .iter()
+                .filter_map(|name| self.symbol_index.get(name))
+                .flatten()
+                .copied()
+                .collect(),
+            None => self.graph.node_indices().collect(),
+        };
+        let mut types = BTreeSet::new();
+        let mut interfaces = BTreeSet::new();
+        for idx in candidates {
             let node = &self.graph[idx];
-            if !node.removed && node.kind == NodeKind::File {
-                live_files.insert(node.file_path.clone(), Vec::new());
+            if !implicit(node) {
+                continue;
             }
+            match node.kind {
+                NodeKind::Interface => interfaces.insert(idx),
+                NodeKind::Struct | NodeKind::Type => types.insert(idx),
+                _ => false,
+            };
         }
 
-        // Build a new graph with only live nodes
-        let mut new_graph = CodeGraph::new();
+        let mut stale: Vec<EdgeIndex> = types
+            .iter()
+            .flat_map(|&idx| self.graph.edges_directed(idx, Direction::Outgoing))
+            .chain(
+                interfaces
+                    .iter()
+                    .flat_map(|&idx| self.graph.edges_directed(idx, Direction::Incoming)),
+            )
+            .filter(|e| {
+                e.weight().kind == EdgeKind::Implements && implicit(&self.graph[e.source()])
+            })
+            .map(|e| e.id())
+            .collect();
+        stale.sort();
+        stale.dedup();
+        for edge in stale.into_iter().rev() {
+            self.graph.remove_edge(edge);
+        }
+
+        // Every method a type has is a candidate interface's method too
+        let mut pairs: BTreeSet<(NodeIndex, NodeIndex)> = BTreeSet::new();
+        for &type_idx in &types {
+            for method in self.method_names(type_idx) {
+                for iface_idx in self.method_owners(method, NodeKind::Interface) {
+                    pairs.insert((type_idx, iface_idx));
+                }
+            }
+        }
+        for &iface_idx in &interfaces {
+            if let Some(method) = self.method_names(iface_idx).into_iter().next() {
+                for type_idx in self.method_owners(method, NodeKind::Struct) {
+                    pairs.insert((type_idx, iface_idx));
+                }
+            }
+        }
+
+        let implements: Vec<(NodeIndex, NodeIndex)> = pairs
+            .into_iter()
+            .filter(|&(type_idx, iface_idx)| {
+                let iface_methods = self.method_names(iface_idx);
+                implicit(&self.graph[type_idx])
+                    && implicit(&self.graph[iface_idx])
+                    && !iface_methods.is_empty()
+                    && iface_methods.is_subset(&self.method_names(type_idx))
+            })
+            .collect();
+        for (type_idx, iface_idx) in implements {
+            self.add_edge(type_idx, iface_idx, EdgeKind::Implements);
+        }
+    }
+
+    /// Names of the live methods a type or interface contains.
+    fn method_names(&self, idx: NodeIndex) -> BTreeSet<&str> {
+        self.graph
+            .edges_directed(idx, Direction::Outgoing)
+            .filter(|e| e.weight().kind == EdgeKind::Contains)
+            .map(|e| &self.graph[e.target()])
+            .filter(|n| !n.removed && n.kind == NodeKind::Method)
+            .map(|n| n.name.as_str())
+            .collect()
+    }
+
+    /// Live interfaces (for `NodeKind::Interface`) or structs and named
+    /// types (otherwise) containing a method called `method`.
+    fn method_owners(&self, method: &str, kind: NodeKind) -> Vec<NodeIndex> {
+        self.symbol_index
+            .get(method)
+            .into_iter()
+            .flatten()
+            .flat_map(|&idx| self.graph.edges_directed(idx, Direction::Incoming))
+            .filter(|e| e.weight().kind == EdgeKind::Contains)
+            .map(|e| e.source())
+            .filter(|&idx| {
+                let node = &self.graph[idx];
+                match kind {
+                    NodeKind::Interface => node.kind == NodeKind::Interface,
+                    _ => matches!(node.kind, NodeKind::Struct | NodeKind::Type),
+                }
+            })
+            .collect()
+    }
+
+    /// Sorted (kind, name) list of the live nodes a file contributes.
+    /// Comparable with `FileExtractions::signature`.
+    pub fn file_signature(&self, path: &Path) -> Vec<(NodeKind, String)> {
+        let mut sig: Vec<(NodeKind, String)> = self
+            .file_symbol_indexes(path)
+            .into_iter()
+            .map(|((kind, name, _), _)| (kind, name))
+            .collect();
+        sig.sort();
+        sig
+    }
+
+    /// Live child nodes of a file keyed by (kind, name, ordinal), where the
+    /// ordinal counts same-named symbols in insertion order — their order
+    /// in the file's extraction.
+    fn file_symbol_indexes(&self, path: &Path) -> Vec<(SymbolKey, NodeIndex)> {
+        let Some(&file_idx) = self.file_index.get(path) else {
+            return Vec::new();
+        };
+        let mut children: Vec<NodeIndex> = self
+            .graph
+            .edges_directed(file_idx, Direction::Outgoing)
+            .filter(|e| {
+                matches!(e.weight().kind, EdgeKind::Defines | EdgeKind::Imports)
+                    && self.is_live(e.target())
+            })
+            .map(|e| e.target())
+            .collect();
+        children.sort();
+        children.dedup();
+
+        let mut seen: HashMap<(NodeKind, String), u32> = HashMap::new();
+        children
+            .into_iter()
+            .map(|idx| {
+                let node = &self.graph[idx];
+                let ordinal = seen.entry((node.kind, node.name.clone())).or_insert(0);
+                let key = (node.kind, node.name.clone(), *ordinal);
+                *ordinal += 1;
+                (key, idx)
+            })
+            .collect()
+    }
+
+    fn file_symbol_ids(&self, path: &Path) -> HashMap<SymbolKey, NodeId> {
+        self.file_symbol_indexes(path)
+            .into_iter()
+            .map(|(key, idx)| (key, self.graph[idx].id))
+            .collect()
+    }
 
-        // Re-add all live file nodes
-        for path in live_files.keys() {
-            new_graph.add_file(path.clone());
+    /// Give a live node a different id, unless another live node holds it.
+    fn reassign_id(&mut self, idx: NodeIndex, id: NodeId) {
+        if self.id_index.contains_key(&id) {
+            return;
         }
+        let old = self.graph[idx].id;
+        if self.id_index.get(&old) == Some(&idx) {
+            self.id_index.remove(&old);
+        }
+        self.graph[idx].id = id;
+        self.id_index.insert(id, idx);
+    }
 
-        // Re-add all live symbol nodes and their edges
+    /// Rebuild the graph from scratch, removing all soft-deleted nodes.
+    /// Call this periodically or after many incremental updates to reclaim memory.
+    pub fn compact(&mut self) {
+        info!("compacting graph — rebuilding without soft-deleted nodes");
+
+        // Build a new graph with only live nodes, keeping their stable ids
+        let mut new_graph = CodeGraph::new();
         let mut old_to_new: HashMap<NodeIndex, NodeIndex> = HashMap::new();
         for idx in self.graph.node_indices() {
             let node = &self.graph[idx];
             if node.removed {
                 continue;
             }
-            if node.kind == NodeKind::File {
-                if let Some(&new_idx) = new_graph.file_index.get(&node.file_path) {
-                    old_to_new.insert(idx, new_idx);
-                }
-            } else {
-                let new_idx = new_graph.add_symbol(
-                    node.name.clone(),
-                    node.kind,
-                    node.file_path.clone(),
-                    node.line_start,
-                    node.line_end,
-                    node.code_snippet.clone(),
-                );
-                old_to_new.insert(idx, new_idx);
-            }
+            let new_idx = new_graph.insert_node(node.clone());
+            old_to_new.insert(idx, new_idx);
         }
 
         // Re-add all edges between live nodes
@@ -755,12 +1620,17 @@ impl CodeGraph {
                 if let (Some(&new_src), Some(&new_tgt)) =
                     (old_to_new.get(&src), old_to_new.get(&tgt))
                 {
-                    let edge_data = &self.graph[edge];
-                    new_graph.add_edge(new_src, new_tgt, edge_data.kind);
+                    let edge_data = self.graph[edge].clone();
+                    new_graph.graph.add_edge(new_src, new_tgt, edge_data);
                 }
             }
         }
 
+        new_graph.calls = std::mem::take(&mut self.calls);
+        new_graph.callee_index = std::mem::take(&mut self.callee_index);
+        new_graph.relations = std::mem::take(&mut self.relations);
+        new_graph.relation_index = std::mem::take(&mut self.relation_index);
+
         // Replace self with the compacted graph
         *self = new_graph;
 
@@ -774,6 +1644,59 @@ impl CodeGraph {
     }
 }
 
+/// `imports` less repeats of a path imported without symbols (a Ruby
+/// constant referenced twice), which add nothing to the graph.
+fn distinct_imports(imports: &[ExtractedImport]) -> Vec<&ExtractedImport> {
+    let mut seen = HashSet::new();
+    imports
+        .iter()
+        .filter(|import| !import.symbols.is_empty() || seen.insert(import.path.as_str()))
+        .collect()
+}
+
+/// Whether a relation target refers to `node`.
+fn relation_matches(target: &RelationTarget, node: &NodeData) -> bool {
+    match target {
+        RelationTarget::Symbol(name) => node.name == *name,
+        RelationTarget::Path(path) => path_matches(path, node),
+        RelationTarget::FirstOf(paths) => paths.iter().any(|path| path_matches(path, node)),
+    }
+}
+
+/// Whether `node` is the file at `path` or a file directly inside it.
+fn path_matches(path: &Path, node: &NodeData) -> bool {
+    node.kind == NodeKind::File
+        && (node.file_path == *path || node.file_path.parent() == Some(path))
+}
+
+/// Keys `relation_index` files a relation under: the targets whose change
+/// can change what it resolves to.
+fn index_keys(target: &RelationTarget) -> Vec<RelationTarget> {
+    match target {
+        RelationTarget::FirstOf(paths) => paths.iter().cloned().map(RelationTarget::Path).collect(),
+        target => vec![target.clone()],
+    }
+}
+
+/// The edge a relation makes to `target`. Where the source language can't
+/// tell a superclass from a protocol (Swift), implementing a class is
+/// extending it.
+fn relation_edge_kind(kind: EdgeKind, target: &NodeData) -> EdgeKind {
+    match (kind, target.kind) {
+        (EdgeKind::Implements, NodeKind::Class) => EdgeKind::Extends,
+        _ => kind,
+    }
+}
+
+/// The type a member belongs to, from its qualified name
+/// (`geo.Shape#area` -> `geo.Shape`).
+fn member_owner(node: &NodeData) -> Option<&str> {
+    node.qualified_name
+        .as_deref()?
+        .rsplit_once('#')
+        .map(|(owner, _)| owner)
+}
+
 impl Default for CodeGraph {
     fn default() -> Self {
         Self::new()
@@ -785,8 +1708,16 @@ impl Default for CodeGraph {
 /// A search result returned by `CodeGraph::search`.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchResult {
+    /// Stable node id.
+    pub id: NodeId,
     /// The symbol name.
     pub symbol: String,
+    /// Fully qualified name (e.g. `com.acme.UserService#findById`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qualified_name: Option<String>,
+    /// Signature, visibility, modifiers, docs and decorators.
+    #[serde(default, skip_serializing_if = "SymbolMetadata::is_empty")]
+    pub metadata: SymbolMetadata,
     /// What kind of code element.
     pub kind: NodeKind,
     /// File where it's defined.
@@ -795,6 +1726,12 @@ pub struct SearchResult {
     pub line_start: usize,
     /// End line.
     pub line_end: usize,
+    /// Exact span of the definition.
+    #[serde(default, skip_serializing_if = "SourceRange::is_empty")]
+    pub range: SourceRange,
+    /// Span of the identifier naming it.
+    #[serde(default, skip_serializing_if = "SourceRange::is_empty")]
+    pub name_range: SourceRange,
     /// The actual source code.
     pub code: String,
     /// What this symbol calls.
@@ -808,17 +1745,24 @@ pub struct SearchResult {
 /// A reference to a symbol (lightweight, for connections).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolRef {
+    /// Stable node id.
+    pub id: NodeId,
     /// Symbol name.
     pub name: String,
     /// File path.
     pub file: PathBuf,
     /// Line number.
     pub line: usize,
+    /// Exact span of the symbol's definition.
+    #[serde(default, skip_serializing_if = "SourceRange::is_empty")]
+    pub range: SourceRange,
 }
 
 /// Dependency information for a symbol.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DependencyInfo {
+    /// Stable node id.
+    pub id: NodeId,
     /// The symbol name.
     pub symbol: String,
     /// The kind of symbol.
@@ -827,8 +1771,17 @@ pub struct DependencyInfo {
     pub file: PathBuf,
     /// Line number.
     pub line: usize,
+    /// Exact span of the symbol's definition.
+    #[serde(default, skip_serializing_if = "SourceRange::is_empty")]
+    pub range: SourceRange,
     /// How it's related.
     pub relationship: EdgeKind,
+    /// For calls, where each call happens (in the caller's file).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub call_sites: Vec<CallSite>,
+    /// For references, where each read, write or type mention happens.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reference_sites: Vec<ReferenceSite>,
 }
 
 /// Statistics about the graph.
@@ -971,7 +1924,11 @@ mod tests {
                     line_start: 1,
                     line_end: 3,
                     code_snippet: "fn add(a: i32, b: i32) -> i32 { a + b }".to_string(),
+                    range: SourceRange::default(),
+                    name_range: SourceRange::default(),
                     parent: None,
+                    qualified_name: None,
+                    metadata: SymbolMetadata::default(),
                 },
                 ExtractedSymbol {
                     name: "multiply".to_string(),
@@ -979,7 +1936,11 @@ mod tests {
                     line_start: 5,
                     line_end: 7,
                     code_snippet: "fn multiply(a: i32, b: i32) -> i32 { a * b }".to_string(),
+                    range: SourceRange::default(),
+                    name_range: SourceRange::default(),
                     parent: None,
+                    qualified_name: None,
+                    metadata: SymbolMetadata::default(),
                 },
             ],
             imports: vec![],
@@ -987,7 +1948,11 @@ mod tests {
                 caller: "multiply".to_string(),
                 callee: "add".to_string(),
                 line: 6,
+                range: SourceRange::default(),
+                style: CallStyle::Direct,
             }],
+            relations: vec![],
+            syntax_errors: vec![],
         }];
 
         let mut graph = CodeGraph::new();
@@ -1246,6 +2211,143 @@ mod tests {
         assert_eq!(graph.search("old_fn", 3).len(), 0);
     }
 
+    #[test]
+    fn test_compact_preserves_node_ids() {
+        let mut graph = CodeGraph::new();
+        let old_file = graph.add_file(PathBuf::from("src/old.rs"));
+        let old_fn = graph.add_symbol(
+            "old_fn".to_string(),
+            NodeKind::Function,
+            PathBuf::from("src/old.rs"),
+            1,
+            3,
+            "fn old_fn() {}".to_string(),
+        );
+        graph.add_edge(old_file, old_fn, EdgeKind::Defines);
+        let file_idx = graph.add_file(PathBuf::from("src/keep.rs"));
+        let fn_idx = graph.add_symbol(
+            "keep_fn".to_string(),
+            NodeKind::Function,
+            PathBuf::from("src/keep.rs"),
+            1,
+            5,
+            "fn keep_fn() {}".to_string(),
+        );
+        graph.add_edge(file_idx, fn_idx, EdgeKind::Defines);
+        let id = graph.inner_graph()[fn_idx].id;
+
+        graph.remove_file(Path::new("src/old.rs"));
+        graph.compact();
+
+        // The index moved, the id did not
+        assert_ne!(graph.index_of(id), Some(fn_idx));
+        assert_eq!(graph.node_by_id(id).unwrap().name, "keep_fn");
+        assert_eq!(graph.search("keep_fn", 1)[0].id, id);
+    }
+
+    #[test]
+    fn test_reextraction_preserves_node_ids() {
+        let extraction = FileExtractions {
+            file_path: PathBuf::from("src/auth.rs"),
+            symbols: vec![ExtractedSymbol {
+                name: "login".to_string(),
+                kind: NodeKind::Function,
+                line_start: 1,
+                line_end: 5,
+                code_snippet: "fn login() {}".to_string(),
+                range: SourceRange::default(),
+                name_range: SourceRange::default(),
+                parent: None,
+                qualified_name: None,
+                metadata: SymbolMetadata::default(),
+            }],
+            imports: vec![],
+            calls: vec![],
+            relations: vec![],
+            syntax_errors: vec![],
+        };
+
+        let mut graph = CodeGraph::new();
+        graph.build_from_extractions(vec![extraction.clone()]);
+        let before = graph.search("login", 1)[0].id;
+
+        graph.remove_file(Path::new("src/auth.rs"));
+        assert!(graph.node_by_id(before).is_none());
+        graph.build_from_extractions(vec![extraction]);
+
+        assert_eq!(graph.search("login", 1)[0].id, before);
+    }
+
+    #[test]
+    fn test_duplicate_symbols_get_distinct_ids() {
+        let mut graph = CodeGraph::new();
+        let a = graph.add_symbol(
+            "new".to_string(),
+            NodeKind::Method,
+            PathBuf::from("src/lib.rs"),
+            1,
+            3,
+            String::new(),
+        );
+        let b = graph.add_symbol(
+            "new".to_string(),
+            NodeKind::Method,
+            PathBuf::from("src/lib.rs"),
+            10,
+            12,
+            String::new(),
+        );
+
+        let (id_a, id_b) = (graph.inner_graph()[a].id, graph.inner_graph()[b].id);
+        assert_ne!(id_a, id_b);
+        assert_eq!(graph.index_of(id_a), Some(a));
+        assert_eq!(graph.index_of(id_b), Some(b));
+    }
+
+    #[test]
+    fn test_same_named_symbol_ids_match_fresh_build_after_edit() {
+        let method = |line: usize| ExtractedSymbol {
+            name: "new".to_string(),
+            kind: NodeKind::Method,
+            line_start: line,
+            line_end: line + 2,
+            code_snippet: "fn new() {}".to_string(),
+            range: SourceRange::default(),
+            name_range: SourceRange::default(),
+            parent: None,
+            qualified_name: None,
+            metadata: SymbolMetadata::default(),
+        };
+        let extraction = |symbols: Vec<ExtractedSymbol>| FileExtractions {
+            file_path: PathBuf::from("src/lib.rs"),
+            symbols,
+            imports: vec![],
+            calls: vec![],
+            relations: vec![],
+            syntax_errors: vec![],
+        };
+        let ids = |graph: &CodeGraph| -> Vec<NodeId> {
+            graph
+                .symbols_in_file(Path::new("src/lib.rs"))
+                .iter()
+                .map(|n| n.id)
+                .collect()
+        };
+
+        let mut graph = CodeGraph::new();
+        graph.build_from_extractions(vec![extraction(vec![method(1), method(10)])]);
+        // Delete the first of the two
+        graph.apply_update(GraphUpdate {
+            changed: vec![extraction(vec![method(7)])],
+            ..Default::default()
+        });
+
+        let mut fresh = CodeGraph::new();
+        fresh.build_from_extractions(vec![extraction(vec![method(7)])]);
+        assert_eq!(ids(&graph), ids(&fresh));
+        assert_eq!(ids(&graph).len(), 1);
+    }
+
     // ─── Edge-Case Tests ───────────────────────────────────────
 
     #[test]
@@ -0,0 +1,490 @@
+//! Source file filter — decides which files belong in the graph.
+//!
+//! Shared by the graph builder and the file watcher so both see exactly
+//! the same set of files. Rules, in order of precedence:
+//! - hidden files and directories (`.git`, `.anchor`, ...) are skipped
+//! - `exclude` globs from `AnchorConfig` always win
+//! - `.anchorignore`, then `.gitignore`, nearest directory first
+//! - `.git/info/exclude`, then the global git excludes file
+//! - if `include` globs are configured, a file must match one of them
+//! - the file must map to a language enabled in `project.languages`,
+//!   by its extension or a `[language.*] extensions` override, or be a
+//!   host format that embeds code (Vue, Svelte, HTML, Markdown, notebooks)
+//!   enabled there too
+//!
+//! The filter also carries the project's tag queries, so everything that
+//! decides how a file is indexed travels together.
+
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::Match;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tracing::warn;
+
+use crate::config::{AnchorConfig, ANCHOR_DIR, QUERIES_DIR};
+use crate::error::Result;
+use crate::parser::{HostFormat, SupportedLanguage, TagQueries};
+
+/// Per-directory ignore file specific to Anchor (gitignore syntax).
+pub const ANCHOR_IGNORE_FILE: &str = ".anchorignore";
+
+/// Per-directory ignore files, highest precedence first.
+const IGNORE_FILES: &[&str] = &[ANCHOR_IGNORE_FILE, ".gitignore"];
+
+/// Compiled ignore rules for a project root.
+pub struct SourceFilter {
+    /// Root as given by the caller; returned paths are joined onto it.
+    root: PathBuf,
+    /// Canonical root, used to relativize absolute event paths.
+    canonical_root: PathBuf,
+    /// Directory of the enclosing git repository, if any.
+    git_root: Option<PathBuf>,
+    /// Per-directory matchers, deepest directory first.
+    dir_ignores: Vec<(PathBuf, Gitignore)>,
+    /// `.git/info/exclude` of the enclosing repository.
+    git_exclude: Gitignore,
+    /// Global git excludes (`core.excludesFile`).
+    global: Gitignore,
+    include: Option<GlobSet>,
+    exclude: GlobSet,
+    /// Languages enabled in the config.
+    languages: HashSet<SupportedLanguage>,
+    /// Host formats enabled in the config.
+    hosts: HashSet<HostFormat>,
+    /// Extra extension -> language mappings from the config.
+    extensions: HashMap<String, SupportedLanguage>,
+    config: AnchorConfig,
+    queries: TagQueries,
+}
+
+impl SourceFilter {
+    /// Compile the filter for `root`, loading every ignore file that
+    /// applies to it (including those in parent directories up to the
+    /// enclosing git repository).
+    pub fn new(root: &Path, config: &AnchorConfig) -> Self {
+        let project = &config.project;
+        let canonical_root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let git_root = canonical_root
+            .ancestors()
+            .find(|dir| dir.join(".git").exists())
+            .map(Path::to_path_buf);
+
+        let git_exclude = match &git_root {
+            Some(git_root) => Gitignore::new(git_root.join(".git/info/exclude")).0,
+            None => Gitignore::empty(),
+        };
+
+        let mut filter = Self {
+            root: root.to_path_buf(),
+            canonical_root: canonical_root.clone(),
+            git_root: git_root.clone(),
+            dir_ignores: Vec::new(),
+            git_exclude,
+            global: Gitignore::global().0,
+            include: (!project.include.is_empty()).then(|| build_globset(&project.include)),
+            exclude: build_globset(&project.exclude),
+            languages: config.enabled_languages().into_iter().collect(),
+            hosts: config.enabled_host_formats().into_iter().collect(),
+            extensions: config.extension_overrides().into_iter().collect(),
+            config: config.clone(),
+            queries: TagQueries::default(),
+        };
+
+        // Ignore files in ancestors of the root apply too (like git)
+        if let Some(git_root) = &git_root {
+            for dir in canonical_root.ancestors().skip(1) {
+                if !dir.starts_with(git_root) {
+                    break;
+                }
+                filter.load_dir_ignores(dir);
+            }
+        }
+
+        // Then every directory under the root that isn't itself ignored
+        let mut stack = vec![canonical_root];
+        while let Some(dir) = stack.pop() {
+            filter.load_dir_ignores(&dir);
+            for (path, is_dir) in read_dir_sorted(&dir) {
+                if is_dir && !filter.is_ignored(&path, true) {
+                    stack.push(path);
+                }
+            }
+        }
+
+        filter
+    }
+
+    /// Compile the filter for a project: scan `project.root` from the
+    /// config and use any tag query overrides in `.anchor/queries/`.
+    pub fn for_project(project_root: &Path, config: &AnchorConfig) -> Result<Self> {
+        let queries = TagQueries::load(&project_root.join(QUERIES_DIR))?;
+        let root = config.resolve_root(&project_root.join(ANCHOR_DIR));
+        Ok(Self {
+            queries,
+            ..Self::new(&root, config)
+        })
+    }
+
+    /// The root this filter was built for.
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    /// The config this filter was compiled from.
+    pub fn config(&self) -> &AnchorConfig {
+        &self.config
+    }
+
+    /// Tag queries to extract files with.
+    pub fn queries(&self) -> &TagQueries {
+        &self.queries
+    }
+
+    /// Language `path` is parsed as, honouring extension overrides.
+    /// None if the language is unknown or not enabled.
+    pub fn language_for(&self, path: &Path) -> Option<SupportedLanguage> {
+        let lang = path
+            .extension()
+            .and_then(|ext| self.extensions.get(ext.to_str()?))
+            .copied()
+            .or_else(|| SupportedLanguage::from_path(path))?;
+        self.languages.contains(&lang).then_some(lang)
+    }
+
+    /// Host format of `path` when it isn't a language itself: its embedded
+    /// regions are extracted in their own languages. None if the format
+    /// is not enabled.
+    pub fn host_for(&self, path: &Path) -> Option<HostFormat> {
+        if self.language_for(path).is_some() {
+            return None;
+        }
+        HostFormat::from_path(path).filter(|format| self.hosts.contains(format))
+    }
+
+    /// Whether `lang` is enabled in `project.languages`.
+    pub fn language_enabled(&self, lang: SupportedLanguage) -> bool {
+        self.languages.contains(&lang)
+    }
+
+    /// Whether `path` (absolute, or relative to the root) is excluded.
+    /// Paths outside the root are always excluded.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        let Some(rel) = self.relativize(path) else {
+            return true;
+        };
+        if rel.as_os_str().is_empty() {
+            return false;
+        }
+
+        // Check every ancestor directory, then the path itself; once a
+        // directory is excluded nothing below it can be re-included.
+        let components: Vec<_> = rel.components().collect();
+        let mut prefix = PathBuf::new();
+        for (i, component) in components.iter().enumerate() {
+            prefix.push(component);
+            let prefix_is_dir = i + 1 < components.len() || is_dir;
+
+            if component.as_os_str().to_string_lossy().starts_with('.') {
+                return true;
+            }
+            if self.exclude.is_match(&prefix) {
+                return true;
+            }
+            if self.gitignored(&self.canonical_root.join(&prefix), prefix_is_dir) {
+                return true;
+            }
+        }
+
+        if !is_dir {
+            if let Some(include) = &self.include {
+                if !rel
+                    .ancestors()
+                    .any(|p| !p.as_os_str().is_empty() && include.is_match(p))
+                {
+                    return true;
+                }
+            }
+        }
+
+        false
+    }
+
+    /// Whether `path` is a source file that belongs in the graph.
+    pub fn accepts(&self, path: &Path) -> bool {
+        (self.language_for(path).is_some() || self.host_for(path).is_some())
+            && !self.is_ignored(path, false)
+    }
+
+    /// Whether `path` is a file that changes the filter itself
+    /// (an ignore file, or the git exclude file). Ignore files inside an
+    /// ignored directory (e.g. packages under `node_modules`) never apply,
+    /// so they don't count.
+    pub fn is_ignore_file(&self, path: &Path) -> bool {
+        let is_named_ignore = path
+            .file_name()
+            .is_some_and(|name| IGNORE_FILES.iter().any(|f| name == *f))
+            && path
+                .parent()
+                .is_some_and(|dir| self.relativize(dir).is_none() || !self.is_ignored(dir, true));
+        let is_git_exclude = self
+            .git_root
+            .as_ref()
+            .is_some_and(|g| path == g.join(".git/info/exclude"));
+        is_named_ignore || is_git_exclude
+    }
+
+    /// Walk the root and return every accepted source file, in a stable order.
+    pub fn source_files(&self) -> Vec<PathBuf> {
+        self.source_files_under(&self.root)
+    }
+
+    /// Like `source_files`, limited to one directory under the root
+    /// (e.g. a directory that was just moved into the project).
+    pub fn source_files_under(&self, dir: &Path) -> Vec<PathBuf> {
+        let Some(rel) = self.relativize(dir) else {
+            return Vec::new();
+        };
+        if self.is_ignored(dir, true) {
+            return Vec::new();
+        }
+        let mut files = Vec::new();
+        let mut stack = vec![self.canonical_root.join(rel)];
+        while let Some(dir) = stack.pop() {
+            for (path, is_dir) in read_dir_sorted(&dir) {
+                if is_dir {
+                    if !self.is_ignored(&path, true) {
+                        stack.push(path);
+                    }
+                } else if self.accepts(&path) {
+                    if let Ok(rel) = path.strip_prefix(&self.canonical_root) {
+                        files.push(self.root.join(rel));
+                    }
+                }
+            }
+        }
+        files.sort();
+        files
+    }
+
+    /// Path relative to the root, or None if it lies outside.
+    fn relativize(&self, path: &Path) -> Option<PathBuf> {
+        if path.is_relative() {
+            return Some(path.strip_prefix(&self.root).unwrap_or(path).to_path_buf());
+        }
+        path.strip_prefix(&self.canonical_root)
+            .or_else(|_| path.strip_prefix(&self.root))
+            .ok()
+            .map(Path::to_path_buf)
+    }
+
+    /// Apply gitignore-style rules to a single absolute path. The nearest
+    /// matching rule wins, so a deeper `!pattern` can re-include a file.
+    fn gitignored(&self, path: &Path, is_dir: bool) -> bool {
+        let dir_matchers = self
+            .dir_ignores
+            .iter()
+            .filter(|(dir, _)| path.starts_with(dir) && path != dir)
+            .map(|(_, gi)| gi);
+
+        for gi in dir_matchers.chain([&self.git_exclude, &self.global]) {
+            match gi.matched(path, is_dir) {
+                Match::Ignore(_) => return true,
+                Match::Whitelist(_) => return false,
+                Match::None => {}
+            }
+        }
+        false
+    }
+
+    /// Load `.anchorignore` / `.gitignore` from a directory.
+    fn load_dir_ignores(&mut self, dir: &Path) {
+        for name in IGNORE_FILES {
+            let file = dir.join(name);
+            if !file.is_file() {
+                continue;
+            }
+            let mut builder = GitignoreBuilder::new(dir);
+            if let Some(e) = builder.add(&file) {
+                warn!(file = %file.display(), error = %e, "invalid ignore file");
+            }
+            if let Ok(gi) = builder.build() {
+                self.dir_ignores.push((dir.to_path_buf(), gi));
+            }
+        }
+        // Deepest first; stable so .anchorignore stays ahead of .gitignore
+        self.dir_ignores
+            .sort_by_key(|(dir, _)| std::cmp::Reverse(dir.components().count()));
+    }
+}
+
+/// Compile config globs, skipping (and logging) invalid ones.
+fn build_globset(patterns: &[String]) -> GlobSet {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns {
+        match Glob::new(pattern) {
+            Ok(glob) => {
+                builder.add(glob);
+            }
+            Err(e) => warn!(pattern = %pattern, error = %e, "invalid glob in config"),
+        }
+    }
+    builder.build().unwrap_or_else(|_| GlobSet::empty())
+}
+
+/// List a directory as (path, is_dir), sorted. Symlinks are not followed.
+fn read_dir_sorted(dir: &Path) -> Vec<(PathBuf, bool)> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut out: Vec<(PathBuf, bool)> = entries
+        .filter_map(|e| e.ok())
+        .filter_map(|e| {
+            let ft = e.file_type().ok()?;
+            (ft.is_dir() || ft.is_file()).then(|| (e.path(), ft.is_dir()))
+        })
+        .collect();
+    out.sort();
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    fn write(root: &Path, rel: &str, contents: &str) {
+        let path = root.join(rel);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+    }
+
+    fn rel_files(filter: &SourceFilter) -> Vec<String> {
+        filter
+            .source_files()
+            .iter()
+            .map(|p| {
+                p.strip_prefix(filter.root())
+                    .unwrap()
+                    .to_string_lossy()
+                    .to_string()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_gitignore_and_anchorignore() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "src/main.rs", "fn main() {}");
+        write(root, "build/gen.rs", "fn gen() {}");
+        write(root, "vendor/lib.rs", "fn lib() {}");
+        write(root, "src/skip.rs", "fn skip() {}");
+        write(root, ".gitignore", "build/\n");
+        write(root, ".anchorignore", "vendor/\n");
+        write(root, "src/.gitignore", "skip.rs\n");
+
+        let filter = SourceFilter::new(root, &AnchorConfig::default());
+        assert_eq!(rel_files(&filter), vec!["src/main.rs"]);
+        assert!(filter.is_ignored(&root.join("build/gen.rs"), false));
+        assert!(filter.is_ignored(&root.join("src/skip.rs"), false));
+    }
+
+    #[test]
+    fn test_anchorignore_can_reinclude() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "build/gen.rs", "fn gen() {}");
+        write(root, ".gitignore", "build/\n");
+        write(root, ".anchorignore", "!build/\n");
+
+        let filter = SourceFilter::new(root, &AnchorConfig::default());
+        assert_eq!(rel_files(&filter), vec!["build/gen.rs"]);
+    }
+
+    #[test]
+    fn test_config_globs() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "src/a.rs", "");
+        write(root, "src/a_generated.rs", "");
+        write(root, "scripts/tool.py", "");
+
+        let mut config = AnchorConfig::default();
+        config.project.include = vec!["src".to_string()];
+        config.project.exclude = vec!["**/*_generated.rs".to_string()];
+        let filter = SourceFilter::new(root, &config);
+        assert_eq!(rel_files(&filter), vec!["src/a.rs"]);
+    }
+
+    #[test]
+    fn test_config_languages_and_extensions() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "a.rs", "");
+        write(root, "b.py", "");
+        write(root, "c.pyi", "");
+
+        let mut config = AnchorConfig::default();
+        config.project.languages = vec!["python".to_string()];
+        config.language.insert(
+            "python".to_string(),
+            crate::config::LanguageConfig {
+                extensions: vec!["pyi".to_string()],
+                max_snippet_lines: None,
+            },
+        );
+        let filter = SourceFilter::new(root, &config);
+        assert_eq!(rel_files(&filter), vec!["b.py", "c.pyi"]);
+        assert_eq!(
+            filter.language_for(Path::new("c.pyi")),
+            Some(SupportedLanguage::Python)
+        );
+    }
+
+    #[test]
+    fn test_host_formats_follow_config_languages() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, "a.py", "");
+        write(root, "App.vue", "");
+        write(root, "README.md", "");
+
+        let mut config = AnchorConfig::default();
+        let filter = SourceFilter::new(root, &config);
+        assert_eq!(rel_files(&filter), vec!["App.vue", "README.md", "a.py"]);
+
+        config.project.languages = vec!["python".to_string(), "markdown".to_string()];
+        let filter = SourceFilter::new(root, &config);
+        assert_eq!(rel_files(&filter), vec!["README.md", "a.py"]);
+        assert_eq!(filter.host_for(Path::new("App.vue")), None);
+    }
+
+    #[test]
+    fn test_hidden_and_ignore_file_detection() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, ".anchor/cache.rs", "");
+        write(root, "sub/.anchorignore", "");
+
+        let filter = SourceFilter::new(root, &AnchorConfig::default());
+        assert!(filter.is_ignored(&root.join(".anchor/cache.rs"), false));
+        assert!(filter.is_ignore_file(&root.join("sub/.anchorignore")));
+        assert!(filter.is_ignore_file(&root.join(".gitignore")));
+        assert!(!filter.is_ignore_file(&root.join("src/main.rs")));
+    }
+
+    #[test]
+    fn test_ignore_files_in_ignored_directories_are_inert() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        write(root, ".gitignore", "node_modules/\n");
+        write(root, "node_modules/pkg/.gitignore", "*.js\n");
+
+        let filter = SourceFilter::new(root, &AnchorConfig::default());
+        assert!(!filter.is_ignore_file(&root.join("node_modules/pkg/.gitignore")));
+        assert!(!filter.is_ignore_file(&root.join("node_modules/.gitignore")));
+        assert!(filter.is_ignore_file(&root.join("src/.gitignore")));
+    }
+}
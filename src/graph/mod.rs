@@ -5,12 +5,23 @@
 
 pub mod builder;
 pub mod engine;
+pub mod filter;
 pub mod persistence;
+pub mod report;
+#[cfg(test)]
+pub(crate) mod test_support;
 pub mod types;
 
-pub use builder::{build_graph, rebuild_file, scan_stats, ScanStats};
+pub use builder::{
+    build_graph, build_graph_with_filter, build_graph_with_report, extract_files, project_filter,
+    rebuild_file, scan_stats, ScanStats,
+};
 pub use engine::{CodeGraph, DependencyInfo, GraphStats, SearchResult, SymbolRef};
+pub use filter::SourceFilter;
+pub use persistence::SettingsFingerprint;
+pub use report::{BuildReport, FileReport, LanguageCoverage};
 pub use types::{
-    ConnectionInfo, EdgeData, EdgeKind, ExtractedCall, ExtractedImport, ExtractedSymbol,
-    FileExtractions, GraphSearchResult, NodeData, NodeKind, SymbolInfo,
+    CallSite, CallStyle, ConnectionInfo, EdgeData, EdgeKind, ExtractedCall, ExtractedImport,
+    ExtractedSymbol, FileExtractions, GraphSearchResult, GraphUpdate, NodeData, NodeId, NodeKind,
+    ReferenceKind, ReferenceSite, SourcePoint, SourceRange, SymbolInfo, SymbolMetadata, Visibility,
 };
@@ -2,16 +2,35 @@
 //!
 //! Uses bincode for compact binary serialization. Atomic writes
 //! (write to .tmp, then rename) prevent corruption from crashes.
+//!
+//! Every file starts with a magic number and `FORMAT_VERSION`, so a cache
+//! written by another version of Anchor is rejected up front (and rebuilt
+//! by the caller) rather than half-decoded by bincode.
+//!
+//! Beside the cache sits a `SettingsFingerprint` of the config and tag
+//! query overrides it was built with (`graph.settings` next to
+//! `graph.bin`), so a cache built under other settings is told apart and
+//! rebuilt too.
 
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::io::Write;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use tracing::{debug, info};
 
 use super::engine::CodeGraph;
-use super::types::{EdgeData, NodeData, NodeKind};
+use super::types::{EdgeData, ExtractedCall, ExtractedRelation, NodeData, StableHasher};
+use crate::config::{AnchorConfig, QUERIES_DIR};
 use crate::error::{AnchorError, Result};
+use crate::parser::SupportedLanguage;
+
+/// First bytes of every saved graph.
+const MAGIC: &[u8; 8] = b"ANCHORGR";
+
+/// Version of the on-disk format. Bump it whenever `SerializableGraph`
+/// or anything stored in it (`NodeData`, `EdgeData`, extracted calls and
+/// relations) changes shape.
+pub const FORMAT_VERSION: u32 = 3;
 
 /// Serializable representation of the graph.
 /// Nodes are stored as a flat vec; edges reference nodes by index position.
@@ -19,6 +38,10 @@ use crate::error::{AnchorError, Result};
 struct SerializableGraph {
     nodes: Vec<NodeData>,
     edges: Vec<(u32, u32, EdgeData)>,
+    /// Extracted calls per file, needed to re-resolve edges incrementally.
+    calls: Vec<(PathBuf, Vec<ExtractedCall>)>,
+    /// Extracted relations per file, for the same reason.
+    relations: Vec<(PathBuf, Vec<ExtractedRelation>)>,
 }
 
 impl CodeGraph {
@@ -30,8 +53,10 @@ impl CodeGraph {
         info!(path = %path.display(), "saving graph");
 
         let sg = self.to_serializable();
-        let bytes =
-            bincode::serialize(&sg).map_err(|e| AnchorError::SerializeError(e.to_string()))?;
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend_from_slice(&FORMAT_VERSION.to_le_bytes());
+        bincode::serialize_into(&mut bytes, &sg)
+            .map_err(|e| AnchorError::SerializeError(e.to_string()))?;
 
         // Atomic write: write to .tmp, then rename
         let tmp_path = path.with_extension("tmp");
@@ -49,7 +74,15 @@ impl CodeGraph {
         info!(path = %path.display(), "loading graph");
 
         let bytes = fs::read(path)?;
-        let sg: SerializableGraph = bincode::deserialize(&bytes)
+        let version = format_version(&bytes);
+        if version != Some(FORMAT_VERSION) {
+            return Err(AnchorError::CacheVersionMismatch(
+                path.to_path_buf(),
+                version,
+                FORMAT_VERSION,
+            ));
+        }
+        let sg: SerializableGraph = bincode::deserialize(&bytes[MAGIC.len() + 4..])
             .map_err(|e| AnchorError::ParseError(format!("bincode: {}", e)))?;
 
         let graph = Self::from_serializable(sg);
@@ -82,52 +115,115 @@ impl CodeGraph {
             })
             .collect();
 
-        SerializableGraph { nodes, edges }
+        let mut calls: Vec<(PathBuf, Vec<ExtractedCall>)> = self
+            .recorded_calls()
+            .map(|(path, calls)| (path.clone(), calls.clone()))
+            .collect();
+        calls.sort_by(|a, b| a.0.cmp(&b.0));
+
+        let mut relations: Vec<(PathBuf, Vec<ExtractedRelation>)> = self
+            .recorded_relations()
+            .map(|(path, relations)| (path.clone(), relations.clone()))
+            .collect();
+        relations.sort_by(|a, b| a.0.cmp(&b.0));
+
+        SerializableGraph {
+            nodes,
+            edges,
+            calls,
+            relations,
+        }
     }
 
     /// Reconstruct from a serializable representation.
+    ///
+    /// Nodes are re-inserted with their stored ids, so `NodeId`s handed
+    /// out before the save stay valid after the load.
     fn from_serializable(sg: SerializableGraph) -> Self {
         use petgraph::graph::NodeIndex;
 
         let mut graph = Self::new();
 
         // Add all nodes
-        let mut index_map: Vec<NodeIndex> = Vec::with_capacity(sg.nodes.len());
-        for node in sg.nodes {
-            let idx = if node.kind == NodeKind::File {
-                graph.add_file(node.file_path.clone())
-            } else {
-                graph.add_symbol(
-                    node.name.clone(),
-                    node.kind,
-                    node.file_path.clone(),
-                    node.line_start,
-                    node.line_end,
-                    node.code_snippet.clone(),
-                )
-            };
-
-            // If the original node was removed, mark it
-            if node.removed {
-                if let Some(n) = graph.inner_graph_mut().node_weight_mut(idx) {
-                    n.removed = true;
-                }
-            }
-
-            index_map.push(idx);
-        }
+        let index_map: Vec<NodeIndex> = sg
+            .nodes
+            .into_iter()
+            .map(|node| graph.insert_node(node))
+            .collect();
 
         // Add all edges
         for (src, tgt, data) in sg.edges {
             let src_idx = index_map[src as usize];
             let tgt_idx = index_map[tgt as usize];
-            graph.add_edge(src_idx, tgt_idx, data.kind);
+            graph.inner_graph_mut().add_edge(src_idx, tgt_idx, data);
+        }
+
+        for (path, calls) in sg.calls {
+            graph.restore_calls(&path, calls);
+        }
+        for (path, relations) in sg.relations {
+            graph.restore_relations(&path, relations);
         }
 
         graph
     }
 }
 
+/// Fingerprint of everything that decides how a project is indexed:
+/// the Anchor version (and with it the bundled queries), the effective
+/// config, and the tag query overrides in `.anchor/queries/`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SettingsFingerprint(u64);
+
+impl SettingsFingerprint {
+    /// Fingerprint the current settings of the project at `project_root`.
+    pub fn for_project(project_root: &Path, config: &AnchorConfig) -> Result<Self> {
+        let mut h = StableHasher::new();
+        h.write_str(env!("CARGO_PKG_VERSION"));
+        let config = serde_json::to_string(config)
+            .map_err(|e| AnchorError::SerializeError(e.to_string()))?;
+        h.write_str(&config);
+        let queries = project_root.join(QUERIES_DIR);
+        for &language in SupportedLanguage::ALL {
+            let name = format!("{}.scm", language.config_name());
+            match fs::read_to_string(queries.join(&name)) {
+                Ok(source) => {
+                    h.write_str(&name);
+                    h.write_str(&source);
+                }
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(e.into()),
+            }
+        }
+        Ok(Self(h.finish()))
+    }
+
+    /// The fingerprint saved beside the cache at `cache_path`, None if
+    /// there is none (or it is unreadable).
+    pub fn saved(cache_path: &Path) -> Option<Self> {
+        let text = fs::read_to_string(settings_path(cache_path)).ok()?;
+        u64::from_str_radix(text.trim(), 16).ok().map(Self)
+    }
+
+    /// Save beside the cache at `cache_path`.
+    pub fn save(&self, cache_path: &Path) -> Result<()> {
+        fs::write(settings_path(cache_path), format!("{:016x}\n", self.0))?;
+        Ok(())
+    }
+}
+
+/// Where the settings fingerprint of the cache at `cache_path` lives.
+fn settings_path(cache_path: &Path) -> PathBuf {
+    cache_path.with_extension("settings")
+}
+
+/// Format version of a saved graph, None if it has no header (written
+/// before the format was versioned, or not a graph at all).
+fn format_version(bytes: &[u8]) -> Option<u32> {
+    let rest = bytes.strip_prefix(MAGIC.as_slice())?;
+    Some(u32::from_le_bytes(rest.get(..4)?.try_into().ok()?))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -186,6 +282,31 @@ mod tests {
         assert_eq!(results[0].calls[0].name, "helper");
     }
 
+    #[test]
+    fn test_save_load_preserves_node_ids() {
+        let mut graph = CodeGraph::new();
+        let file_idx = graph.add_file(PathBuf::from("src/main.rs"));
+        let fn_idx = graph.add_symbol(
+            "main".to_string(),
+            NodeKind::Function,
+            PathBuf::from("src/main.rs"),
+            1,
+            10,
+            "fn main() {}".to_string(),
+        );
+        graph.add_edge(file_idx, fn_idx, EdgeKind::Defines);
+        let file_id = graph.inner_graph()[file_idx].id;
+        let fn_id = graph.inner_graph()[fn_idx].id;
+
+        let dir = tempdir().unwrap();
+        let save_path = dir.path().join("graph.bin");
+        graph.save(&save_path).unwrap();
+        let loaded = CodeGraph::load(&save_path).unwrap();
+
+        assert_eq!(loaded.node_by_id(file_id).unwrap().kind, NodeKind::File);
+        assert_eq!(loaded.node_by_id(fn_id).unwrap().name, "main");
+    }
+
     #[test]
     fn test_save_load_preserves_removed_nodes() {
         let mut graph = CodeGraph::new();
@@ -222,4 +343,70 @@ mod tests {
         let result = CodeGraph::load(Path::new("/nonexistent/graph.bin"));
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_load_rejects_other_format_versions() {
+        let dir = tempdir().unwrap();
+        let save_path = dir.path().join("graph.bin");
+        CodeGraph::new().save(&save_path).unwrap();
+
+        // A newer format version
+        let mut bytes = fs::read(&save_path).unwrap();
+        bytes[MAGIC.len()..MAGIC.len() + 4].copy_from_slice(&(FORMAT_VERSION + 1).to_le_bytes());
+        fs::write(&save_path, &bytes).unwrap();
+        let result = CodeGraph::load(&save_path);
+        assert!(matches!(
+            result,
+            Err(AnchorError::CacheVersionMismatch(_, Some(v), _)) if v == FORMAT_VERSION + 1
+        ));
+
+        // An unversioned cache from before the header existed
+        fs::write(&save_path, &bytes[MAGIC.len() + 4..]).unwrap();
+        let result = CodeGraph::load(&save_path);
+        assert!(matches!(
+            result,
+            Err(AnchorError::CacheVersionMismatch(_, None, _))
+        ));
+    }
+
+    #[test]
+    fn test_settings_fingerprint_follows_config_and_queries() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        let cache_path = root.join(".anchor/graph.bin");
+        fs::create_dir_all(root.join(QUERIES_DIR)).unwrap();
+        let config = AnchorConfig::default();
+        let settings = SettingsFingerprint::for_project(root, &config).unwrap();
+        assert_eq!(SettingsFingerprint::saved(&cache_path), None);
+        settings.save(&cache_path).unwrap();
+        assert_eq!(SettingsFingerprint::saved(&cache_path), Some(settings));
+        assert_eq!(
+            SettingsFingerprint::for_project(root, &config).unwrap(),
+            settings
+        );
+
+        let mut narrowed = config.clone();
+        narrowed.project.languages = vec!["rust".to_string()];
+        assert_ne!(
+            SettingsFingerprint::for_project(root, &narrowed).unwrap(),
+            settings
+        );
+
+        fs::write(
+            root.join(QUERIES_DIR).join("rust.scm"),
+            "(function_item) @definition.function",
+        )
+        .unwrap();
+        let overridden = SettingsFingerprint::for_project(root, &config).unwrap();
+        assert_ne!(overridden, settings);
+        fs::write(
+            root.join(QUERIES_DIR).join("rust.scm"),
+            "(struct_item) @definition.struct",
+        )
+        .unwrap();
+        assert_ne!(
+            SettingsFingerprint::for_project(root, &config).unwrap(),
+            overridden
+        );
+    }
 }
@@ -0,0 +1,130 @@
+//! Build report — what a full build indexed, skipped and struggled with.
+//!
+//! `build_graph_with_report` records, per file, how long extraction took,
+//! how many symbols it produced, where tree-sitter hit syntax errors, and
+//! why a file was skipped (unreadable, not UTF-8, parser failure). The
+//! report is saved as JSON in `.anchor/` so it can be inspected after the
+//! fact (`anchor build --report`).
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use crate::error::{AnchorError, Result};
+
+/// Outcome of one full graph build.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BuildReport {
+    /// Wall-clock time of the whole build, in milliseconds.
+    pub elapsed_ms: u64,
+    /// Every file the build considered, in path order.
+    pub files: Vec<FileReport>,
+}
+
+/// How one file fared.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileReport {
+    pub path: PathBuf,
+    /// Language (or host format, e.g. `markdown`) it was extracted as.
+    pub language: String,
+    /// Time spent reading and extracting it, in microseconds.
+    pub elapsed_us: u64,
+    /// Symbols extracted.
+    pub symbols: usize,
+    /// Lines where tree-sitter recovered from a syntax error.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub syntax_errors: Vec<usize>,
+    /// Why the file isn't in the graph, if it isn't.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub skipped: Option<String>,
+}
+
+/// Per-language totals, derived from the file reports.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LanguageCoverage {
+    pub language: String,
+    pub files: usize,
+    pub indexed: usize,
+    pub skipped: usize,
+    /// Indexed files that had syntax errors.
+    pub with_errors: usize,
+    pub symbols: usize,
+}
+
+impl BuildReport {
+    /// Files left out of the graph.
+    pub fn skipped(&self) -> impl Iterator<Item = &FileReport> {
+        self.files.iter().filter(|f| f.skipped.is_some())
+    }
+
+    /// Indexed files that parsed with syntax errors, worst first.
+    pub fn with_syntax_errors(&self) -> Vec<&FileReport> {
+        let mut files: Vec<_> = self
+            .files
+            .iter()
+            .filter(|f| f.skipped.is_none() && !f.syntax_errors.is_empty())
+            .collect();
+        files.sort_by_key(|f| std::cmp::Reverse(f.syntax_errors.len()));
+        files
+    }
+
+    /// The `n` files that took longest to extract.
+    pub fn slowest(&self, n: usize) -> Vec<&FileReport> {
+        let mut files: Vec<_> = self.files.iter().collect();
+        files.sort_by_key(|f| std::cmp::Reverse(f.elapsed_us));
+        files.truncate(n);
+        files
+    }
+
+    /// Totals per language, by language name.
+    pub fn coverage(&self) -> Vec<LanguageCoverage> {
+        let mut by_language: BTreeMap<&str, LanguageCoverage> = BTreeMap::new();
+        for file in &self.files {
+            let entry = by_language
+                .entry(&file.language)
+                .or_insert_with(|| LanguageCoverage {
+                    language: file.language.clone(),
+                    ..LanguageCoverage::default()
+                });
+            entry.files += 1;
+            if file.skipped.is_some() {
+                entry.skipped += 1;
+                continue;
+            }
+            entry.indexed += 1;
+            entry.symbols += file.symbols;
+            if !file.syntax_errors.is_empty() {
+                entry.with_errors += 1;
+            }
+        }
+        by_language.into_values().collect()
+    }
+
+    /// Save as pretty JSON, atomically (like the graph cache).
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let json = serde_json::to_vec_pretty(&self.to_json())
+            .map_err(|e| AnchorError::SerializeError(e.to_string()))?;
+        let tmp_path = path.with_extension("tmp");
+        let mut file = fs::File::create(&tmp_path)?;
+        file.write_all(&json)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+
+    /// Load a report saved by `save`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let bytes = fs::read(path)?;
+        serde_json::from_slice(&bytes)
+            .map_err(|e| AnchorError::ParseError(format!("build report: {}", e)))
+    }
+
+    /// The report with its per-language coverage, as written to disk.
+    pub fn to_json(&self) -> serde_json::Value {
+        let mut json = serde_json::to_value(self).unwrap_or_default();
+        json["languages"] = serde_json::to_value(self.coverage()).unwrap_or_default();
+        json
+    }
+}
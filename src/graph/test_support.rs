@@ -0,0 +1,79 @@
+//! Helpers for tests that build a graph from files on disk and check how
+//! it was linked, shared by the builder and parser tests.
+
+use std::path::{Path, PathBuf};
+
+use super::builder::build_graph;
+use super::engine::CodeGraph;
+use super::types::{EdgeKind, NodeKind};
+
+/// A node by content: (file, kind, name, first line).
+pub(crate) type NodeKey = (PathBuf, NodeKind, String, usize);
+
+/// Order-independent view of the live graph: nodes plus a multiset of
+/// edges, both keyed by content rather than index or id.
+pub(crate) fn snapshot(graph: &CodeGraph) -> (Vec<NodeKey>, Vec<(NodeKey, NodeKey, EdgeKind)>) {
+    let g = graph.inner_graph();
+    let key = |idx: petgraph::graph::NodeIndex| {
+        let n = &g[idx];
+        (n.file_path.clone(), n.kind, n.name.clone(), n.line_start)
+    };
+    let mut nodes: Vec<NodeKey> = g
+        .node_indices()
+        .filter(|&i| !g[i].removed)
+        .map(key)
+        .collect();
+    nodes.sort();
+    let mut edges: Vec<_> = g
+        .edge_indices()
+        .filter_map(|e| {
+            let (a, b) = g.edge_endpoints(e)?;
+            (!g[a].removed && !g[b].removed).then(|| (key(a), key(b), g[e].kind))
+        })
+        .collect();
+    edges.sort();
+    (nodes, edges)
+}
+
+/// One end of an edge for `has_edge`: a bare name, `(file, name)` or
+/// `(file, kind, name)`.
+pub(crate) trait EdgeEnd {
+    fn matches(&self, key: &NodeKey) -> bool;
+}
+
+impl EdgeEnd for &str {
+    fn matches(&self, key: &NodeKey) -> bool {
+        key.2 == *self
+    }
+}
+
+impl EdgeEnd for (&PathBuf, &str) {
+    fn matches(&self, key: &NodeKey) -> bool {
+        key.0 == *self.0 && key.2 == self.1
+    }
+}
+
+impl EdgeEnd for (&PathBuf, NodeKind, &str) {
+    fn matches(&self, key: &NodeKey) -> bool {
+        key.0 == *self.0 && key.1 == self.1 && key.2 == self.2
+    }
+}
+
+/// Whether the live graph has a `kind` edge between the two ends.
+pub(crate) fn has_edge(
+    graph: &CodeGraph,
+    from: impl EdgeEnd,
+    to: impl EdgeEnd,
+    kind: EdgeKind,
+) -> bool {
+    snapshot(graph)
+        .1
+        .iter()
+        .any(|(a, b, k)| from.matches(a) && to.matches(b) && *k == kind)
+}
+
+/// Assert that the incrementally updated `graph` is the one a fresh
+/// build of `root` produces.
+pub(crate) fn assert_matches_fresh_build(graph: &CodeGraph, root: &Path) {
+    assert_eq!(snapshot(graph), snapshot(&build_graph(root).unwrap()));
+}
@@ -5,10 +5,11 @@
 
 use serde::{Deserialize, Serialize};
 use std::fmt;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
 /// The kind of a node in the code graph.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum NodeKind {
     /// A source file.
@@ -39,6 +40,8 @@ pub enum NodeKind {
     Impl,
     /// A variable or field.
     Variable,
+    /// A macro definition (C preprocessor, `macro_rules!`).
+    Macro,
 }
 
 impl fmt::Display for NodeKind {
@@ -58,12 +61,13 @@ impl fmt::Display for NodeKind {
             NodeKind::Trait => write!(f, "trait"),
             NodeKind::Impl => write!(f, "impl"),
             NodeKind::Variable => write!(f, "variable"),
+            NodeKind::Macro => write!(f, "macro"),
         }
     }
 }
 
 /// The kind of an edge (relationship) in the code graph.
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum EdgeKind {
     /// File defines a symbol (File -> Symbol).
@@ -108,9 +112,221 @@ impl fmt::Display for EdgeKind {
     }
 }
 
+/// Who can see a symbol, normalized across languages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Visibility {
+    /// Visible everywhere (`pub`, `public`, an exported Go name).
+    Public,
+    /// Visible within the crate, package or assembly (`pub(crate)`,
+    /// Java package-private, C# `internal`, an unexported Go name).
+    Internal,
+    /// Visible to subclasses (`protected`).
+    Protected,
+    /// Visible only where it's declared (`private`, Python `_name`).
+    Private,
+}
+
+impl fmt::Display for Visibility {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Visibility::Public => write!(f, "public"),
+            Visibility::Internal => write!(f, "internal"),
+            Visibility::Protected => write!(f, "protected"),
+            Visibility::Private => write!(f, "private"),
+        }
+    }
+}
+
+impl FromStr for Visibility {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_ascii_lowercase().as_str() {
+            "public" => Ok(Visibility::Public),
+            "internal" => Ok(Visibility::Internal),
+            "protected" => Ok(Visibility::Protected),
+            "private" => Ok(Visibility::Private),
+            other => Err(format!("unknown visibility: {}", other)),
+        }
+    }
+}
+
+/// What a symbol's declaration says about it beyond its name and kind.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SymbolMetadata {
+    /// The declaration up to its body, whitespace collapsed
+    /// (e.g. `pub async fn login(user: &str) -> Result<Token>`).
+    #[serde(default)]
+    pub signature: Option<String>,
+    /// Declared visibility, or the language's default when it has one.
+    #[serde(default)]
+    pub visibility: Option<Visibility>,
+    /// Keywords such as `async`, `static`, `abstract`, `const`, plus
+    /// `deprecated` when an attribute or doc comment says so.
+    #[serde(default)]
+    pub modifiers: Vec<String>,
+    /// The doc comment or docstring, without comment markers.
+    #[serde(default)]
+    pub docstring: Option<String>,
+    /// Decorators, annotations and attributes, as written without the
+    /// `@`/`#[...]` (e.g. `property`, `derive(Debug)`, `Override`).
+    #[serde(default)]
+    pub decorators: Vec<String>,
+}
+
+impl SymbolMetadata {
+    pub fn is_empty(&self) -> bool {
+        *self == SymbolMetadata::default()
+    }
+
+    /// Whether the symbol carries a modifier (case-insensitive).
+    pub fn has_modifier(&self, modifier: &str) -> bool {
+        self.modifiers
+            .iter()
+            .any(|m| m.eq_ignore_ascii_case(modifier))
+    }
+
+    /// Whether any decorator's name (the part before its arguments)
+    /// ends with `name`: `app.get("/x")` matches `get`.
+    pub fn has_decorator(&self, name: &str) -> bool {
+        self.decorators.iter().any(|d| {
+            let head = d.split('(').next().unwrap_or(d).trim();
+            let last = head.rsplit(['.', ':', '\\']).next().unwrap_or(head);
+            head.eq_ignore_ascii_case(name) || last.eq_ignore_ascii_case(name)
+        })
+    }
+}
+
+/// A position in a source file.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct SourcePoint {
+    /// Byte offset from the start of the file.
+    pub byte: usize,
+    /// Line number (1-indexed).
+    pub line: usize,
+    /// Column in UTF-8 bytes (0-indexed).
+    pub column: usize,
+    /// Column in UTF-16 code units (0-indexed), as LSP clients count.
+    pub column_utf16: usize,
+}
+
+/// A half-open span of source text, `start` inclusive and `end` exclusive.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+pub struct SourceRange {
+    pub start: SourcePoint,
+    pub end: SourcePoint,
+}
+
+impl SourceRange {
+    /// Whether the range was never set (file nodes, older graphs).
+    pub fn is_empty(&self) -> bool {
+        *self == SourceRange::default()
+    }
+
+    /// Whether `other` lies entirely within this range.
+    pub fn contains(&self, other: &SourceRange) -> bool {
+        self.start.byte <= other.start.byte && other.end.byte <= self.end.byte
+    }
+}
+
+/// Stable identity of a graph node.
+///
+/// Unlike `NodeIndex`, which petgraph reassigns whenever the graph is
+/// rebuilt, a `NodeId` is derived from what the node *is* (file, kind,
+/// name and an occurrence ordinal), so it survives compaction, save/load
+/// and re-extraction of an unchanged symbol. Rendered as 16 hex digits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Default)]
+pub struct NodeId(pub u64);
+
+impl NodeId {
+    /// Identity of a file node.
+    pub fn for_file(path: &Path) -> Self {
+        let mut h = StableHasher::new();
+        h.write_str("file");
+        h.write_str(&path.to_string_lossy());
+        NodeId(h.finish())
+    }
+
+    /// Identity of a symbol node.
+    ///
+    /// `ordinal` disambiguates symbols that share file, kind and name
+    /// (e.g. two `new` methods in one file); the first occurrence is 0.
+    pub fn for_symbol(file_path: &Path, kind: NodeKind, name: &str, ordinal: u32) -> Self {
+        let mut h = StableHasher::new();
+        h.write_str("symbol");
+        h.write_str(&file_path.to_string_lossy());
+        h.write_str(&kind.to_string());
+        h.write_str(name);
+        h.write_bytes(&ordinal.to_le_bytes());
+        NodeId(h.finish())
+    }
+}
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:016x}", self.0)
+    }
+}
+
+impl FromStr for NodeId {
+    type Err = std::num::ParseIntError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        u64::from_str_radix(s, 16).map(NodeId)
+    }
+}
+
+// Serialized as a hex string so JSON clients don't lose precision on u64.
+impl Serialize for NodeId {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for NodeId {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// FNV-1a, 64-bit. Used instead of `DefaultHasher`, whose output is not
+/// guaranteed to stay the same across Rust releases.
+pub(crate) struct StableHasher(u64);
+
+impl StableHasher {
+    pub(crate) fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    pub(crate) fn write_bytes(&mut self, bytes: &[u8]) {
+        for &b in bytes {
+            self.0 ^= b as u64;
+            self.0 = self.0.wrapping_mul(0x0100_0000_01b3);
+        }
+    }
+
+    /// Length-prefixed so ("ab", "c") and ("a", "bc") hash differently.
+    pub(crate) fn write_str(&mut self, s: &str) {
+        self.write_bytes(&(s.len() as u64).to_le_bytes());
+        self.write_bytes(s.as_bytes());
+    }
+
+    pub(crate) fn finish(&self) -> u64 {
+        self.0
+    }
+}
+
 /// Data stored in a graph node.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NodeData {
+    /// Stable identity (see [`NodeId`]).
+    pub id: NodeId,
     /// The name of the symbol (e.g., "login", "UserService", "main.rs").
     pub name: String,
     /// What kind of code element this is.
@@ -123,6 +339,18 @@ pub struct NodeData {
     pub line_end: usize,
     /// The actual source code snippet.
     pub code_snippet: String,
+    /// Exact span of the definition; empty for file nodes.
+    #[serde(default)]
+    pub range: SourceRange,
+    /// Span of the identifier naming the definition.
+    #[serde(default)]
+    pub name_range: SourceRange,
+    /// Fully qualified name, when the extractor knows one.
+    #[serde(default)]
+    pub qualified_name: Option<String>,
+    /// Signature, visibility, modifiers, docs and decorators.
+    #[serde(default)]
+    pub metadata: SymbolMetadata,
     /// Soft-delete flag. Removed nodes are skipped in queries
     /// and cleaned up during compaction.
     #[serde(default)]
@@ -136,16 +364,23 @@ impl NodeData {
             .map(|n| n.to_string_lossy().to_string())
             .unwrap_or_default();
         Self {
+            id: NodeId::for_file(&path),
             name,
             kind: NodeKind::File,
             file_path: path,
             line_start: 0,
             line_end: 0,
             code_snippet: String::new(),
+            range: SourceRange::default(),
+            name_range: SourceRange::default(),
+            qualified_name: None,
+            metadata: SymbolMetadata::default(),
             removed: false,
         }
     }
 
+    /// Create a symbol node. The id assumes ordinal 0; `CodeGraph`
+    /// reassigns it if another live symbol already holds that identity.
     pub fn new_symbol(
         name: String,
         kind: NodeKind,
@@ -155,27 +390,142 @@ impl NodeData {
         code_snippet: String,
     ) -> Self {
         Self {
+            id: NodeId::for_symbol(&file_path, kind, &name, 0),
             name,
             kind,
             file_path,
             line_start,
             line_end,
             code_snippet,
+            range: SourceRange::default(),
+            name_range: SourceRange::default(),
+            qualified_name: None,
+            metadata: SymbolMetadata::default(),
             removed: false,
         }
     }
 }
 
+/// How a call is written.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize,
+)]
+#[serde(rename_all = "snake_case")]
+pub enum CallStyle {
+    /// A bare name: `login()`.
+    #[default]
+    Direct,
+    /// On a receiver value: `user.login()`, `$this->save()`, `self.step()`.
+    Method,
+    /// Through a type, module or namespace path: `User::new()`,
+    /// `Math.max()`, `App\helper()`.
+    Qualified,
+    /// Creates an instance: `new User()`, or calling a class by name.
+    Constructor,
+}
+
+impl fmt::Display for CallStyle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CallStyle::Direct => write!(f, "direct"),
+            CallStyle::Method => write!(f, "method"),
+            CallStyle::Qualified => write!(f, "qualified"),
+            CallStyle::Constructor => write!(f, "constructor"),
+        }
+    }
+}
+
+/// One place a `Calls` edge's caller calls its callee.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CallSite {
+    /// Line of the call (1-indexed).
+    pub line: usize,
+    /// Exact span of the call expression.
+    pub range: SourceRange,
+    /// How the call is written.
+    pub style: CallStyle,
+}
+
+/// How a usage that isn't a call refers to its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReferenceKind {
+    /// Reads a constant, variable or field: `MAX_RETRIES`, `user.name`.
+    Read,
+    /// Assigns to one: `count += 1`, `self.name = name`.
+    Write,
+    /// Mentions a type in an annotation or generic argument: `x: Config`,
+    /// `Vec<User>`.
+    Type,
+    /// Tests against a type: `instanceof User`, `isinstance(x, User)`.
+    TypeCheck,
+    /// Builds a value of a type from a literal: `Point { x, y }`.
+    Construct,
+}
+
+impl ReferenceKind {
+    /// The edge a reference of this kind becomes: `UsesType` for type
+    /// mentions, `References` for reads and writes.
+    pub fn edge_kind(self) -> EdgeKind {
+        match self {
+            ReferenceKind::Read | ReferenceKind::Write => EdgeKind::References,
+            ReferenceKind::Type | ReferenceKind::TypeCheck | ReferenceKind::Construct => {
+                EdgeKind::UsesType
+            }
+        }
+    }
+}
+
+impl fmt::Display for ReferenceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ReferenceKind::Read => write!(f, "read"),
+            ReferenceKind::Write => write!(f, "write"),
+            ReferenceKind::Type => write!(f, "type"),
+            ReferenceKind::TypeCheck => write!(f, "type_check"),
+            ReferenceKind::Construct => write!(f, "construct"),
+        }
+    }
+}
+
+/// One place a `References` or `UsesType` edge's source uses its target.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReferenceSite {
+    /// Line of the usage (1-indexed).
+    pub line: usize,
+    /// Exact span of the referring identifier.
+    pub range: SourceRange,
+    /// How the target is used.
+    pub kind: ReferenceKind,
+}
+
 /// Data stored on a graph edge.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EdgeData {
     /// The kind of relationship.
     pub kind: EdgeKind,
+    /// For `Calls`, every call from the caller to the callee, in source
+    /// order. Repeated calls share one edge.
+    #[serde(default)]
+    pub call_sites: Vec<CallSite>,
+    /// For `References` and `UsesType`, every usage of the target, in
+    /// source order.
+    #[serde(default)]
+    pub reference_sites: Vec<ReferenceSite>,
 }
 
 impl EdgeData {
     pub fn new(kind: EdgeKind) -> Self {
-        Self { kind }
+        Self {
+            kind,
+            call_sites: Vec::new(),
+            reference_sites: Vec::new(),
+        }
+    }
+
+    /// Number of calls the edge stands for; 0 for other edges.
+    pub fn call_count(&self) -> usize {
+        self.call_sites.len()
     }
 }
 
@@ -193,8 +543,17 @@ pub struct ExtractedSymbol {
     pub line_end: usize,
     /// The source code of this symbol.
     pub code_snippet: String,
+    /// Exact span of the definition.
+    pub range: SourceRange,
+    /// Span of the identifier naming the definition.
+    pub name_range: SourceRange,
     /// Parent symbol name (for methods inside classes/impls).
     pub parent: Option<String>,
+    /// Fully qualified name, e.g. `com.acme.UserService#findById`:
+    /// package and enclosing types joined by `.`, members after `#`.
+    pub qualified_name: Option<String>,
+    /// Signature, visibility, modifiers, docs and decorators.
+    pub metadata: SymbolMetadata,
 }
 
 /// An import extracted from a source file.
@@ -206,10 +565,29 @@ pub struct ExtractedImport {
     pub symbols: Vec<String>,
     /// Line number of the import.
     pub line: usize,
+    /// The file, or package directory, the import refers to within the
+    /// project, when it could be resolved (e.g. via `go.mod`).
+    pub resolved: Option<PathBuf>,
+    /// Every path the import was tried against, in order; `resolved` is
+    /// the first that exists. Empty when resolution doesn't depend on
+    /// which files exist (Go packages).
+    pub candidates: Vec<PathBuf>,
+}
+
+impl ExtractedImport {
+    /// What the import links to in the graph: the first of its candidates
+    /// that is indexed (so it follows files as they come and go), or the
+    /// path it resolved to. None for imports outside the project.
+    pub fn target(&self) -> Option<RelationTarget> {
+        if !self.candidates.is_empty() {
+            return Some(RelationTarget::FirstOf(self.candidates.clone()));
+        }
+        self.resolved.clone().map(RelationTarget::Path)
+    }
 }
 
 /// A function call extracted from a source file.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct ExtractedCall {
     /// The name of the function being called.
     pub callee: String,
@@ -217,6 +595,72 @@ pub struct ExtractedCall {
     pub caller: String,
     /// Line number of the call.
     pub line: usize,
+    /// Exact span of the call expression.
+    #[serde(default)]
+    pub range: SourceRange,
+    /// How the call is written.
+    #[serde(default)]
+    pub style: CallStyle,
+}
+
+impl ExtractedCall {
+    /// Where the call happens, as recorded on its `Calls` edge.
+    pub fn site(&self) -> CallSite {
+        CallSite {
+            line: self.line,
+            range: self.range,
+            style: self.style,
+        }
+    }
+}
+
+/// What an extracted relation points at.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum RelationTarget {
+    /// A symbol found by name: in the same file if possible, then the
+    /// same directory, then anywhere.
+    Symbol(String),
+    /// A file, or every file directly inside a directory.
+    Path(PathBuf),
+    /// The first of these paths (each as for `Path`) with anything in
+    /// the graph: an import's candidate files, in resolution order.
+    FirstOf(Vec<PathBuf>),
+}
+
+/// A relationship from a symbol in this file to something that is only
+/// resolved when the graph is built and may live in another file, such
+/// as a Go method's receiver type or an import's package directory.
+///
+/// The edge runs from `source` to the target, except for `Contains`,
+/// where the target is the container holding `source`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExtractedRelation {
+    /// Kind of edge to create.
+    pub kind: EdgeKind,
+    /// Name of the symbol (or import path) in this file; empty for a
+    /// relation from the file itself, such as `Exports`.
+    pub source: String,
+    /// What the relation points at.
+    pub target: RelationTarget,
+    /// Line the relation was found on.
+    pub line: usize,
+    /// Exact span of the relation's target as written.
+    #[serde(default)]
+    pub range: SourceRange,
+    /// For a usage (`@reference.<kind>`), how it uses the target.
+    #[serde(default)]
+    pub reference: Option<ReferenceKind>,
+}
+
+impl ExtractedRelation {
+    /// Where the usage happens, for relations that are usages.
+    pub fn site(&self) -> Option<ReferenceSite> {
+        Some(ReferenceSite {
+            line: self.line,
+            range: self.range,
+            kind: self.reference?,
+        })
+    }
 }
 
 /// All extracted information from a single source file.
@@ -230,6 +674,49 @@ pub struct FileExtractions {
     pub imports: Vec<ExtractedImport>,
     /// Function/method calls.
     pub calls: Vec<ExtractedCall>,
+    /// Relations resolved against other files when the graph is built.
+    pub relations: Vec<ExtractedRelation>,
+    /// Lines of the syntax errors tree-sitter recovered from; empty when
+    /// the file parsed cleanly.
+    pub syntax_errors: Vec<usize>,
+}
+
+impl FileExtractions {
+    /// Sorted (kind, name) list of everything this file contributes as a
+    /// node. The watcher recognises a rename by how much of it a new file
+    /// shares with a vanished one.
+    pub fn signature(&self) -> Vec<(NodeKind, String)> {
+        let mut sig: Vec<(NodeKind, String)> = self
+            .symbols
+            .iter()
+            .map(|s| (s.kind, s.name.clone()))
+            .chain(
+                self.imports
+                    .iter()
+                    .map(|i| (NodeKind::Import, i.path.clone())),
+            )
+            .collect();
+        sig.sort();
+        sig
+    }
+}
+
+/// A batch of file changes applied to the graph in one step.
+#[derive(Debug, Clone, Default)]
+pub struct GraphUpdate {
+    /// Files that no longer exist (or are no longer indexed).
+    pub removed: Vec<PathBuf>,
+    /// New or modified files, freshly extracted.
+    pub changed: Vec<FileExtractions>,
+    /// Renamed files: (old path, extraction at the new path). Symbols keep
+    /// the ids they had under the old path.
+    pub renamed: Vec<(PathBuf, FileExtractions)>,
+}
+
+impl GraphUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.removed.is_empty() && self.changed.is_empty() && self.renamed.is_empty()
+    }
 }
 
 // ─── Graph Search Results ─────────────────────────────────────────────────────
@@ -254,10 +741,13 @@ pub struct GraphSearchResult {
 /// Information about a symbol in search results.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolInfo {
+    pub id: NodeId,
     pub name: String,
     pub kind: NodeKind,
     pub file: PathBuf,
     pub line: usize,
+    #[serde(default)]
+    pub range: SourceRange,
     pub code: String,
 }
 
@@ -47,7 +47,10 @@ pub async fn execute(schema: &AnchorSchema, query: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::graph::CodeGraph;
+    use crate::graph::{
+        CallSite, CallStyle, CodeGraph, EdgeData, EdgeKind, NodeKind, ReferenceKind, ReferenceSite,
+    };
+    use std::path::PathBuf;
 
     #[tokio::test]
     async fn test_stats_query() {
@@ -72,4 +75,93 @@ mod tests {
         assert!(result.contains("symbol"));
         assert!(!result.contains("error"));
     }
+
+    #[tokio::test]
+    async fn test_call_sites_of_same_named_methods() {
+        let mut graph = CodeGraph::new();
+        let mut method = |file: &str, name: &str, line: usize| {
+            graph.add_symbol(
+                name.to_string(),
+                NodeKind::Method,
+                PathBuf::from(file),
+                line,
+                line,
+                String::new(),
+            )
+        };
+        let user_save = method("user.rs", "save", 2);
+        let _order_save = method("order.rs", "save", 2);
+        let run = method("main.rs", "run", 1);
+        let mut calls = EdgeData::new(EdgeKind::Calls);
+        calls.call_sites.push(CallSite {
+            line: 3,
+            range: Default::default(),
+            style: CallStyle::Method,
+        });
+        graph.inner_graph_mut().add_edge(run, user_save, calls);
+        let schema = build_schema(Arc::new(graph));
+
+        let result = execute(
+            &schema,
+            r#"{ symbol(name: "save", exact: true) { file callSites { caller line } } }"#,
+        )
+        .await;
+        let result: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let symbols = result["data"]["symbol"].as_array().unwrap();
+        assert_eq!(symbols.len(), 2);
+        for symbol in symbols {
+            let sites = symbol["callSites"].as_array().unwrap();
+            if symbol["file"] == "user.rs" {
+                assert_eq!(sites.len(), 1);
+                assert_eq!(sites[0]["caller"], "run");
+            } else {
+                assert!(sites.is_empty());
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn test_usages_of_same_named_fields() {
+        let mut graph = CodeGraph::new();
+        let mut symbol = |file: &str, name: &str, kind: NodeKind| {
+            graph.add_symbol(
+                name.to_string(),
+                kind,
+                PathBuf::from(file),
+                1,
+                1,
+                String::new(),
+            )
+        };
+        let user_name = symbol("user.rs", "name", NodeKind::Variable);
+        let _team_name = symbol("team.rs", "name", NodeKind::Variable);
+        let greet = symbol("main.rs", "greet", NodeKind::Function);
+        let mut reads = EdgeData::new(EdgeKind::References);
+        reads.reference_sites.push(ReferenceSite {
+            line: 2,
+            range: Default::default(),
+            kind: ReferenceKind::Read,
+        });
+        graph.inner_graph_mut().add_edge(greet, user_name, reads);
+        let schema = build_schema(Arc::new(graph));
+
+        let result = execute(
+            &schema,
+            r#"{ symbol(name: "name", exact: true) { file usages { user kind } } }"#,
+        )
+        .await;
+        let result: serde_json::Value = serde_json::from_str(&result).unwrap();
+        let symbols = result["data"]["symbol"].as_array().unwrap();
+        assert_eq!(symbols.len(), 2);
+        for symbol in symbols {
+            let usages = symbol["usages"].as_array().unwrap();
+            if symbol["file"] == "user.rs" {
+                assert_eq!(usages.len(), 1);
+                assert_eq!(usages[0]["user"], "greet");
+                assert_eq!(usages[0]["kind"], "read");
+            } else {
+                assert!(usages.is_empty());
+            }
+        }
+    }
 }
@@ -5,8 +5,8 @@
 use async_graphql::{Context, Object, Result};
 use std::sync::Arc;
 
-use super::schema::{File, Stats, Symbol};
-use crate::graph::CodeGraph;
+use super::schema::{File, Stats, Symbol, SymbolFilter};
+use crate::graph::{CodeGraph, NodeId};
 use crate::regex::{parse, Matcher};
 
 /// Root query type
@@ -31,9 +31,11 @@ impl Query {
         name: String,
         #[graphql(default = false)] exact: bool,
         #[graphql(default)] pattern: Option<String>,
+        #[graphql(default)] filter: SymbolFilter,
     ) -> Result<Vec<Symbol>> {
         let graph = ctx.data::<Arc<CodeGraph>>()?;
-        let results = graph.search(&name, 50); // Get more for pattern filtering
+        let mut results = graph.search(&name, 50); // Get more for pattern filtering
+        results.retain(|r| filter.matches(&r.metadata));
 
         let filtered: Vec<_> = if let Some(ref pat) = pattern {
             // Use Brzozowski derivatives regex - ReDoS-safe
@@ -59,6 +61,7 @@ impl Query {
             .into_iter()
             .take(10)
             .map(|r| Symbol {
+                id: r.id.to_string(),
                 name: r.symbol,
                 kind: r.kind.to_string(),
                 file: r.file.to_string_lossy().to_string(),
@@ -68,6 +71,22 @@ impl Query {
             .collect())
     }
 
+    /// Look up a symbol by its stable id
+    async fn node(&self, ctx: &Context<'_>, id: String) -> Result<Option<Symbol>> {
+        let graph = ctx.data::<Arc<CodeGraph>>()?;
+        let id: NodeId = id
+            .parse()
+            .map_err(|_| async_graphql::Error::new(format!("invalid node id: {}", id)))?;
+        Ok(graph.node_by_id(id).map(|n| Symbol {
+            id: n.id.to_string(),
+            name: n.name.clone(),
+            kind: n.kind.to_string(),
+            file: n.file_path.to_string_lossy().to_string(),
+            line: n.line_start as i32,
+            code_internal: Some(n.code_snippet.clone()),
+        }))
+    }
+
     /// Get a file and its symbols
     async fn file(&self, ctx: &Context<'_>, path: String) -> Result<File> {
         let graph = ctx.data::<Arc<CodeGraph>>()?;
@@ -86,6 +105,7 @@ impl Query {
             .into_iter()
             .take(50)
             .map(|d| Symbol {
+                id: d.id.to_string(),
                 name: d.symbol,
                 kind: d.kind.to_string(),
                 file: d.file.to_string_lossy().to_string(),
@@ -103,6 +123,7 @@ impl Query {
             .into_iter()
             .take(50)
             .map(|d| Symbol {
+                id: d.id.to_string(),
                 name: d.symbol,
                 kind: d.kind.to_string(),
                 file: d.file.to_string_lossy().to_string(),
@@ -141,6 +162,7 @@ impl Query {
         ctx: &Context<'_>,
         pattern: String,
         #[graphql(default = 20)] limit: i32,
+        #[graphql(default)] filter: SymbolFilter,
     ) -> Result<Vec<Symbol>> {
         let graph = ctx.data::<Arc<CodeGraph>>()?;
         let regex = parse(&pattern).map_err(|e| async_graphql::Error::new(e.to_string()))?;
@@ -151,8 +173,10 @@ impl Query {
         let matched: Vec<_> = all_symbols
             .into_iter()
             .filter(|r| matcher.is_match(&r.symbol))
+            .filter(|r| filter.matches(&r.metadata))
             .take(limit as usize)
             .map(|r| Symbol {
+                id: r.id.to_string(),
                 name: r.symbol,
                 kind: r.kind.to_string(),
                 file: r.file.to_string_lossy().to_string(),
@@ -2,15 +2,19 @@
 //!
 //! These types are returned by queries and define the shape of responses.
 
-use async_graphql::{ComplexObject, Context, Result, SimpleObject};
+use async_graphql::{ComplexObject, Context, InputObject, Result, SimpleObject};
 use std::sync::Arc;
 
-use crate::graph::CodeGraph;
+use crate::graph::{
+    CodeGraph, EdgeKind, NodeData, NodeId, SourcePoint, SourceRange, SymbolMetadata,
+};
 
 /// A code symbol (function, class, struct, etc.)
 #[derive(SimpleObject)]
 #[graphql(complex)]
 pub struct Symbol {
+    /// Stable node id (survives rebuilds)
+    pub id: String,
     /// Symbol name
     pub name: String,
     /// Kind: function, class, struct, method, etc.
@@ -31,6 +35,45 @@ impl Symbol {
         self.code_internal.as_deref()
     }
 
+    /// Declaration line(s) up to the body
+    async fn signature(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        Ok(self.metadata(ctx)?.signature)
+    }
+
+    /// public, internal, protected or private
+    async fn visibility(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        Ok(self.metadata(ctx)?.visibility.map(|v| v.to_string()))
+    }
+
+    /// Modifiers such as static, async, abstract, deprecated
+    async fn modifiers(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        Ok(self.metadata(ctx)?.modifiers)
+    }
+
+    /// Doc comment or docstring, comment markers stripped
+    async fn docstring(&self, ctx: &Context<'_>) -> Result<Option<String>> {
+        Ok(self.metadata(ctx)?.docstring)
+    }
+
+    /// Decorators, annotations or attributes
+    async fn decorators(&self, ctx: &Context<'_>) -> Result<Vec<String>> {
+        Ok(self.metadata(ctx)?.decorators)
+    }
+
+    /// Exact span of the definition
+    async fn range(&self, ctx: &Context<'_>) -> Result<Option<Range>> {
+        Ok(self
+            .with_node(ctx, |n| Range::from_source(&n.range))?
+            .flatten())
+    }
+
+    /// Span of the identifier naming the definition
+    async fn name_range(&self, ctx: &Context<'_>) -> Result<Option<Range>> {
+        Ok(self
+            .with_node(ctx, |n| Range::from_source(&n.name_range))?
+            .flatten())
+    }
+
     /// Symbols that call/use this symbol
     async fn callers(&self, ctx: &Context<'_>) -> Result<Vec<Symbol>> {
         let graph = ctx.data::<Arc<CodeGraph>>()?;
@@ -39,6 +82,7 @@ impl Symbol {
             .into_iter()
             .take(20) // Limit to prevent explosion
             .map(|d| Symbol {
+                id: d.id.to_string(),
                 name: d.symbol,
                 kind: d.kind.to_string(),
                 file: d.file.to_string_lossy().to_string(),
@@ -48,6 +92,54 @@ impl Symbol {
             .collect())
     }
 
+    /// Every place this symbol is called, with the calling symbol
+    async fn call_sites(&self, ctx: &Context<'_>) -> Result<Vec<CallSite>> {
+        let graph = ctx.data::<Arc<CodeGraph>>()?;
+        let Ok(id) = self.id.parse::<NodeId>() else {
+            return Ok(Vec::new());
+        };
+        let deps = graph.dependents_of(id, &[EdgeKind::Calls]);
+        Ok(deps
+            .into_iter()
+            .flat_map(|d| {
+                let caller = d.symbol.clone();
+                let file = d.file.to_string_lossy().to_string();
+                d.call_sites.into_iter().map(move |site| CallSite {
+                    caller: caller.clone(),
+                    file: file.clone(),
+                    line: site.line as i32,
+                    range: Range::from_source(&site.range),
+                    style: site.style.to_string(),
+                })
+            })
+            .take(100)
+            .collect())
+    }
+
+    /// Every read, write or type mention of this symbol, with the using symbol
+    async fn usages(&self, ctx: &Context<'_>) -> Result<Vec<Usage>> {
+        let graph = ctx.data::<Arc<CodeGraph>>()?;
+        let Ok(id) = self.id.parse::<NodeId>() else {
+            return Ok(Vec::new());
+        };
+        let deps = graph.dependents_of(id, &[EdgeKind::References, EdgeKind::UsesType]);
+        Ok(deps
+            .into_iter()
+            .flat_map(|d| {
+                let user = d.symbol.clone();
+                let file = d.file.to_string_lossy().to_string();
+                d.reference_sites.into_iter().map(move |site| Usage {
+                    user: user.clone(),
+                    file: file.clone(),
+                    line: site.line as i32,
+                    range: Range::from_source(&site.range),
+                    kind: site.kind.to_string(),
+                })
+            })
+            .take(100)
+            .collect())
+    }
+
     /// Symbols this symbol calls/uses
     async fn callees(&self, ctx: &Context<'_>) -> Result<Vec<Symbol>> {
         let graph = ctx.data::<Arc<CodeGraph>>()?;
@@ -56,6 +148,7 @@ impl Symbol {
             .into_iter()
             .take(20)
             .map(|d| Symbol {
+                id: d.id.to_string(),
                 name: d.symbol,
                 kind: d.kind.to_string(),
                 file: d.file.to_string_lossy().to_string(),
@@ -66,6 +159,123 @@ impl Symbol {
     }
 }
 
+impl Symbol {
+    /// Read from this symbol's graph node, if it still exists.
+    fn with_node<T>(&self, ctx: &Context<'_>, f: impl FnOnce(&NodeData) -> T) -> Result<Option<T>> {
+        let graph = ctx.data::<Arc<CodeGraph>>()?;
+        Ok(self
+            .id
+            .parse::<NodeId>()
+            .ok()
+            .and_then(|id| graph.node_by_id(id))
+            .map(f))
+    }
+
+    fn metadata(&self, ctx: &Context<'_>) -> Result<SymbolMetadata> {
+        Ok(self
+            .with_node(ctx, |n| n.metadata.clone())?
+            .unwrap_or_default())
+    }
+}
+
+/// One call of a symbol
+#[derive(SimpleObject)]
+pub struct CallSite {
+    /// Name of the calling symbol
+    pub caller: String,
+    /// File the call is in
+    pub file: String,
+    /// Line of the call
+    pub line: i32,
+    /// Exact span of the call expression
+    pub range: Option<Range>,
+    /// direct, method, qualified or constructor
+    pub style: String,
+}
+
+/// One non-call usage of a symbol
+#[derive(SimpleObject)]
+pub struct Usage {
+    /// Name of the symbol containing the usage (empty at file level)
+    pub user: String,
+    /// File the usage is in
+    pub file: String,
+    /// Line of the usage
+    pub line: i32,
+    /// Exact span of the identifier
+    pub range: Option<Range>,
+    /// read, write, type, type_check or construct
+    pub kind: String,
+}
+
+/// A span of source text; `end` is exclusive
+#[derive(SimpleObject)]
+pub struct Range {
+    pub start: Position,
+    pub end: Position,
+}
+
+impl Range {
+    fn from_source(range: &SourceRange) -> Option<Self> {
+        (!range.is_empty()).then(|| Self {
+            start: Position::from(range.start),
+            end: Position::from(range.end),
+        })
+    }
+}
+
+/// A position in a source file
+#[derive(SimpleObject)]
+pub struct Position {
+    /// Byte offset from the start of the file
+    pub byte: i32,
+    /// Line number (1-indexed)
+    pub line: i32,
+    /// Column in UTF-8 bytes (0-indexed)
+    pub column: i32,
+    /// Column in UTF-16 code units (0-indexed), as LSP counts
+    pub column_utf16: i32,
+}
+
+impl From<SourcePoint> for Position {
+    fn from(point: SourcePoint) -> Self {
+        Self {
+            byte: point.byte as i32,
+            line: point.line as i32,
+            column: point.column as i32,
+            column_utf16: point.column_utf16 as i32,
+        }
+    }
+}
+
+/// Optional metadata filters for symbol queries; unset fields match anything
+#[derive(InputObject, Default)]
+pub struct SymbolFilter {
+    /// public, internal, protected or private
+    pub visibility: Option<String>,
+    /// A modifier the symbol must carry, e.g. async or static
+    pub modifier: Option<String>,
+    /// A decorator, annotation or attribute the symbol must carry
+    pub decorator: Option<String>,
+}
+
+impl SymbolFilter {
+    pub fn matches(&self, metadata: &SymbolMetadata) -> bool {
+        self.visibility.as_deref().is_none_or(|v| {
+            metadata
+                .visibility
+                .is_some_and(|mv| mv.to_string().eq_ignore_ascii_case(v))
+        }) && self
+            .modifier
+            .as_deref()
+            .is_none_or(|m| metadata.has_modifier(m))
+            && self
+                .decorator
+                .as_deref()
+                .is_none_or(|d| metadata.has_decorator(d))
+    }
+}
+
 /// File with its symbols
 #[derive(SimpleObject)]
 #[graphql(complex)]
@@ -85,6 +295,7 @@ impl File {
         Ok(symbols
             .into_iter()
             .map(|s| Symbol {
+                id: s.id.to_string(),
                 name: s.name.clone(),
                 kind: s.kind.to_string(),
                 file: s.file_path.to_string_lossy().to_string(),
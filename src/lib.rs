@@ -18,8 +18,8 @@
 //! use anchor::{build_graph, get_context};
 //! use std::path::Path;
 //!
-//! // Build graph from project directory
-//! let graph = build_graph(Path::new("."));
+//! // Build graph from project directory (fails on an invalid config)
+//! let graph = build_graph(Path::new(".")).unwrap();
 //!
 //! // Query for a symbol with full context
 //! let result = get_context(&graph, "login", "understand");
@@ -215,7 +215,7 @@ const API_URL = "https://api.example.com";
     fn test_build_graph_self() {
         use std::path::Path;
         let src_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src");
-        let graph = build_graph(&src_dir);
+        let graph = build_graph(&src_dir).unwrap();
 
         let stats = graph.stats();
         assert!(stats.file_count > 0);
@@ -265,6 +265,9 @@ fn check_password(pw: &str) -> bool {
                 symbol: "validate".to_string(),
                 kind: Some("function".to_string()),
                 file: None,
+                visibility: None,
+                modifier: None,
+                decorator: None,
             },
         );
         assert!(response.found);
@@ -280,7 +283,7 @@ fn check_password(pw: &str) -> bool {
     #[test]
     fn test_extract_unsupported_language() {
         use std::path::PathBuf;
-        let path = PathBuf::from("main.lua");
+        let path = PathBuf::from("main.zig");
         let result = parser::extract_file(&path, "print('hello')");
         assert!(result.is_err());
         assert!(matches!(
@@ -492,7 +495,7 @@ mod benchmarks {
     #[test]
     fn benchmark_search() {
         let repo_path = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("src");
-        let graph = build_graph(&repo_path);
+        let graph = build_graph(&repo_path).unwrap();
 
         let start = std::time::Instant::now();
         let _result = graph_search(&graph, "CodeGraph", 2);
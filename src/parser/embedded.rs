@@ -0,0 +1,753 @@
+//! Embedded languages — code inside files of another format.
+//!
+//! Vue and Svelte components, HTML pages, Markdown documents and Jupyter
+//! notebooks aren't parsed themselves, but the code in them is: their
+//! `<script>` blocks, fenced code blocks and code cells. Each region is
+//! extracted with its own language's tag query and everything found is
+//! attributed to the host file.
+//!
+//! Script blocks and fences are parsed in place with tree-sitter's
+//! included ranges, so lines and byte offsets are the host file's.
+//! Notebook cells are JSON strings: they are decoded into one source and
+//! its lines mapped back to the notebook's, leaving byte ranges empty.
+//!
+//! SQL in string literals is embedded code too, inside any language: a
+//! query like `"SELECT * FROM users WHERE id = ?"` is parsed with the SQL
+//! grammar and the tables it names become type usages of the enclosing
+//! symbol (see `sql_tables`). Strings are only taken for SQL when they
+//! start with a query keyword and parse cleanly, so prose such as
+//! `"Select a file"` stays a string.
+
+use std::path::Path;
+use tree_sitter::{Node, Range};
+
+use super::extractor::{
+    extract_file_with, extract_ranges, parse, point_at, source_range, ExtractOptions,
+};
+use super::language::SupportedLanguage;
+use super::tags::TagQuery;
+use crate::graph::types::{FileExtractions, SourceRange};
+
+/// A file format that embeds code in other languages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum HostFormat {
+    Vue,
+    Svelte,
+    Html,
+    Markdown,
+    Notebook,
+}
+
+impl HostFormat {
+    /// Every host format.
+    pub const ALL: &'static [HostFormat] = &[
+        HostFormat::Vue,
+        HostFormat::Svelte,
+        HostFormat::Html,
+        HostFormat::Markdown,
+        HostFormat::Notebook,
+    ];
+
+    /// Parse a host format name as used in `project.languages`.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "vue" => Some(HostFormat::Vue),
+            "svelte" => Some(HostFormat::Svelte),
+            "html" => Some(HostFormat::Html),
+            "markdown" | "md" => Some(HostFormat::Markdown),
+            "notebook" | "ipynb" | "jupyter" => Some(HostFormat::Notebook),
+            _ => None,
+        }
+    }
+
+    /// Detect a host format from file extension.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()? {
+            "vue" => Some(HostFormat::Vue),
+            "svelte" => Some(HostFormat::Svelte),
+            "html" | "htm" => Some(HostFormat::Html),
+            "md" | "markdown" => Some(HostFormat::Markdown),
+            "ipynb" => Some(HostFormat::Notebook),
+            _ => None,
+        }
+    }
+
+    /// Lowercase name, as used in `project.languages` and shown in build
+    /// reports.
+    pub fn name(&self) -> &'static str {
+        match self {
+            HostFormat::Vue => "vue",
+            HostFormat::Svelte => "svelte",
+            HostFormat::Html => "html",
+            HostFormat::Markdown => "markdown",
+            HostFormat::Notebook => "notebook",
+        }
+    }
+}
+
+/// A byte span of a host file holding code in one language.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Region {
+    pub language: SupportedLanguage,
+    pub start: usize,
+    pub end: usize,
+}
+
+/// The embedded code regions of `source`, in document order. Notebooks
+/// have none: their cells aren't spans of the file (see module docs).
+pub fn regions(format: HostFormat, source: &str) -> Vec<Region> {
+    match format {
+        HostFormat::Vue | HostFormat::Svelte | HostFormat::Html => script_regions(source),
+        HostFormat::Markdown => fence_regions(source),
+        HostFormat::Notebook => Vec::new(),
+    }
+}
+
+/// Extract every embedded region of a host file. `query_for` gives the
+/// query and options for a language, or None to skip its regions (e.g.
+/// when it isn't enabled in the config).
+pub fn extract_embedded<'q>(
+    path: &Path,
+    source: &str,
+    format: HostFormat,
+    query_for: impl Fn(SupportedLanguage) -> Option<(&'q TagQuery, ExtractOptions)>,
+) -> crate::error::Result<FileExtractions> {
+    let mut extractions = FileExtractions {
+        file_path: path.to_path_buf(),
+        symbols: Vec::new(),
+        imports: Vec::new(),
+        calls: Vec::new(),
+        relations: Vec::new(),
+        syntax_errors: Vec::new(),
+    };
+
+    if format == HostFormat::Notebook {
+        if let Some(notebook) = Notebook::parse(source) {
+            if let Some((query, options)) = query_for(notebook.language) {
+                let mut part = extract_file_with(path, &notebook.source, query, &options)?;
+                notebook.map_lines(&mut part);
+                merge(&mut extractions, part);
+            }
+        }
+        return Ok(extractions);
+    }
+
+    for region in regions(format, source) {
+        let Some((query, options)) = query_for(region.language) else {
+            continue;
+        };
+        let range = Range {
+            start_byte: region.start,
+            end_byte: region.end,
+            start_point: point_at(source, region.start),
+            end_point: point_at(source, region.end),
+        };
+        merge(
+            &mut extractions,
+            extract_ranges(path, source, query, &options, &[range])?,
+        );
+    }
+    Ok(extractions)
+}
+
+fn merge(into: &mut FileExtractions, part: FileExtractions) {
+    into.symbols.extend(part.symbols);
+    into.imports.extend(part.imports);
+    into.calls.extend(part.calls);
+    into.relations.extend(part.relations);
+    into.syntax_errors.extend(part.syntax_errors);
+}
+
+// ─── SQL Strings ────────────────────────────────────────────────
+
+/// Keywords a string must start with to be taken for a SQL query.
+const SQL_KEYWORDS: &[&str] = &["select", "insert", "update", "delete", "with"];
+
+/// Tables named by the SQL queries in string literals under `root` (only
+/// those touching `within`, when given): (string node, table name, range
+/// of the name in `source`).
+pub(crate) fn sql_tables<'tree>(
+    path: &Path,
+    root: Node<'tree>,
+    source: &str,
+    within: Option<std::ops::Range<usize>>,
+) -> Vec<(Node<'tree>, String, SourceRange)> {
+    let mut strings = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if within
+            .as_ref()
+            .is_some_and(|r| node.end_byte() <= r.start || node.start_byte() >= r.end)
+        {
+            continue;
+        }
+        if node.is_named() && node.kind().contains("string") {
+            if let Some(body) = sql_body(node, source) {
+                strings.push((node, body));
+            }
+            continue;
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.children(&mut cursor));
+    }
+    if strings.is_empty() {
+        return Vec::new();
+    }
+
+    // Placeholders are rewritten to `?` in place, keeping every offset
+    let mut normalized = source.as_bytes().to_vec();
+    for (_, body) in &strings {
+        normalize_placeholders(&mut normalized[body.clone()]);
+    }
+    let Ok(normalized) = String::from_utf8(normalized) else {
+        return Vec::new();
+    };
+
+    let mut tables = Vec::new();
+    for (string, body) in strings {
+        let range = Range {
+            start_byte: body.start,
+            end_byte: body.end,
+            start_point: point_at(source, body.start),
+            end_point: point_at(source, body.end),
+        };
+        let Ok(tree) = parse(path, &normalized, SupportedLanguage::Sql, &[range], None) else {
+            continue;
+        };
+        if tree.root_node().has_error() {
+            continue;
+        }
+        let mut ctes = Vec::new();
+        let mut named = Vec::new();
+        let mut stack = vec![tree.root_node()];
+        while let Some(node) = stack.pop() {
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+            let text = |n: Node| n.utf8_text(source.as_bytes()).unwrap_or("").to_string();
+            match node.kind() {
+                "cte" => ctes.extend(node.named_child(0).map(text)),
+                "object_reference" if is_table_position(node) => {
+                    if let Some(name) = node.child_by_field_name("name") {
+                        named.push((text(name), source_range(name, source.as_bytes())));
+                    }
+                }
+                _ => {}
+            }
+        }
+        named.sort_by_key(|(_, range)| range.start.byte);
+        tables.extend(
+            named
+                .into_iter()
+                .filter(|(name, _)| !ctes.contains(name))
+                .map(|(name, range)| (string, name, range)),
+        );
+    }
+    tables
+}
+
+/// Byte span of a string literal's contents when they look like a SQL
+/// query: the text between its quotes (`"`, `'`, backticks, tripled or
+/// with prefixes like `r#"` and `@"`) starting with a query keyword.
+fn sql_body(string: Node, source: &str) -> Option<std::ops::Range<usize>> {
+    let text = string.utf8_text(source.as_bytes()).ok()?;
+    let open = text.find(['"', '\'', '`'])?;
+    let quote = text[open..].chars().next()?;
+    let quotes = text[open..].chars().take_while(|&c| c == quote).count();
+    let inner = text.trim_end_matches('#');
+    let close = inner.len()
+        - inner
+            .chars()
+            .rev()
+            .take(quotes)
+            .take_while(|&c| c == quote)
+            .count();
+    let body = open + quotes..close;
+    let contents = text.get(body.clone())?;
+    let trimmed = contents.trim_start();
+    let keyword = trimmed.split(|c: char| !c.is_ascii_alphabetic()).next()?;
+    let spaced = trimmed[keyword.len()..].starts_with(char::is_whitespace);
+    (spaced && SQL_KEYWORDS.contains(&keyword.to_ascii_lowercase().as_str())).then(|| {
+        let start = string.start_byte() + body.start + (contents.len() - trimmed.len());
+        start..string.start_byte() + body.end
+    })
+}
+
+/// Rewrite the placeholders of the drivers and string interpolations the
+/// SQL grammar doesn't know (`%s`, `%(name)s`, `:name`, `@name`, `$name`,
+/// `{name}`, `${expr}`, `#{expr}`) to `?` padded with spaces.
+fn normalize_placeholders(sql: &mut [u8]) {
+    let mut i = 0;
+    while i < sql.len() {
+        let next = sql.get(i + 1).copied().unwrap_or(0);
+        let word = |from: usize| {
+            from + sql[from..]
+                .iter()
+                .take_while(|b| b.is_ascii_alphanumeric() || **b == b'_')
+                .count()
+        };
+        let end = match sql[i] {
+            b'%' if next == b'(' => sql[i..]
+                .iter()
+                .position(|&b| b == b')')
+                .map(|p| (i + p + 2).min(sql.len())),
+            b'%' if next.is_ascii_alphabetic() => Some(i + 2),
+            // Not a Postgres cast (`::int`)
+            b':' if next.is_ascii_alphabetic() && (i == 0 || sql[i - 1] != b':') => {
+                Some(word(i + 1))
+            }
+            b'@' | b'$' if next.is_ascii_alphabetic() => Some(word(i + 1)),
+            b'$' | b'#' if next == b'{' => {
+                sql[i..].iter().position(|&b| b == b'}').map(|p| i + p + 1)
+            }
+            b'{' => sql[i..].iter().position(|&b| b == b'}').map(|p| i + p + 1),
+            _ => None,
+        };
+        match end {
+            Some(end) => {
+                sql[i] = b'?';
+                sql[i + 1..end].fill(b' ');
+                i = end;
+            }
+            None => i += 1,
+        }
+    }
+}
+
+/// Whether an `object_reference` names a table being read or written,
+/// rather than a column's table or alias (`u.id`) or a function.
+fn is_table_position(node: Node) -> bool {
+    node.parent().is_some_and(|parent| {
+        matches!(
+            parent.kind(),
+            "relation" | "from" | "insert" | "update" | "delete"
+        )
+    })
+}
+
+// ─── Script Blocks ──────────────────────────────────────────────
+
+/// `<script>` elements of HTML, Vue and Svelte. The language comes from
+/// `lang` (`<script setup lang="ts">`) or `type`; scripts of other types
+/// (JSON, templates) and external ones (`src=`) are skipped.
+fn script_regions(source: &str) -> Vec<Region> {
+    // ASCII lowercasing keeps byte offsets
+    let lower = source.to_ascii_lowercase();
+    let mut regions = Vec::new();
+    let mut at = 0;
+    while let Some(open) = lower[at..].find("<script").map(|i| at + i) {
+        let Some(tag_end) = lower[open..].find('>').map(|i| open + i) else {
+            break;
+        };
+        let start = tag_end + 1;
+        let end = lower[start..]
+            .find("</script")
+            .map_or(source.len(), |i| start + i);
+        let attrs = &lower[open + "<script".len()..tag_end];
+        if let Some(language) = script_language(attrs) {
+            if !source[start..end].trim().is_empty() {
+                regions.push(Region {
+                    language,
+                    start,
+                    end,
+                });
+            }
+        }
+        at = end;
+    }
+    regions
+}
+
+fn script_language(attrs: &str) -> Option<SupportedLanguage> {
+    if let Some(lang) = attribute(attrs, "lang") {
+        return SupportedLanguage::from_name(lang);
+    }
+    match attribute(attrs, "type") {
+        None | Some("module" | "text/javascript" | "application/javascript") => {
+            Some(SupportedLanguage::JavaScript)
+        }
+        Some("text/babel" | "text/jsx") => Some(SupportedLanguage::Tsx),
+        Some("text/typescript" | "application/typescript") => Some(SupportedLanguage::TypeScript),
+        Some(_) => None,
+    }
+}
+
+/// Value of attribute `name` in an opening tag's attribute text.
+fn attribute<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+    let (at, _) = attrs.match_indices(name).find(|&(i, _)| {
+        attrs[..i].ends_with(char::is_whitespace)
+            && attrs[i + name.len()..].trim_start().starts_with('=')
+    })?;
+    let value = attrs[at + name.len()..]
+        .trim_start()
+        .strip_prefix('=')?
+        .trim_start();
+    match value.chars().next()? {
+        quote @ ('"' | '\'') => value[1..].split(quote).next(),
+        _ => value.split(|c: char| c.is_whitespace() || c == '/').next(),
+    }
+}
+
+// ─── Markdown Fences ────────────────────────────────────────────
+
+/// Fenced code blocks (```` ```rust ```` or `~~~python`) whose info string
+/// names a supported language. An unclosed fence runs to the end.
+fn fence_regions(source: &str) -> Vec<Region> {
+    let mut regions = Vec::new();
+    // (fence character, fence length, language, content start)
+    let mut open: Option<(char, usize, Option<SupportedLanguage>, usize)> = None;
+    let mut offset = 0;
+    for line in source.split_inclusive('\n') {
+        let line_start = offset;
+        offset += line.len();
+        let indent = line.len() - line.trim_start_matches(' ').len();
+        let text = line.trim();
+        if indent > 3 {
+            continue;
+        }
+        match open {
+            None => {
+                let Some(fence) = text.chars().next().filter(|c| matches!(c, '`' | '~')) else {
+                    continue;
+                };
+                let len = text.chars().take_while(|&c| c == fence).count();
+                if len < 3 {
+                    continue;
+                }
+                let info = text[len..].trim_start().trim_start_matches(['{', '.']);
+                let name = info.split([' ', ',', '}', '{']).next().unwrap_or("");
+                open = Some((fence, len, SupportedLanguage::from_name(name), offset));
+            }
+            Some((fence, len, language, start)) => {
+                let closes = text.chars().all(|c| c == fence) && text.len() >= len;
+                if closes {
+                    if let Some(language) = language {
+                        regions.push(Region {
+                            language,
+                            start,
+                            end: line_start,
+                        });
+                    }
+                    open = None;
+                }
+            }
+        }
+    }
+    if let Some((_, _, Some(language), start)) = open {
+        regions.push(Region {
+            language,
+            start,
+            end: source.len(),
+        });
+    }
+    regions.retain(|r| r.start < r.end);
+    regions
+}
+
+// ─── Notebooks ──────────────────────────────────────────────────
+
+/// The code cells of a Jupyter notebook joined into one source, with the
+/// notebook line of each of its lines.
+struct Notebook {
+    language: SupportedLanguage,
+    source: String,
+    /// Notebook line (1-indexed) of each source line.
+    lines: Vec<usize>,
+}
+
+impl Notebook {
+    fn parse(json: &str) -> Option<Self> {
+        let notebook: serde_json::Value = serde_json::from_str(json).ok()?;
+        let metadata = &notebook["metadata"];
+        let language = metadata["kernelspec"]["language"]
+            .as_str()
+            .or_else(|| metadata["language_info"]["name"].as_str())
+            .map_or(
+                Some(SupportedLanguage::Python),
+                SupportedLanguage::from_name,
+            )?;
+        let cells = notebook["cells"].as_array()?;
+
+        // nbformat writes one `"source": [` per cell and one source line
+        // per JSON line after it; without that layout, fall back to the
+        // line of the cell's key
+        let keys: Vec<(usize, bool)> = json
+            .lines()
+            .enumerate()
+            .filter_map(|(i, line)| {
+                let line = line.trim();
+                line.starts_with("\"source\":")
+                    .then(|| (i + 1, line.ends_with('[')))
+            })
+            .collect();
+
+        let mut source = String::new();
+        let mut lines = Vec::new();
+        for (i, cell) in cells.iter().enumerate() {
+            if cell["cell_type"] != "code" {
+                continue;
+            }
+            let text = match &cell["source"] {
+                serde_json::Value::Array(parts) => {
+                    parts.iter().filter_map(|p| p.as_str()).collect()
+                }
+                serde_json::Value::String(text) => text.clone(),
+                _ => continue,
+            };
+            let key = keys.get(i).filter(|_| keys.len() == cells.len());
+            for (k, line) in text.lines().enumerate() {
+                // IPython magics and shell escapes aren't the kernel's language
+                let code = if line.starts_with(['%', '!']) {
+                    ""
+                } else {
+                    line
+                };
+                source.push_str(code);
+                source.push('\n');
+                lines.push(match key {
+                    Some(&(key_line, true)) => key_line + 1 + k,
+                    Some(&(key_line, false)) => key_line,
+                    None => lines.len() + 1,
+                });
+            }
+        }
+        Some(Self {
+            language,
+            source,
+            lines,
+        })
+    }
+
+    /// Move lines from the joined source to the notebook. Byte ranges
+    /// don't map to the JSON file and are cleared.
+    fn map_lines(&self, extractions: &mut FileExtractions) {
+        let line = |l: usize| self.lines.get(l.wrapping_sub(1)).copied().unwrap_or(l);
+        for symbol in &mut extractions.symbols {
+            symbol.line_start = line(symbol.line_start);
+            symbol.line_end = line(symbol.line_end);
+            symbol.range = SourceRange::default();
+            symbol.name_range = SourceRange::default();
+        }
+        for import in &mut extractions.imports {
+            import.line = line(import.line);
+        }
+        for call in &mut extractions.calls {
+            call.line = line(call.line);
+            call.range = SourceRange::default();
+        }
+        for relation in &mut extractions.relations {
+            relation.line = line(relation.line);
+            relation.range = SourceRange::default();
+        }
+        for error in &mut extractions.syntax_errors {
+            *error = line(*error);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::build_graph;
+    use crate::graph::types::{EdgeKind, RelationTarget};
+    use crate::parser::extract_file;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_script_and_fence_regions() {
+        let vue = "<template>\n  <div>{{ total }}</div>\n</template>\n\
+                   <script setup lang=\"ts\">\nfunction total(): number { return 1 }\n</script>\n\
+                   <script type=\"application/json\">{\"a\": 1}</script>\n";
+        let found = regions(HostFormat::Vue, vue);
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].language, SupportedLanguage::TypeScript);
+        assert!(vue[found[0].start..found[0].end].contains("function total"));
+
+        let md = "# Usage\n\n```rust\nfn demo() {}\n```\n\n```text\nfn not_code() {}\n```\n\n~~~ python\ndef example():\n    pass\n~~~\n";
+        let found: Vec<_> = regions(HostFormat::Markdown, md)
+            .into_iter()
+            .map(|r| (r.language, &md[r.start..r.end]))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                (SupportedLanguage::Rust, "fn demo() {}\n"),
+                (SupportedLanguage::Python, "def example():\n    pass\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_embedded_symbols_keep_host_lines() {
+        let html = "<html>\n<body>\n<script>\nfunction boot() {\n  render();\n}\n</script>\n</body>\n</html>\n";
+        let extraction = extract_file(Path::new("index.html"), html).unwrap();
+        let boot = extraction
+            .symbols
+            .iter()
+            .find(|s| s.name == "boot")
+            .unwrap();
+        assert_eq!((boot.line_start, boot.line_end), (4, 6));
+        assert_eq!(
+            &html[boot.range.start.byte..boot.range.end.byte],
+            "function boot() {\n  render();\n}"
+        );
+        assert_eq!(extraction.calls[0].line, 5);
+
+        let md = "Intro\n\n```python\nimport os\n\ndef main():\n    os.getcwd()\n```\n";
+        let extraction = extract_file(Path::new("README.md"), md).unwrap();
+        let main = extraction
+            .symbols
+            .iter()
+            .find(|s| s.name == "main")
+            .unwrap();
+        assert_eq!(main.line_start, 6);
+        assert_eq!(extraction.imports[0].line, 4);
+    }
+
+    #[test]
+    fn test_sql_strings_use_their_tables() {
+        let tables = |path: &str, source: &str| -> Vec<(String, String, usize)> {
+            let extraction = extract_file(Path::new(path), source).unwrap();
+            let mut tables: Vec<_> = extraction
+                .relations
+                .iter()
+                .filter(|r| r.kind == EdgeKind::UsesType)
+                .map(|r| {
+                    let RelationTarget::Symbol(table) = &r.target else {
+                        panic!("unexpected target {:?}", r.target);
+                    };
+                    assert_eq!(&source[r.range.start.byte..r.range.end.byte], table);
+                    (r.source.clone(), table.clone(), r.line)
+                })
+                .collect();
+            tables.sort();
+            tables
+        };
+
+        let py = "def active_users(db):\n    \
+                  return db.execute(\n        \"SELECT u.* FROM users u JOIN teams t ON t.id = u.team_id WHERE u.name = %(name)s\"\n    )\n\n\
+                  def archive(db):\n    \
+                  db.execute('''\n        WITH old AS (SELECT id FROM orders WHERE created < :cutoff)\n        \
+                  INSERT INTO archived_orders SELECT * FROM old\n    ''')\n    \
+                  print(\"Select a file to archive\")\n";
+        assert_eq!(
+            tables("queries.py", py),
+            vec![
+                ("active_users".to_string(), "teams".to_string(), 3),
+                ("active_users".to_string(), "users".to_string(), 3),
+                ("archive".to_string(), "archived_orders".to_string(), 9),
+                ("archive".to_string(), "orders".to_string(), 8),
+            ]
+        );
+
+        let go = "package store\n\nfunc purge() {\n\tdb.Exec(`DELETE FROM sessions WHERE user_id = $1`, 7)\n}\n";
+        assert_eq!(
+            tables("store.go", go),
+            vec![("purge".to_string(), "sessions".to_string(), 4)]
+        );
+    }
+
+    #[test]
+    fn test_notebook_cells_map_to_notebook_lines() {
+        let notebook = r##"{
+ "cells": [
+  {
+   "cell_type": "markdown",
+   "metadata": {},
+   "source": [
+    "# Analysis"
+   ]
+  },
+  {
+   "cell_type": "code",
+   "execution_count": 1,
+   "metadata": {},
+   "outputs": [],
+   "source": [
+    "%matplotlib inline\n",
+    "import pandas as pd\n",
+    "\n",
+    "def load(path):\n",
+    "    return pd.read_csv(path)"
+   ]
+  }
+ ],
+ "metadata": {
+  "kernelspec": {"language": "python", "name": "python3"}
+ },
+ "nbformat": 4,
+ "nbformat_minor": 5
+}
+"##;
+        let extraction = extract_file(Path::new("analysis.ipynb"), notebook).unwrap();
+        let load = extraction
+            .symbols
+            .iter()
+            .find(|s| s.name == "load")
+            .unwrap();
+        assert_eq!((load.line_start, load.line_end), (19, 20));
+        assert!(load.range.is_empty());
+        assert_eq!(extraction.imports[0].line, 17);
+        assert_eq!(extraction.calls[0].line, 20);
+    }
+
+    #[test]
+    fn test_embedded_code_joins_the_graph() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("format.ts"),
+            "export function formatPrice(n: number) { return `$${n}`; }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Cart.vue"),
+            "<template>\n  <p>{{ label }}</p>\n</template>\n\n<script setup lang=\"ts\">\n\
+             import { formatPrice } from './format';\n\
+             function label() {\n  return formatPrice(3);\n}\n</script>\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("GUIDE.md"),
+            "# Guide\n\n```python\ndef example():\n    pass\n```\n",
+        )
+        .unwrap();
+
+        let graph = build_graph(root).unwrap();
+        let label = &graph.search("label", 1)[0];
+        assert_eq!(label.file, root.join("Cart.vue"));
+        assert_eq!(label.line_start, 7);
+        assert!(graph
+            .dependencies("label")
+            .iter()
+            .any(|d| d.symbol == "formatPrice"
+                && d.relationship == EdgeKind::Calls
+                && d.file == root.join("format.ts")));
+
+        let example = &graph.search("example", 1)[0];
+        assert_eq!(
+            (example.file.clone(), example.line_start),
+            (root.join("GUIDE.md"), 4)
+        );
+
+        // Regions in languages that aren't enabled are skipped
+        fs::create_dir(root.join(".anchor")).unwrap();
+        fs::write(
+            root.join(".anchor/config.toml"),
+            "[project]\nlanguages = [\"typescript\", \"vue\", \"markdown\"]\n",
+        )
+        .unwrap();
+        let graph = build_graph(root).unwrap();
+        assert!(graph.search("example", 1).is_empty());
+        assert!(!graph.search("label", 1).is_empty());
+
+        // And so are host formats that aren't
+        fs::write(
+            root.join(".anchor/config.toml"),
+            "[project]\nlanguages = [\"typescript\"]\n",
+        )
+        .unwrap();
+        let graph = build_graph(root).unwrap();
+        assert!(graph.search("label", 1).is_empty());
+    }
+}
@@ -1,659 +1,853 @@
 //! Symbol extraction from source code using tree-sitter ASTs.
 //!
-//! Walks the AST of a source file and extracts:
+//! Runs a language's tag query (see `tags`) over the AST of a source file
+//! and extracts:
 //! - Symbol definitions (functions, structs, classes, etc.)
 //! - Import statements
 //! - Function calls (for building call graphs)
 
+use std::cell::RefCell;
+use std::cmp::Reverse;
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
 use std::path::Path;
-use tree_sitter::{Node, Parser};
+use tree_sitter::{Node, Parser, Point, QueryCursor, Range, StreamingIterator, Tree};
 
+use super::embedded::HostFormat;
 use super::language::SupportedLanguage;
+use super::tags::{CaptureRole, TagQuery};
 use crate::error::AnchorError;
 use crate::graph::types::*;
 
+/// Tunable limits for extraction (see `AnchorConfig::extract_options`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExtractOptions {
+    /// Maximum lines kept in a code snippet.
+    pub max_snippet_lines: usize,
+}
+
+impl Default for ExtractOptions {
+    fn default() -> Self {
+        Self {
+            max_snippet_lines: 50,
+        }
+    }
+}
+
 /// Extract all symbols, imports, and calls from a source file.
 ///
 /// Returns an error if the file's language is unsupported, the parser
 /// fails to initialize, or tree-sitter returns no parse tree.
 pub fn extract_file(path: &Path, source: &str) -> crate::error::Result<FileExtractions> {
+    if let Some(format) = HostFormat::from_path(path) {
+        return super::embedded::extract_embedded(path, source, format, |lang| {
+            Some((TagQuery::bundled(lang), ExtractOptions::default()))
+        });
+    }
     let lang = SupportedLanguage::from_path(path)
-        .ok_or_else(|| AnchorError::UnsupportedLanguage(path.to_path_buf()))?;
-
-    let mut parser = Parser::new();
-    parser
-        .set_language(&lang.tree_sitter_language())
-        .map_err(|e| AnchorError::ParserInitError(path.to_path_buf(), e.to_string()))?;
-
-    let tree = parser
-        .parse(source, None)
-        .ok_or_else(|| AnchorError::TreeSitterParseFailed(path.to_path_buf()))?;
-    let root = tree.root_node();
-
-    let mut symbols = Vec::new();
-    let mut imports = Vec::new();
-    let mut calls = Vec::new();
-
-    extract_node(
-        &root,
-        source.as_bytes(),
-        lang,
-        None,
-        &mut symbols,
-        &mut imports,
-        &mut calls,
-    );
-
-    Ok(FileExtractions {
-        file_path: path.to_path_buf(),
-        symbols,
-        imports,
-        calls,
-    })
+        .ok_or_else(|| AnchorError::UnsupportedLanguage(path.to_path_buf()))?
+        .refine_for_source(path, source);
+    extract_file_with(
+        path,
+        source,
+        TagQuery::bundled(lang),
+        &ExtractOptions::default(),
+    )
 }
 
-/// Recursively extract information from a tree-sitter node.
-fn extract_node(
-    node: &Node,
-    source: &[u8],
-    lang: SupportedLanguage,
-    current_scope: Option<&str>,
-    symbols: &mut Vec<ExtractedSymbol>,
-    imports: &mut Vec<ExtractedImport>,
-    calls: &mut Vec<ExtractedCall>,
-) {
-    let kind = node.kind();
-
-    match lang {
-        SupportedLanguage::Rust => {
-            extract_rust_node(node, source, kind, current_scope, symbols, imports, calls);
-        }
-        SupportedLanguage::Python => {
-            extract_python_node(node, source, kind, current_scope, symbols, imports, calls);
-        }
-        SupportedLanguage::JavaScript | SupportedLanguage::Tsx => {
-            extract_js_node(node, source, kind, current_scope, symbols, imports, calls);
-        }
-        SupportedLanguage::TypeScript => {
-            extract_ts_node(node, source, kind, current_scope, symbols, imports, calls);
-        }
-        SupportedLanguage::Go => {
-            extract_generic_node(
-                node,
-                source,
-                kind,
-                current_scope,
-                symbols,
-                imports,
-                calls,
-                &["function_declaration", "method_declaration"],
-                &["import_declaration"],
-                &["call_expression"],
-            );
-        }
-        SupportedLanguage::Java => {
-            extract_generic_node(
-                node,
-                source,
-                kind,
-                current_scope,
-                symbols,
-                imports,
-                calls,
-                &[
-                    "method_declaration",
-                    "class_declaration",
-                    "interface_declaration",
-                ],
-                &["import_declaration"],
-                &["method_invocation"],
-            );
-        }
-        SupportedLanguage::CSharp => {
-            extract_generic_node(
-                node,
-                source,
-                kind,
-                current_scope,
-                symbols,
-                imports,
-                calls,
-                &[
-                    "method_declaration",
-                    "class_declaration",
-                    "interface_declaration",
-                ],
-                &["using_directive"],
-                &["invocation_expression"],
-            );
-        }
-        SupportedLanguage::Ruby => {
-            extract_generic_node(
-                node,
-                source,
-                kind,
-                current_scope,
-                symbols,
-                imports,
-                calls,
-                &["method", "class", "module"],
-                &["call"],
-                &["call", "method_call"],
-            );
-        }
-        SupportedLanguage::Cpp | SupportedLanguage::Swift => {
-            extract_generic_node(
-                node,
-                source,
-                kind,
-                current_scope,
-                symbols,
-                imports,
-                calls,
-                &["function_definition", "class_specifier"],
-                &["preproc_include"],
-                &["call_expression"],
-            );
-        }
-    }
+/// Extract a file with an explicit tag query and options, for callers
+/// that resolve the language and query themselves (e.g. from config and
+/// `.anchor/queries/` overrides).
+pub fn extract_file_with(
+    path: &Path,
+    source: &str,
+    query: &TagQuery,
+    options: &ExtractOptions,
+) -> crate::error::Result<FileExtractions> {
+    extract_ranges(path, source, query, options, &[])
+}
 
-    // Determine if this node creates a new scope for children
-    let new_scope = match lang {
-        SupportedLanguage::Rust => match kind {
-            "impl_item" => get_rust_impl_name(node, source),
-            "function_item" => node_name(node, source),
-            "struct_item" | "enum_item" | "trait_item" => node_name(node, source),
-            _ => None,
-        },
-        SupportedLanguage::Python => match kind {
-            "class_definition" | "function_definition" => node_name(node, source),
-            _ => None,
-        },
-        SupportedLanguage::JavaScript | SupportedLanguage::Tsx | SupportedLanguage::TypeScript => {
-            match kind {
-                "class_declaration" | "function_declaration" => node_name(node, source),
-                _ => None,
+/// Extract only the parts of `source` within `ranges` (all of it when
+/// empty), e.g. the `<script>` of a Vue component. Lines and offsets stay
+/// those of the whole file.
+pub(crate) fn extract_ranges(
+    path: &Path,
+    source: &str,
+    query: &TagQuery,
+    options: &ExtractOptions,
+    ranges: &[Range],
+) -> crate::error::Result<FileExtractions> {
+    let tree = parse(path, source, query.language(), ranges, None)?;
+    Ok(extract_tree(path, source, query, options, &tree, None).0)
+}
+
+thread_local! {
+    /// One parser per language per thread, reused across files.
+    static PARSERS: RefCell<HashMap<SupportedLanguage, Parser>> = RefCell::new(HashMap::new());
+}
+
+/// Parse `source` (only `ranges` of it, when given) with this thread's
+/// parser for `language`. An `old` tree, already edited to match
+/// `source`, is reused for an incremental parse.
+pub(crate) fn parse(
+    path: &Path,
+    source: &str,
+    language: SupportedLanguage,
+    ranges: &[Range],
+    old: Option<&Tree>,
+) -> crate::error::Result<Tree> {
+    PARSERS.with_borrow_mut(|parsers| {
+        let parser = match parsers.entry(language) {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => {
+                let mut parser = Parser::new();
+                parser
+                    .set_language(&language.tree_sitter_language())
+                    .map_err(|e| AnchorError::ParserInitError(path.to_path_buf(), e.to_string()))?;
+                entry.insert(parser)
             }
-        }
-        SupportedLanguage::Go => match kind {
-            "function_declaration" | "method_declaration" => node_name(node, source),
-            _ => None,
-        },
-        SupportedLanguage::Java | SupportedLanguage::CSharp => match kind {
-            "method_declaration" | "class_declaration" => node_name(node, source),
-            _ => None,
-        },
-        SupportedLanguage::Ruby => match kind {
-            "method" | "class" | "module" => node_name(node, source),
-            _ => None,
-        },
-        SupportedLanguage::Cpp | SupportedLanguage::Swift => match kind {
-            "function_definition" | "class_specifier" => node_name(node, source),
-            _ => None,
-        },
-    };
+        };
+        parser.set_included_ranges(ranges).map_err(|e| {
+            AnchorError::ParserInitError(path.to_path_buf(), format!("bad range #{}", e.0))
+        })?;
+        parser
+            .parse(source, old)
+            .ok_or_else(|| AnchorError::TreeSitterParseFailed(path.to_path_buf()))
+    })
+}
 
-    let scope = new_scope.as_deref().or(current_scope);
+/// Extract from a parsed tree. With `within`, only matches touching that
+/// byte range are used (see `incremental`). Also returns whether the file
+/// declares a `@package`, which qualifies every name in it.
+pub(crate) fn extract_tree(
+    path: &Path,
+    source: &str,
+    query: &TagQuery,
+    options: &ExtractOptions,
+    tree: &Tree,
+    within: Option<std::ops::Range<usize>>,
+) -> (FileExtractions, bool) {
+    let tags = collect_tags(query, tree.root_node(), source.as_bytes(), within.clone());
+    let tables = match query.language() {
+        SupportedLanguage::Sql => Vec::new(),
+        _ => super::embedded::sql_tables(path, tree.root_node(), source, within),
+    };
+    let mut extractions = tags.resolve(path, query.language(), source.as_bytes(), tables);
+    extractions.syntax_errors = syntax_errors(tree.root_node());
+    super::resolve::resolve_imports(path, query.language(), &mut extractions.imports);
+    reexport_modules(&mut extractions);
 
-    // Recurse into children
-    let child_count = node.child_count();
-    for i in 0..child_count {
-        if let Some(child) = node.child(i) {
-            extract_node(&child, source, lang, scope, symbols, imports, calls);
-        }
+    for symbol in &mut extractions.symbols {
+        truncate_lines(&mut symbol.code_snippet, options.max_snippet_lines);
     }
+
+    (extractions, !tags.packages.is_empty())
 }
 
-// ─── Rust Extraction ────────────────────────────────────────────
+/// Row and byte column of a byte offset, as tree-sitter counts them.
+pub(crate) fn point_at(source: &str, byte: usize) -> Point {
+    let before = &source[..byte];
+    let row = before.matches('\n').count();
+    let column = byte - before.rfind('\n').map_or(0, |i| i + 1);
+    Point::new(row, column)
+}
 
-fn extract_rust_node(
-    node: &Node,
-    source: &[u8],
-    kind: &str,
-    current_scope: Option<&str>,
-    symbols: &mut Vec<ExtractedSymbol>,
-    imports: &mut Vec<ExtractedImport>,
-    calls: &mut Vec<ExtractedCall>,
-) {
-    match kind {
-        "function_item" => {
-            if let Some(name) = node_name(node, source) {
-                let parent_scope = current_scope.map(|s| s.to_string());
-                let sym_kind = if parent_scope.is_some() {
-                    NodeKind::Method
-                } else {
-                    NodeKind::Function
-                };
+/// Lines of the outermost ERROR nodes and of MISSING nodes: where
+/// tree-sitter had to recover from a syntax error.
+fn syntax_errors(root: Node) -> Vec<usize> {
+    let mut lines = Vec::new();
+    let mut stack = vec![root];
+    while let Some(node) = stack.pop() {
+        if node.is_error() || node.is_missing() {
+            lines.push(node.start_position().row + 1);
+        } else if node.has_error() {
+            let mut cursor = node.walk();
+            stack.extend(node.children(&mut cursor));
+        }
+    }
+    lines.sort_unstable();
+    lines
+}
 
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: sym_kind,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: parent_scope,
-                });
-            }
+/// An export of an import's path (`export * from './util'`) re-exports
+/// the whole module: point it at the file the import resolves to, or
+/// drop it when the module is outside the project.
+fn reexport_modules(extractions: &mut FileExtractions) {
+    let imports = &extractions.imports;
+    extractions.relations.retain_mut(|relation| {
+        let RelationTarget::Symbol(name) = &relation.target else {
+            return true;
+        };
+        if relation.kind != EdgeKind::Exports {
+            return true;
         }
-        "struct_item" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Struct,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: None,
-                });
+        let Some(import) = imports
+            .iter()
+            .find(|i| i.line == relation.line && i.path == *name)
+        else {
+            return true;
+        };
+        match import.target() {
+            Some(target) => {
+                relation.target = target;
+                true
             }
+            None => false,
         }
-        "enum_item" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Enum,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: None,
-                });
+    });
+}
+
+// ─── Tag Collection ─────────────────────────────────────────────
+
+/// A definition match: (pattern, kind, node, `@name` node, explicit
+/// `@parent` as written).
+type DefinitionTag<'tree> = (usize, NodeKind, Node<'tree>, Node<'tree>, Option<String>);
+
+/// Raw query matches, keyed by tree-sitter node id. When several
+/// patterns tag the same node the first pattern in the query wins.
+#[derive(Default)]
+struct Tags<'tree> {
+    /// (definition node, name) -> definition tag
+    definitions: HashMap<(usize, String), DefinitionTag<'tree>>,
+    /// scope node -> (name, kind of the definition it belongs to)
+    scopes: HashMap<usize, (String, Option<NodeKind>)>,
+    /// (import node, path or None) -> symbols
+    imports: Vec<(Node<'tree>, Option<String>, Vec<String>)>,
+    /// call node -> (pattern, node, callee, style)
+    calls: HashMap<usize, (usize, Node<'tree>, String, CallStyle)>,
+    /// (kind, source name if the match has one, target node)
+    relations: Vec<(EdgeKind, Option<String>, Node<'tree>)>,
+    /// usage node -> (pattern, node, kind)
+    references: HashMap<usize, (usize, Node<'tree>, ReferenceKind)>,
+    /// End bytes of callee expressions: a usage ending there is the
+    /// called name, already covered by the call
+    callee_ends: HashSet<usize>,
+    /// `@package` definitions: (node, name)
+    packages: Vec<(Node<'tree>, String)>,
+    /// definition node -> its `@decorator` nodes
+    decorators: HashMap<usize, Vec<Node<'tree>>>,
+}
+
+/// Run a tag query over a tree, or the part of it touching `within`, and
+/// group the captures of each match.
+fn collect_tags<'tree>(
+    query: &TagQuery,
+    root: Node<'tree>,
+    source: &[u8],
+    within: Option<std::ops::Range<usize>>,
+) -> Tags<'tree> {
+    let mut tags = Tags::default();
+    let mut cursor = QueryCursor::new();
+    if let Some(range) = within {
+        cursor.set_byte_range(range);
+    }
+    let mut matches = cursor.matches(query.query(), root, source);
+
+    while let Some(m) = matches.next() {
+        let capture = |role: CaptureRole| {
+            m.captures
+                .iter()
+                .find(|c| query.role(c.index) == role)
+                .map(|c| c.node)
+        };
+        let text = |node: Node| node.utf8_text(source).unwrap_or("").trim().to_string();
+        // A Ruby symbol (`attr_reader :total`) names what it declares
+        let mut name =
+            capture(CaptureRole::Name).map(|n| text(n).trim_start_matches(':').to_string());
+        let mut parent = capture(CaptureRole::Parent).map(text);
+
+        let definition = m.captures.iter().find_map(|c| match query.role(c.index) {
+            CaptureRole::Definition(kind) => Some((c.node, kind)),
+            _ => None,
+        });
+        // A dotted name token (Elixir `defmodule MyApp.Billing`) is its
+        // last segment inside the rest, or inside itself when it's also
+        // the parent (`defimpl ..., for: MyApp.Billing`)
+        let dotted = capture(CaptureRole::Name).filter(|n| n.named_child_count() == 0);
+        if let (Some(full), None) = (dotted.and(name.clone()), capture(CaptureRole::Package)) {
+            if let Some((prefix, last)) = full.rsplit_once('.') {
+                if parent.is_none() {
+                    parent = Some(prefix.to_string());
+                }
+                if parent
+                    .as_deref()
+                    .is_some_and(|parent| parent == prefix || parent == full)
+                {
+                    name = Some(last.to_string());
+                }
             }
         }
-        "trait_item" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Trait,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: None,
-                });
+        if let (Some((node, kind)), Some(name), Some(name_node)) =
+            (definition, &name, capture(CaptureRole::Name))
+        {
+            let tag = (m.pattern_index, kind, node, name_node, parent);
+            let entry = tags
+                .definitions
+                .entry((node.id(), name.clone()))
+                .or_insert(tag.clone());
+            if m.pattern_index < entry.0 {
+                *entry = tag;
             }
         }
-        "impl_item" => {
-            if let Some(name) = get_rust_impl_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Impl,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: None,
-                });
-            }
+
+        if let (Some(node), Some(name)) = (capture(CaptureRole::Package), &name) {
+            // PHP namespaces (`App\Billing`) qualify like the others
+            tags.packages.push((node, name.replace('\\', ".")));
         }
-        "const_item" | "static_item" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Constant,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: current_scope.map(|s| s.to_string()),
-                });
+
+        for c in m.captures {
+            match query.role(c.index) {
+                CaptureRole::Relation(kind) => {
+                    // An export starts from the file, not a named symbol
+                    let from = name.clone().filter(|_| kind != EdgeKind::Exports);
+                    tags.relations.push((kind, from, c.node));
+                }
+                CaptureRole::Reference(kind) => {
+                    let usage = (m.pattern_index, c.node, kind);
+                    let entry = tags.references.entry(c.node.id()).or_insert(usage);
+                    if m.pattern_index < entry.0 {
+                        *entry = usage;
+                    }
+                }
+                _ => {}
             }
         }
-        "type_item" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Type,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: None,
-                });
+
+        if let Some((node, _)) = definition {
+            for c in m.captures {
+                if query.role(c.index) == CaptureRole::Decorator {
+                    let decorators = tags.decorators.entry(node.id()).or_default();
+                    if !decorators.iter().any(|d| d.id() == c.node.id()) {
+                        decorators.push(c.node);
+                    }
+                }
             }
         }
-        "mod_item" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Module,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: None,
-                });
+
+        if let Some(node) = capture(CaptureRole::Scope) {
+            let scope_name = capture(CaptureRole::ScopeName).map(text).or(name.clone());
+            if let Some(scope_name) = scope_name {
+                tags.scopes
+                    .entry(node.id())
+                    .or_insert((scope_name, definition.map(|(_, kind)| kind)));
             }
         }
-        "use_declaration" => {
-            let text = node_text(node, source);
-            // Parse "use foo::bar::Baz;" into path
-            let path = text
-                .trim_start_matches("use ")
-                .trim_end_matches(';')
-                .trim()
-                .to_string();
-
-            imports.push(ExtractedImport {
-                path,
-                symbols: Vec::new(),
-                line: node.start_position().row + 1,
+
+        if let Some(node) = capture(CaptureRole::Import) {
+            // A path captured piecewise (Scala `import a.b.c`) spans from
+            // its first piece to its last
+            let pieces = m
+                .captures
+                .iter()
+                .filter(|c| query.role(c.index) == CaptureRole::ImportPath);
+            let span = pieces.fold(None, |span: Option<(usize, usize)>, c| {
+                let (start, end) = span.unwrap_or((c.node.start_byte(), c.node.end_byte()));
+                Some((start.min(c.node.start_byte()), end.max(c.node.end_byte())))
             });
+            let path = span
+                .and_then(|(start, end)| std::str::from_utf8(source.get(start..end)?).ok())
+                .map(|text| import_path(text.trim()));
+            let symbols = m
+                .captures
+                .iter()
+                .filter(|c| query.role(c.index) == CaptureRole::ImportSymbol)
+                .map(|c| text(c.node))
+                .collect();
+            tags.imports.push((node, path, symbols));
         }
-        "call_expression" => {
-            if let Some(callee_name) = get_call_name(node, source) {
-                if let Some(caller) = current_scope {
-                    calls.push(ExtractedCall {
-                        callee: callee_name,
-                        caller: caller.to_string(),
-                        line: node.start_position().row + 1,
-                    });
-                }
+
+        // A nameless `@call` keeps later patterns from calling it a call
+        if let Some(node) = capture(CaptureRole::Call) {
+            let callee_node = capture(CaptureRole::CallName);
+            if let Some(callee) = callee_node {
+                tags.callee_ends.insert(callee.end_byte());
+            }
+            let style =
+                callee_node.map_or(CallStyle::Direct, |callee| call_style(node, callee, source));
+            let callee = callee_node
+                .map(|callee| match style {
+                    CallStyle::Constructor => type_name(&text(callee)),
+                    _ => call_name(&text(callee)),
+                })
+                .unwrap_or_default();
+            let call = (m.pattern_index, node, callee, style);
+            let entry = tags.calls.entry(node.id()).or_insert(call.clone());
+            if m.pattern_index < entry.0 {
+                *entry = call;
             }
         }
-        _ => {}
     }
+
+    tags
 }
 
-// ─── Python Extraction ──────────────────────────────────────────
+impl<'tree> Tags<'tree> {
+    /// Turn the grouped matches into extracted symbols, imports, calls and
+    /// relations, attributing each to its enclosing scope. Output is in
+    /// source order.
+    fn resolve(
+        &self,
+        path: &Path,
+        language: SupportedLanguage,
+        source: &[u8],
+        tables: Vec<(Node, String, SourceRange)>,
+    ) -> FileExtractions {
+        let mut definitions: Vec<_> = self.definitions.iter().collect();
+        definitions.sort_by_key(|((_, name), (pattern, _, node, _, _))| {
+            (node.start_byte(), Reverse(node.end_byte()), *pattern, name)
+        });
+        let symbols = definitions
+            .into_iter()
+            .map(|((_, name), (_, kind, node, name_node, explicit_parent))| {
+                let (kind, node) = (*kind, *node);
+                let scope = self.enclosing_scope(node);
+                let kind = match (kind, scope.and_then(|(_, kind)| *kind)) {
+                    (NodeKind::Function, Some(scope_kind)) if is_type_like(scope_kind) => {
+                        NodeKind::Method
+                    }
+                    // An out-of-line member definition (`void Foo::bar()`)
+                    (NodeKind::Function, _) if explicit_parent.is_some() => NodeKind::Method,
+                    _ => kind,
+                };
+                let parent = explicit_parent
+                    .as_deref()
+                    .map(type_name)
+                    .or_else(|| scope.map(|(name, _)| name.clone()));
+                ExtractedSymbol {
+                    name: name.clone(),
+                    kind,
+                    line_start: node.start_position().row + 1,
+                    line_end: end_line(node),
+                    code_snippet: bounded_snippet(&node, source),
+                    range: source_range(node, source),
+                    name_range: source_range(*name_node, source),
+                    parent,
+                    qualified_name: Some(self.qualified_name(node, name, kind, explicit_parent)),
+                    metadata: super::metadata::extract(
+                        node,
+                        name,
+                        kind,
+                        language,
+                        source,
+                        self.decorators(node, source),
+                    ),
+                }
+            })
+            .collect();
 
-fn extract_python_node(
-    node: &Node,
-    source: &[u8],
-    kind: &str,
-    current_scope: Option<&str>,
-    symbols: &mut Vec<ExtractedSymbol>,
-    imports: &mut Vec<ExtractedImport>,
-    calls: &mut Vec<ExtractedCall>,
-) {
-    match kind {
-        "function_definition" => {
-            if let Some(name) = node_name(node, source) {
-                let parent_scope = current_scope.map(|s| s.to_string());
-                let sym_kind = if parent_scope.is_some() {
-                    NodeKind::Method
-                } else {
-                    NodeKind::Function
+        let mut calls: Vec<_> = self.calls.values().collect();
+        calls.sort_by_key(|(pattern, node, _, _)| {
+            (node.start_byte(), Reverse(node.end_byte()), *pattern)
+        });
+        let calls = calls
+            .into_iter()
+            .filter(|(_, _, callee, _)| !callee.is_empty())
+            .filter_map(|(_, node, callee, style)| {
+                let (caller, _) = self.enclosing_scope(*node)?;
+                Some(ExtractedCall {
+                    callee: callee.clone(),
+                    caller: caller.clone(),
+                    line: node.start_position().row + 1,
+                    range: source_range(*node, source),
+                    style: *style,
+                })
+            })
+            .collect();
+
+        let mut relations: Vec<_> = self
+            .relations
+            .iter()
+            .filter_map(|(kind, name, node)| {
+                let text = node.utf8_text(source).ok()?;
+                // Exports name a symbol or a module path, never a type expression
+                let target = match kind {
+                    EdgeKind::Exports => import_path(text.trim()),
+                    _ => type_name(text),
+                };
+                // Without a name the relation starts from the enclosing
+                // scope (a component using `<Button />`), or the file
+                let from = match (kind, name) {
+                    (EdgeKind::Exports, _) => String::new(),
+                    (_, Some(name)) => name.clone(),
+                    (_, None) => self
+                        .enclosing_scope(*node)
+                        .map(|(scope, _)| scope.clone())
+                        .unwrap_or_default(),
                 };
+                (!target.is_empty()).then(|| ExtractedRelation {
+                    kind: *kind,
+                    source: from,
+                    target: RelationTarget::Symbol(target),
+                    line: node.start_position().row + 1,
+                    range: SourceRange::default(),
+                    reference: None,
+                })
+            })
+            .collect();
+        relations.extend(self.usages(source));
+        relations.extend(self.sql_usages(tables));
+        relations.sort_by(|a, b| {
+            (a.line, a.range.start.byte, &a.source, &a.target).cmp(&(
+                b.line,
+                b.range.start.byte,
+                &b.source,
+                &b.target,
+            ))
+        });
+        relations.dedup();
 
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: sym_kind,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: parent_scope,
-                });
-            }
+        FileExtractions {
+            file_path: path.to_path_buf(),
+            symbols,
+            imports: self.merge_imports(source),
+            calls,
+            relations,
+            syntax_errors: Vec::new(),
         }
-        "class_definition" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Class,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: None,
-                });
-            }
-        }
-        "import_statement" => {
-            let text = node_text(node, source);
-            let path = text.trim_start_matches("import ").trim().to_string();
-            imports.push(ExtractedImport {
-                path,
-                symbols: Vec::new(),
-                line: node.start_position().row + 1,
-            });
-        }
-        "import_from_statement" => {
-            let text = node_text(node, source);
-            // "from foo import bar, baz"
-            let path = text
-                .split("import")
-                .next()
-                .unwrap_or("")
-                .trim_start_matches("from ")
-                .trim()
-                .to_string();
-            let syms: Vec<String> = text
-                .split("import")
-                .nth(1)
-                .unwrap_or("")
-                .split(',')
-                .map(|s| s.trim().to_string())
-                .filter(|s| !s.is_empty())
-                .collect();
+    }
 
-            imports.push(ExtractedImport {
-                path,
-                symbols: syms,
-                line: node.start_position().row + 1,
-            });
-        }
-        "call" => {
-            if let Some(callee_name) = get_python_call_name(node, source) {
-                if let Some(caller) = current_scope {
-                    calls.push(ExtractedCall {
-                        callee: callee_name,
-                        caller: caller.to_string(),
-                        line: node.start_position().row + 1,
-                    });
-                }
-            }
-        }
-        _ => {}
+    /// `@reference` captures as relations from their enclosing scope (or
+    /// the file), leaving out definition names, callees and imports.
+    fn usages(&self, source: &[u8]) -> Vec<ExtractedRelation> {
+        let names: HashSet<usize> = self
+            .definitions
+            .values()
+            .map(|(_, _, _, name, _)| name.id())
+            .collect();
+        let imports: Vec<(usize, usize)> = self
+            .imports
+            .iter()
+            .map(|(node, _, _)| (node.start_byte(), node.end_byte()))
+            .collect();
+        self.references
+            .values()
+            .filter(|(_, node, _)| {
+                !names.contains(&node.id()) && !self.callee_ends.contains(&node.end_byte())
+            })
+            .filter(|(_, node, _)| {
+                !imports
+                    .iter()
+                    .any(|&(start, end)| start <= node.start_byte() && node.end_byte() <= end)
+            })
+            .filter_map(|(_, node, kind)| {
+                let text = node.utf8_text(source).ok()?.trim();
+                let target = match kind {
+                    ReferenceKind::Read | ReferenceKind::Write => call_name(text),
+                    _ => type_name(text),
+                };
+                let from = self
+                    .enclosing_scope(*node)
+                    .map(|(scope, _)| scope.clone())
+                    .unwrap_or_default();
+                (!target.is_empty()).then(|| ExtractedRelation {
+                    kind: kind.edge_kind(),
+                    source: from,
+                    target: RelationTarget::Symbol(target),
+                    line: node.start_position().row + 1,
+                    range: source_range(*node, source),
+                    reference: Some(*kind),
+                })
+            })
+            .collect()
     }
-}
 
-// ─── JavaScript Extraction ──────────────────────────────────────
+    /// Tables named by SQL in string literals (see `embedded`), as type
+    /// usages from the string's enclosing scope (or the file).
+    fn sql_usages(&self, tables: Vec<(Node, String, SourceRange)>) -> Vec<ExtractedRelation> {
+        tables
+            .into_iter()
+            .map(|(string, table, range)| ExtractedRelation {
+                kind: EdgeKind::UsesType,
+                source: self
+                    .enclosing_scope(string)
+                    .map(|(scope, _)| scope.clone())
+                    .unwrap_or_default(),
+                target: RelationTarget::Symbol(table),
+                line: range.start.line,
+                range,
+                reference: Some(ReferenceKind::Type),
+            })
+            .collect()
+    }
 
-fn extract_js_node(
-    node: &Node,
-    source: &[u8],
-    kind: &str,
-    current_scope: Option<&str>,
-    symbols: &mut Vec<ExtractedSymbol>,
-    imports: &mut Vec<ExtractedImport>,
-    calls: &mut Vec<ExtractedCall>,
-) {
-    match kind {
-        "function_declaration" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Function,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: current_scope.map(|s| s.to_string()),
-                });
-            }
-        }
-        "class_declaration" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Class,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: None,
-                });
+    /// `package.Outer.Inner#member`: the preceding `@package`, then the
+    /// enclosing scopes and any explicit parent, with `#` before members
+    /// of a type.
+    fn qualified_name(
+        &self,
+        node: Node,
+        name: &str,
+        kind: NodeKind,
+        explicit_parent: &Option<String>,
+    ) -> String {
+        let mut path: Vec<String> = Vec::new();
+        let mut in_type = false;
+        let mut current = node.parent();
+        while let Some(n) = current {
+            if let Some((scope, scope_kind)) = self.scopes.get(&n.id()) {
+                if path.is_empty() {
+                    in_type = scope_kind.is_some_and(is_type_like);
+                }
+                path.push(scope.clone());
+                // `class Billing::Invoice` scopes its members under `Billing`
+                let scope_parent = self
+                    .definitions
+                    .get(&(n.id(), scope.clone()))
+                    .and_then(|(_, _, _, _, parent)| parent_path(parent.as_deref()?, scope));
+                if let Some(parent) = scope_parent {
+                    path.push(parent);
+                }
             }
+            current = n.parent();
         }
-        "method_definition" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Method,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: current_scope.map(|s| s.to_string()),
-                });
+        path.reverse();
+        if let Some(parent) = explicit_parent.as_deref() {
+            // An extension named after the type it extends qualifies as the type
+            let extends = type_name(parent) == name;
+            if let Some(parent) = parent_path(parent, name) {
+                path.push(parent);
+                in_type = !extends;
             }
         }
-        "lexical_declaration" | "variable_declaration" => {
-            // Handle: const foo = () => {} or const FOO = "bar"
-            extract_js_variable_declaration(node, source, current_scope, symbols);
+        let package = self
+            .packages
+            .iter()
+            .filter(|(package, _)| package.end_byte() <= node.start_byte())
+            .max_by_key(|(package, _)| package.end_byte());
+        if let Some((_, package)) = package {
+            path.insert(0, package.clone());
         }
-        "import_statement" => {
-            extract_js_import(node, source, imports);
-        }
-        "export_statement" => {
-            // Exports may contain declarations — let children handle extraction
-        }
-        "call_expression" => {
-            if let Some(callee_name) = get_call_name(node, source) {
-                if let Some(caller) = current_scope {
-                    calls.push(ExtractedCall {
-                        callee: callee_name,
-                        caller: caller.to_string(),
-                        line: node.start_position().row + 1,
-                    });
-                }
-            }
+
+        let mut qualified = path.join(".");
+        if !qualified.is_empty() {
+            let member = in_type && !is_type_like(kind) && kind != NodeKind::Module;
+            qualified.push(if member { '#' } else { '.' });
         }
-        _ => {}
+        qualified.push_str(name);
+        qualified
     }
-}
 
-// ─── TypeScript Extraction ──────────────────────────────────────
+    /// A definition's decorators in source order, without the `@`.
+    fn decorators(&self, node: Node, source: &[u8]) -> Vec<String> {
+        let mut nodes = self.decorators.get(&node.id()).cloned().unwrap_or_default();
+        nodes.sort_by_key(|n| n.start_byte());
+        nodes
+            .into_iter()
+            .map(|n| {
+                n.utf8_text(source)
+                    .unwrap_or("")
+                    .trim()
+                    .trim_start_matches('@')
+                    .trim()
+                    .to_string()
+            })
+            .collect()
+    }
 
-fn extract_ts_node(
-    node: &Node,
-    source: &[u8],
-    kind: &str,
-    current_scope: Option<&str>,
-    symbols: &mut Vec<ExtractedSymbol>,
-    imports: &mut Vec<ExtractedImport>,
-    calls: &mut Vec<ExtractedCall>,
-) {
-    // TypeScript shares most node kinds with JavaScript
-    extract_js_node(node, source, kind, current_scope, symbols, imports, calls);
-
-    // TypeScript-specific nodes
-    match kind {
-        "interface_declaration" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Interface,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: None,
-                });
+    /// Name and kind of the innermost scope strictly enclosing `node`.
+    fn enclosing_scope(&self, node: Node) -> Option<&(String, Option<NodeKind>)> {
+        let mut current = node.parent();
+        while let Some(n) = current {
+            if let Some(scope) = self.scopes.get(&n.id()) {
+                return Some(scope);
             }
+            current = n.parent();
         }
-        "type_alias_declaration" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Type,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: None,
-                });
+        None
+    }
+
+    /// One import per (import node, path), symbols merged across matches.
+    /// Matches without `@import.path` fall back to the node's text, but
+    /// only when no other match for that node found a path.
+    fn merge_imports(&self, source: &[u8]) -> Vec<ExtractedImport> {
+        let with_path: HashSet<usize> = self
+            .imports
+            .iter()
+            .filter(|(_, path, _)| path.is_some())
+            .map(|(node, _, _)| node.id())
+            .collect();
+
+        let mut merged: Vec<(Node, ExtractedImport)> = Vec::new();
+        for (node, path, symbols) in &self.imports {
+            let path = match path {
+                Some(path) => path.clone(),
+                None if with_path.contains(&node.id()) => continue,
+                None => node.utf8_text(source).unwrap_or("").trim().to_string(),
+            };
+            if path.is_empty() {
+                continue;
             }
-        }
-        "enum_declaration" => {
-            if let Some(name) = node_name(node, source) {
-                symbols.push(ExtractedSymbol {
-                    name,
-                    kind: NodeKind::Enum,
-                    line_start: node.start_position().row + 1,
-                    line_end: node.end_position().row + 1,
-                    code_snippet: bounded_snippet(node, source),
-                    parent: None,
-                });
+            let import = match merged
+                .iter_mut()
+                .find(|(n, import)| n.id() == node.id() && import.path == path)
+            {
+                Some((_, import)) => import,
+                None => {
+                    merged.push((
+                        *node,
+                        ExtractedImport {
+                            path,
+                            symbols: Vec::new(),
+                            line: node.start_position().row + 1,
+                            resolved: None,
+                            candidates: Vec::new(),
+                        },
+                    ));
+                    &mut merged.last_mut().expect("just pushed").1
+                }
+            };
+            for symbol in symbols {
+                if !import.symbols.contains(symbol) {
+                    import.symbols.push(symbol.clone());
+                }
             }
         }
-        _ => {}
+
+        merged.sort_by_key(|(node, _)| node.start_byte());
+        merged.into_iter().map(|(_, import)| import).collect()
     }
 }
 
-// ─── Generic Extraction (for new languages) ─────────────────────
+/// Last line (1-indexed) of a node. Nodes that swallow their trailing
+/// newline (preprocessor directives) end at column 0 of the next line.
+fn end_line(node: Node) -> usize {
+    let (start, end) = (node.start_position(), node.end_position());
+    if end.column == 0 && end.row > start.row {
+        end.row
+    } else {
+        end.row + 1
+    }
+}
 
-/// Generic node extraction for languages without dedicated extractors.
-#[allow(clippy::too_many_arguments)]
-fn extract_generic_node(
-    node: &Node,
-    source: &[u8],
-    kind: &str,
-    current_scope: Option<&str>,
-    symbols: &mut Vec<ExtractedSymbol>,
-    imports: &mut Vec<ExtractedImport>,
-    calls: &mut Vec<ExtractedCall>,
-    func_kinds: &[&str],
-    import_kinds: &[&str],
-    call_kinds: &[&str],
-) {
-    // Extract functions/methods
-    if func_kinds.contains(&kind) {
-        if let Some(name) = node_name(node, source) {
-            let sym_kind = if current_scope.is_some() {
-                NodeKind::Method
-            } else {
-                NodeKind::Function
-            };
-            symbols.push(ExtractedSymbol {
-                name,
-                kind: sym_kind,
-                line_start: node.start_position().row + 1,
-                line_end: node.end_position().row + 1,
-                code_snippet: bounded_snippet(node, source),
-                parent: current_scope.map(|s| s.to_string()),
-            });
+/// A node's span, with columns counted in both UTF-8 bytes and UTF-16
+/// code units.
+pub(crate) fn source_range(node: Node, source: &[u8]) -> SourceRange {
+    let point = |byte: usize, position: tree_sitter::Point| {
+        let line_start = byte - position.column;
+        let column_utf16 = std::str::from_utf8(&source[line_start..byte])
+            .map(|prefix| prefix.encode_utf16().count())
+            .unwrap_or(position.column);
+        SourcePoint {
+            byte,
+            line: position.row + 1,
+            column: position.column,
+            column_utf16,
         }
+    };
+    SourceRange {
+        start: point(node.start_byte(), node.start_position()),
+        end: point(node.end_byte(), node.end_position()),
     }
+}
 
-    // Extract imports
-    if import_kinds.contains(&kind) {
-        let text = node_text(node, source);
-        imports.push(ExtractedImport {
-            path: text.trim().to_string(),
-            symbols: Vec::new(),
-            line: node.start_position().row + 1,
-        });
-    }
+/// Kinds whose nested functions are methods.
+fn is_type_like(kind: NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class
+            | NodeKind::Struct
+            | NodeKind::Enum
+            | NodeKind::Interface
+            | NodeKind::Trait
+            | NodeKind::Impl
+    )
+}
 
-    // Extract calls
-    if call_kinds.contains(&kind) {
-        if let Some(callee_name) = get_call_name(node, source) {
-            if let Some(caller) = current_scope {
-                calls.push(ExtractedCall {
-                    callee: callee_name,
-                    caller: caller.to_string(),
-                    line: node.start_position().row + 1,
-                });
-            }
-        }
+/// The qualified path an explicit parent puts before `name`: all of it,
+/// or only its prefix when it names `name` itself (a Swift extension,
+/// an Elixir `defimpl ..., for:`).
+fn parent_path(parent: &str, name: &str) -> Option<String> {
+    let path = qualified_path(parent);
+    if type_name(parent) != name {
+        return Some(path);
     }
+    path.rsplit_once('.').map(|(prefix, _)| prefix.to_string())
 }
 
-// ─── Helper Functions ───────────────────────────────────────────
+/// A written type path in qualified-name form:
+/// `geo::Shape` -> `geo.Shape`, `App\\Models` -> `App.Models`.
+fn qualified_path(text: &str) -> String {
+    let bare = text.split(['<', '[', '(']).next().unwrap_or(text);
+    bare.trim_start_matches(['*', '&', ':', '\\'])
+        .replace("::", ".")
+        .replace('\\', ".")
+}
 
-/// Get the name of a node from its "name" field.
-fn node_name(node: &Node, source: &[u8]) -> Option<String> {
-    node.child_by_field_name("name")
-        .and_then(|n| n.utf8_text(source).ok())
-        .map(|s| s.to_string())
+/// Strip quotes and angle brackets from a captured import path
+/// (`"fmt"`, `'./x'`, `<stdio.h>`).
+fn import_path(text: &str) -> String {
+    text.trim_matches(|c| matches!(c, '"' | '\'' | '`' | '<' | '>'))
+        .to_string()
 }
 
-/// Get the full text of a node.
-fn node_text(node: &Node, source: &[u8]) -> String {
-    node.utf8_text(source).unwrap_or("").to_string()
+/// How a call is written, from the call node and its callee: the text
+/// from the start of the call to the end of the callee name holds any
+/// receiver or path (`user.login`, `User::new`, `$this->save`,
+/// `player:move`). A capitalised receiver is taken to be a type
+/// (`Math.max`); calls that turn out to name a class are made
+/// constructors when linked.
+fn call_style(call: Node, callee: Node, source: &[u8]) -> CallStyle {
+    let kind = call.kind();
+    if kind.starts_with("new_")
+        || kind.contains("object_creation")
+        || kind.contains("instance_creation")
+    {
+        return CallStyle::Constructor;
+    }
+    if kind.starts_with("scoped_") {
+        return CallStyle::Qualified;
+    }
+    let path = source
+        .get(call.start_byte()..callee.end_byte().max(call.start_byte()))
+        .and_then(|bytes| std::str::from_utf8(bytes).ok())
+        .unwrap_or("")
+        .trim();
+    if path.contains("::") || path.contains('\\') {
+        return CallStyle::Qualified;
+    }
+    let receiver = match path.rfind(['.', '>', ':']) {
+        Some(end) => path[..end].trim_end_matches(['-', '?']),
+        None => return CallStyle::Direct,
+    };
+    let receiver = receiver
+        .rsplit(['.', '>', '$'])
+        .next()
+        .unwrap_or(receiver)
+        .trim();
+    if receiver.starts_with(|c: char| c.is_uppercase()) && !receiver.ends_with([')', ']']) {
+        CallStyle::Qualified
+    } else {
+        CallStyle::Method
+    }
+}
+
+/// Reduce a callee expression to its final name segment:
+/// `obj.method` -> `method`, `mod::func` -> `func`, `\App\func` -> `func`.
+fn call_name(text: &str) -> String {
+    text.rsplit(['.', ':', '\\'])
+        .next()
+        .unwrap_or(text)
+        .trim()
+        .to_string()
+}
+
+/// Reduce a type expression to its bare name:
+/// `java.util.List<String>` -> `List`, `Base(name)` -> `Base`.
+fn type_name(text: &str) -> String {
+    call_name(text.split(['<', '[', '(']).next().unwrap_or(text))
 }
 
-/// Maximum lines kept in a code snippet.
-const MAX_SNIPPET_LINES: usize = 50;
+// ─── Snippets ───────────────────────────────────────────────────
+
 /// Maximum bytes kept in a code snippet.
 const MAX_SNIPPET_BYTES: usize = 8192;
 
-/// Truncate a code snippet to bounded size (lines and bytes).
+/// Truncate a code snippet to bounded size (bytes). The line limit is
+/// configurable and applied afterwards by `truncate_lines`.
 fn bounded_snippet(node: &Node, source: &[u8]) -> String {
     let raw = node.utf8_text(source).unwrap_or("").to_string();
 
-    // Apply byte limit first
-    let byte_bounded = if raw.len() > MAX_SNIPPET_BYTES {
+    if raw.len() > MAX_SNIPPET_BYTES {
         // Find a clean UTF-8 boundary
         let mut end = MAX_SNIPPET_BYTES;
         while end > 0 && !raw.is_char_boundary(end) {
@@ -664,164 +858,686 @@ fn bounded_snippet(node: &Node, source: &[u8]) -> String {
         s
     } else {
         raw
-    };
-
-    // Apply line limit
-    let lines: Vec<&str> = byte_bounded.lines().collect();
-    if lines.len() <= MAX_SNIPPET_LINES {
-        byte_bounded
-    } else {
-        let mut truncated: String = lines[..MAX_SNIPPET_LINES].join("\n");
-        truncated.push_str("\n    // ...");
-        truncated
     }
 }
 
-/// Get the type name from a Rust impl block.
-/// Handles `impl Foo` and `impl Trait for Foo`.
-fn get_rust_impl_name(node: &Node, source: &[u8]) -> Option<String> {
-    // Try "type" field first (impl Type)
-    if let Some(type_node) = node.child_by_field_name("type") {
-        return type_node.utf8_text(source).ok().map(|s| s.to_string());
-    }
-    // Try "body" sibling for the type name
-    let text = node_text(node, source);
-    // Parse "impl Foo {" or "impl Trait for Foo {"
-    let parts: Vec<&str> = text.split_whitespace().collect();
-    if parts.len() >= 2 {
-        if parts.contains(&"for") {
-            // impl Trait for Type
-            parts
-                .iter()
-                .position(|&p| p == "for")
-                .and_then(|i| parts.get(i + 1))
-                .map(|s| s.trim_end_matches('{').trim().to_string())
-        } else {
-            // impl Type
-            Some(
-                parts[1]
-                    .trim_end_matches('{')
-                    .trim_end_matches('<')
-                    .to_string(),
-            )
+/// Cut a snippet down to `max_lines` lines.
+fn truncate_lines(snippet: &mut String, max_lines: usize) {
+    if let Some((cut, _)) = snippet.match_indices('\n').nth(max_lines.saturating_sub(1)) {
+        if snippet[cut + 1..].is_empty() {
+            return;
         }
-    } else {
-        None
+        snippet.truncate(cut);
+        snippet.push_str("\n    // ...");
     }
 }
 
-/// Get the function name from a call_expression node (Rust/JS/TS).
-fn get_call_name(node: &Node, source: &[u8]) -> Option<String> {
-    let func_node = node.child_by_field_name("function")?;
-    let text = func_node.utf8_text(source).ok()?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::build_graph;
+    use std::fs;
+    use tempfile::tempdir;
 
-    // Handle method calls: obj.method() -> "method"
-    // Handle simple calls: func() -> "func"
-    // Handle namespaced: mod::func() -> "func"
-    let name = text.rsplit(['.', ':']).next().unwrap_or(text).trim();
+    fn extract(name: &str, source: &str) -> FileExtractions {
+        extract_file(Path::new(name), source).unwrap()
+    }
 
-    if name.is_empty() {
-        None
-    } else {
-        Some(name.to_string())
+    fn symbol<'a>(
+        extraction: &'a FileExtractions,
+        name: &str,
+        kind: NodeKind,
+    ) -> &'a ExtractedSymbol {
+        extraction
+            .symbols
+            .iter()
+            .find(|s| s.name == name && s.kind == kind)
+            .unwrap_or_else(|| panic!("no {:?} {}", kind, name))
     }
-}
 
-/// Get the function name from a Python call node.
-fn get_python_call_name(node: &Node, source: &[u8]) -> Option<String> {
-    let func_node = node.child_by_field_name("function")?;
-    let text = func_node.utf8_text(source).ok()?;
+    #[test]
+    fn test_rust_metadata() {
+        let file = extract(
+            "lib.rs",
+            "/// Adds two numbers.\n///\n/// Saturates on overflow.\n#[inline]\n#[deprecated(note = \"use add2\")]\n\
+             pub async unsafe fn add(a: i32,\n    b: i32) -> i32 {\n    a + b\n}\n\n\
+             pub(crate) struct Config {\n    pub name: String,\n    #[serde(default)]\n    retries: u32,\n}\n",
+        );
+        let add = &symbol(&file, "add", NodeKind::Function).metadata;
+        assert_eq!(
+            add.signature.as_deref(),
+            Some("pub async unsafe fn add(a: i32, b: i32) -> i32")
+        );
+        assert_eq!(add.visibility, Some(Visibility::Public));
+        assert_eq!(add.modifiers, vec!["async", "unsafe", "deprecated"]);
+        assert_eq!(
+            add.docstring.as_deref(),
+            Some("Adds two numbers.\n\nSaturates on overflow.")
+        );
+        assert_eq!(
+            add.decorators,
+            vec!["inline", "deprecated(note = \"use add2\")"]
+        );
+        assert_eq!(
+            symbol(&file, "Config", NodeKind::Struct)
+                .metadata
+                .visibility,
+            Some(Visibility::Internal)
+        );
+        let retries = &symbol(&file, "retries", NodeKind::Variable).metadata;
+        assert_eq!(retries.visibility, Some(Visibility::Private));
+        assert_eq!(retries.decorators, vec!["serde(default)"]);
+    }
 
-    // Handle: obj.method() -> "method"
-    // Handle: func() -> "func"
-    let name = text.rsplit('.').next().unwrap_or(text).trim();
+    #[test]
+    fn test_python_metadata_and_decorators() {
+        let file = extract(
+            "service.py",
+            "@dataclass(frozen=True)\nclass Service:\n    \"\"\"Runs jobs.\n\n    Twice a day.\n    \"\"\"\n\n    \
+             @staticmethod\n    async def run(job: str) -> bool:\n        return True\n\n    \
+             @property\n    @cached\n    def label(self):\n        pass\n\n    def _retry(self):\n        pass\n",
+        );
+        let service = &symbol(&file, "Service", NodeKind::Class).metadata;
+        assert_eq!(
+            service.docstring.as_deref(),
+            Some("Runs jobs.\n\nTwice a day.")
+        );
+        assert_eq!(service.decorators, vec!["dataclass(frozen=True)"]);
+        let run = &symbol(&file, "run", NodeKind::Method).metadata;
+        assert_eq!(
+            run.signature.as_deref(),
+            Some("async def run(job: str) -> bool")
+        );
+        assert_eq!(run.decorators, vec!["staticmethod"]);
+        assert!(run.has_modifier("async"));
+        assert_eq!(
+            symbol(&file, "label", NodeKind::Method).metadata.decorators,
+            vec!["property", "cached"]
+        );
+        assert_eq!(
+            symbol(&file, "_retry", NodeKind::Method)
+                .metadata
+                .visibility,
+            Some(Visibility::Private)
+        );
+    }
 
-    if name.is_empty() {
-        None
-    } else {
-        Some(name.to_string())
+    #[test]
+    fn test_java_and_csharp_metadata_and_qualified_names() {
+        let file = extract(
+            "Api.java",
+            "package com.acme.api;\n\npublic class Api {\n    /** Handles GET. */\n    @Override\n    \
+             protected static synchronized String get(int id) {\n        return null;\n    }\n}\n",
+        );
+        let get = symbol(&file, "get", NodeKind::Method);
+        assert_eq!(get.qualified_name.as_deref(), Some("com.acme.api.Api#get"));
+        assert_eq!(get.metadata.visibility, Some(Visibility::Protected));
+        assert_eq!(get.metadata.modifiers, vec!["static", "synchronized"]);
+        assert_eq!(get.metadata.docstring.as_deref(), Some("Handles GET."));
+        assert!(get.metadata.has_decorator("override"));
+
+        let file = extract(
+            "Greeter.cs",
+            "namespace Acme.Greeting\n{\n    public class Greeter { public void Greet() {} }\n}\n",
+        );
+        assert_eq!(
+            symbol(&file, "Greet", NodeKind::Method)
+                .qualified_name
+                .as_deref(),
+            Some("Acme.Greeting.Greeter#Greet")
+        );
     }
-}
 
-/// Extract variable declarations that define functions or constants (JS/TS).
-fn extract_js_variable_declaration(
-    node: &Node,
-    source: &[u8],
-    current_scope: Option<&str>,
-    symbols: &mut Vec<ExtractedSymbol>,
-) {
-    let child_count = node.child_count();
-    for i in 0..child_count {
-        if let Some(declarator) = node.child(i) {
-            if declarator.kind() == "variable_declarator" {
-                let name = node_name(&declarator, source);
-                let value = declarator.child_by_field_name("value");
-
-                if let (Some(name), Some(value)) = (name, value) {
-                    let kind = match value.kind() {
-                        "arrow_function" | "function" => NodeKind::Function,
-                        _ => {
-                            // Check if it's a constant (ALL_CAPS name)
-                            if name.chars().all(|c| c.is_uppercase() || c == '_') {
-                                NodeKind::Constant
-                            } else {
-                                NodeKind::Variable
-                            }
-                        }
-                    };
-
-                    symbols.push(ExtractedSymbol {
-                        name,
-                        kind,
-                        line_start: node.start_position().row + 1,
-                        line_end: node.end_position().row + 1,
-                        code_snippet: bounded_snippet(node, source),
-                        parent: current_scope.map(|s| s.to_string()),
-                    });
-                }
-            }
+    #[test]
+    fn test_typescript_metadata_and_decorators() {
+        let file = extract(
+            "store.ts",
+            "@Component({ tag: 'app' })\nexport class Store {\n  \
+             private readonly cache: Map<string, number> = new Map();\n  @Input() title: string;\n\n  \
+             @Watch('title')\n  @Log()\n  changed() {}\n\n  \
+             static async load(): Promise<Store> {\n    return new Store();\n  }\n}\n",
+        );
+        assert_eq!(
+            symbol(&file, "Store", NodeKind::Class).metadata.decorators,
+            vec!["Component({ tag: 'app' })"]
+        );
+        let cache = &symbol(&file, "cache", NodeKind::Variable).metadata;
+        assert_eq!(cache.visibility, Some(Visibility::Private));
+        assert_eq!(cache.modifiers, vec!["readonly"]);
+        assert_eq!(
+            symbol(&file, "title", NodeKind::Variable)
+                .metadata
+                .decorators,
+            vec!["Input()"]
+        );
+        assert_eq!(
+            symbol(&file, "changed", NodeKind::Method)
+                .metadata
+                .decorators,
+            vec!["Watch('title')", "Log()"]
+        );
+        let load = &symbol(&file, "load", NodeKind::Method).metadata;
+        assert_eq!(
+            load.signature.as_deref(),
+            Some("static async load(): Promise<Store>")
+        );
+        assert_eq!(load.visibility, Some(Visibility::Public));
+    }
+
+    #[test]
+    fn test_kotlin_declarations() {
+        let file = extract(
+            "User.kt",
+            "package com.acme.users\n\nimport com.acme.base.Entity\n\n\
+             data class User(val id: Long) : Entity(), Named {\n    \
+             companion object {\n        const val MAX = 10\n    }\n    \
+             override fun label(): String = id.toString()\n}\n\n\
+             object Registry {\n    private val users = mutableListOf<User>()\n}\n\n\
+             fun String?.shout(): String = orEmpty()\n\n\
+             @GetMapping(\"/users\")\nsuspend fun handler(): User? {\n    val local = 1\n    return null\n}\n",
+        );
+        let user = symbol(&file, "User", NodeKind::Class);
+        assert_eq!(user.qualified_name.as_deref(), Some("com.acme.users.User"));
+        assert_eq!(user.metadata.modifiers, vec!["data"]);
+        assert_eq!(
+            symbol(&file, "id", NodeKind::Variable).parent.as_deref(),
+            Some("User")
+        );
+        assert_eq!(
+            symbol(&file, "MAX", NodeKind::Constant).parent.as_deref(),
+            Some("User")
+        );
+        assert_eq!(
+            symbol(&file, "label", NodeKind::Method).metadata.modifiers,
+            vec!["override"]
+        );
+        let users = symbol(&file, "users", NodeKind::Variable);
+        assert_eq!(users.parent.as_deref(), Some("Registry"));
+        assert_eq!(users.metadata.visibility, Some(Visibility::Private));
+        // Extension functions belong to their receiver type
+        assert_eq!(
+            symbol(&file, "shout", NodeKind::Method).parent.as_deref(),
+            Some("String")
+        );
+        let handler = &symbol(&file, "handler", NodeKind::Function).metadata;
+        assert_eq!(handler.modifiers, vec!["suspend"]);
+        assert_eq!(handler.decorators, vec!["GetMapping(\"/users\")"]);
+        assert!(file.symbols.iter().all(|s| s.name != "local"));
+        assert_eq!(file.imports[0].path, "com.acme.base.Entity");
+
+        let supertypes: Vec<_> = file
+            .relations
+            .iter()
+            .filter(|r| r.source == "User" && r.reference.is_none())
+            .map(|r| (r.kind, r.target.clone()))
+            .collect();
+        assert_eq!(
+            supertypes,
+            vec![
+                (
+                    EdgeKind::Extends,
+                    RelationTarget::Symbol("Entity".to_string())
+                ),
+                (
+                    EdgeKind::Implements,
+                    RelationTarget::Symbol("Named".to_string())
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scala_declarations() {
+        let file = extract(
+            "Circle.scala",
+            "package geo.shapes\n\nimport geo.util.{Helper, Math => M}\nimport scala.collection.mutable\n\n\
+             case class Circle(r: Double) extends Base(1) with Shape with Ordered[Circle] {\n  \
+             private var hits = 0\n  \
+             override def area(): Double = { hits = hits + 1; Helper.scale(r) }\n}\n\n\
+             object Circle {\n  final val MaxRadius = 10\n}\n\n\
+             extension (c: Circle) def diameter: Double = c.r * 2\n\n\
+             @main def run(): Unit = {\n  val local = 1\n}\n",
+        );
+        let circle = symbol(&file, "Circle", NodeKind::Class);
+        assert_eq!(circle.qualified_name.as_deref(), Some("geo.shapes.Circle"));
+        let hits = symbol(&file, "hits", NodeKind::Variable);
+        assert_eq!(hits.parent.as_deref(), Some("Circle"));
+        assert_eq!(hits.metadata.visibility, Some(Visibility::Private));
+        assert_eq!(
+            symbol(&file, "area", NodeKind::Method).metadata.modifiers,
+            vec!["override"]
+        );
+        assert_eq!(
+            symbol(&file, "MaxRadius", NodeKind::Constant)
+                .metadata
+                .modifiers,
+            vec!["final"]
+        );
+        // Extension methods belong to the type they extend
+        assert_eq!(
+            symbol(&file, "diameter", NodeKind::Method)
+                .parent
+                .as_deref(),
+            Some("Circle")
+        );
+        assert_eq!(
+            symbol(&file, "run", NodeKind::Function).metadata.decorators,
+            vec!["main"]
+        );
+        assert!(file.symbols.iter().all(|s| s.name != "local"));
+
+        // Import paths are captured piece by piece, renamed selectors by
+        // their original name
+        assert_eq!(file.imports[0].path, "geo.util");
+        assert_eq!(file.imports[0].symbols, vec!["Helper", "Math"]);
+        assert_eq!(file.imports[1].path, "scala.collection.mutable");
+
+        let supertypes: Vec<_> = file
+            .relations
+            .iter()
+            .filter(|r| r.source == "Circle" && r.reference.is_none())
+            .map(|r| (r.kind, r.target.clone()))
+            .collect();
+        assert_eq!(supertypes.len(), 3);
+        for (kind, target) in [
+            (EdgeKind::Extends, "Base"),
+            (EdgeKind::Implements, "Shape"),
+            (EdgeKind::Implements, "Ordered"),
+        ] {
+            assert!(supertypes.contains(&(kind, RelationTarget::Symbol(target.to_string()))));
         }
+        let scale = file.calls.iter().find(|c| c.callee == "scale").unwrap();
+        assert_eq!(scale.caller, "area");
+        assert_eq!(scale.style, CallStyle::Qualified);
     }
-}
 
-/// Extract JS/TS import statements.
-fn extract_js_import(node: &Node, source: &[u8], imports: &mut Vec<ExtractedImport>) {
-    let text = node_text(node, source);
+    #[test]
+    fn test_lua_declarations() {
+        let file = extract(
+            "point.lua",
+            "local json = require(\"json\")\nlocal geo = require \"geo.util\"\n\n\
+             Point = {}\nPoint.__index = Point\nlocal MAX = 10\n\n\
+             function Point.new(x, y)\n  return setmetatable({x = x, y = y}, Point)\nend\n\n\
+             function Point:dist(other)\n  local d = geo.sqrt(self.x)\n  return self:scale(d)\nend\n\n\
+             local function clamp(v)\n  local inner = 1\n  return v\nend\n\n\
+             Point.draw = function(self) print(MAX) end\n",
+        );
+        assert_eq!(
+            symbol(&file, "Point", NodeKind::Class).metadata.visibility,
+            Some(Visibility::Public)
+        );
+        assert_eq!(
+            symbol(&file, "MAX", NodeKind::Constant).metadata.visibility,
+            Some(Visibility::Private)
+        );
+        for method in ["new", "dist", "draw"] {
+            assert_eq!(
+                symbol(&file, method, NodeKind::Method).parent.as_deref(),
+                Some("Point")
+            );
+        }
+        assert_eq!(
+            symbol(&file, "clamp", NodeKind::Function)
+                .metadata
+                .visibility,
+            Some(Visibility::Private)
+        );
+        // Locals and required modules aren't definitions
+        for name in ["inner", "d", "json", "geo"] {
+            assert!(file.symbols.iter().all(|s| s.name != name), "{}", name);
+        }
+        let imports: Vec<_> = file.imports.iter().map(|i| i.path.as_str()).collect();
+        assert_eq!(imports, vec!["json", "geo.util"]);
 
-    // Extract the module path from: import { x } from 'path'
-    // or: import x from 'path'
-    let path = text
-        .rsplit("from")
-        .next()
-        .unwrap_or("")
-        .trim()
-        .trim_matches(|c| c == '\'' || c == '"' || c == ';' || c == ' ')
-        .to_string();
-
-    // Extract imported symbols
-    let syms: Vec<String> = if text.contains('{') {
-        text.split('{')
-            .nth(1)
-            .unwrap_or("")
-            .split('}')
-            .next()
-            .unwrap_or("")
-            .split(',')
-            .map(|s| {
-                // Handle "x as y" -> take "x"
-                s.split(" as ").next().unwrap_or("").trim().to_string()
+        let style = |callee: &str| {
+            file.calls
+                .iter()
+                .find(|c| c.callee == callee)
+                .unwrap()
+                .style
+        };
+        assert_eq!(style("sqrt"), CallStyle::Method);
+        assert_eq!(style("scale"), CallStyle::Method);
+        assert_eq!(style("setmetatable"), CallStyle::Direct);
+    }
+
+    #[test]
+    fn test_sql_schema() {
+        let file = extract(
+            "schema.sql",
+            "CREATE TABLE teams (id INT PRIMARY KEY);\n\n\
+             -- Everyone who can sign in\n\
+             CREATE TABLE users (\n  id BIGINT PRIMARY KEY,\n  team_id INT REFERENCES teams(id)\n);\n\
+             CREATE INDEX idx_users_team ON users (team_id);\n\
+             ALTER TABLE users ADD COLUMN email TEXT;\n\
+             CREATE VIEW team_sizes AS SELECT t.id, count(*) FROM teams t JOIN users u ON u.team_id = t.id;\n",
+        );
+        let users = symbol(&file, "users", NodeKind::Struct);
+        assert_eq!(
+            users.metadata.docstring.as_deref(),
+            Some("Everyone who can sign in")
+        );
+        for (name, kind) in [
+            ("team_id", NodeKind::Variable),
+            ("email", NodeKind::Variable),
+            ("idx_users_team", NodeKind::Constant),
+        ] {
+            assert_eq!(symbol(&file, name, kind).parent.as_deref(), Some("users"));
+        }
+        let references = |source: &str| -> Vec<_> {
+            file.relations
+                .iter()
+                .filter(|r| r.source == source)
+                .map(|r| (r.kind, r.target.clone()))
+                .collect()
+        };
+        assert_eq!(
+            references("users"),
+            vec![(
+                EdgeKind::References,
+                RelationTarget::Symbol("teams".to_string())
+            )]
+        );
+        let view = references("team_sizes");
+        for table in ["teams", "users"] {
+            assert!(view.contains(&(
+                EdgeKind::UsesType,
+                RelationTarget::Symbol(table.to_string())
+            )));
+        }
+    }
+
+    #[test]
+    fn test_protobuf_schema() {
+        let file = extract(
+            "users.proto",
+            "syntax = \"proto3\";\npackage acme.users.v1;\n\nimport \"google/protobuf/timestamp.proto\";\n\n\
+             message User {\n  string id = 1;\n  google.protobuf.Timestamp created = 2;\n  \
+             map<string, Team> teams = 3;\n  enum Status { ACTIVE = 0; }\n}\n\n\
+             service UserService {\n  rpc GetUser(GetUserRequest) returns (User);\n}\n",
+        );
+        let user = symbol(&file, "User", NodeKind::Struct);
+        assert_eq!(user.qualified_name.as_deref(), Some("acme.users.v1.User"));
+        assert_eq!(
+            symbol(&file, "teams", NodeKind::Variable).parent.as_deref(),
+            Some("User")
+        );
+        assert_eq!(
+            symbol(&file, "ACTIVE", NodeKind::Constant)
+                .qualified_name
+                .as_deref(),
+            Some("acme.users.v1.User.Status#ACTIVE")
+        );
+        let rpc = symbol(&file, "GetUser", NodeKind::Method);
+        assert_eq!(rpc.parent.as_deref(), Some("UserService"));
+        assert_eq!(
+            rpc.metadata.signature.as_deref(),
+            Some("rpc GetUser(GetUserRequest) returns (User)")
+        );
+        assert_eq!(
+            symbol(&file, "UserService", NodeKind::Interface).name,
+            "UserService"
+        );
+        assert_eq!(file.imports[0].path, "google/protobuf/timestamp.proto");
+
+        let used: Vec<_> = file
+            .relations
+            .iter()
+            .filter(|r| r.kind == EdgeKind::UsesType)
+            .map(|r| (r.source.as_str(), r.target.clone()))
+            .collect();
+        for (source, target) in [
+            ("User", "Timestamp"),
+            ("User", "Team"),
+            ("GetUser", "GetUserRequest"),
+            ("GetUser", "User"),
+        ] {
+            assert!(used.contains(&(source, RelationTarget::Symbol(target.to_string()))));
+        }
+    }
+
+    #[test]
+    fn test_graphql_schema_and_operations() {
+        let file = extract(
+            "schema.graphql",
+            "\"\"\"Someone who can sign in\"\"\"\n\
+             type User implements Node & Entity {\n  id: ID!\n  posts(first: Int): [Post!]!\n}\n\n\
+             input NewUser { name: String! }\nenum Role { ADMIN }\nunion Result = User | Team\n\n\
+             type Query { me: User }\nextend type User { age: Int }\n\n\
+             query Profile { me { id ...Avatar } }\n",
+        );
+        let user = symbol(&file, "User", NodeKind::Class);
+        assert_eq!(
+            user.metadata.docstring.as_deref(),
+            Some("Someone who can sign in")
+        );
+        assert_eq!(
+            user.metadata.signature.as_deref(),
+            Some("type User implements Node & Entity")
+        );
+        // Fields taking arguments, and root fields, are resolved by code
+        assert_eq!(
+            symbol(&file, "posts", NodeKind::Method).parent.as_deref(),
+            Some("User")
+        );
+        assert_eq!(
+            symbol(&file, "me", NodeKind::Method).parent.as_deref(),
+            Some("Query")
+        );
+        assert_eq!(
+            symbol(&file, "id", NodeKind::Variable).parent.as_deref(),
+            Some("User")
+        );
+        assert_eq!(
+            symbol(&file, "age", NodeKind::Variable).parent.as_deref(),
+            Some("User")
+        );
+        symbol(&file, "name", NodeKind::Variable);
+        symbol(&file, "ADMIN", NodeKind::Constant);
+        symbol(&file, "Result", NodeKind::Type);
+
+        let implemented: Vec<_> = file
+            .relations
+            .iter()
+            .filter(|r| r.kind == EdgeKind::Implements)
+            .map(|r| (r.source.as_str(), r.target.clone()))
+            .collect();
+        assert_eq!(implemented.len(), 2);
+        assert!(implemented.contains(&("User", RelationTarget::Symbol("Entity".to_string()))));
+        assert!(implemented.contains(&("User", RelationTarget::Symbol("Node".to_string()))));
+
+        let calls: Vec<_> = file
+            .calls
+            .iter()
+            .map(|c| (c.caller.as_str(), c.callee.as_str()))
+            .collect();
+        assert_eq!(calls, vec![("Profile", "me"), ("Profile", "Avatar")]);
+    }
+
+    #[test]
+    fn test_source_ranges_count_bytes_and_utf16() {
+        // `é` is two UTF-8 bytes but one UTF-16 unit; `🦀` is four and two
+        let source = "// é🦀\nfn greet() {\n    let s = \"🦀\"; shout(s);\n}\n";
+        let file = extract("lib.rs", source);
+        let greet = symbol(&file, "greet", NodeKind::Function);
+        assert_eq!(greet.range.start.byte, source.find("fn greet").unwrap());
+        assert_eq!(greet.range.end.byte, source.len() - 1);
+        assert_eq!((greet.range.start.line, greet.range.end.line), (2, 4));
+        assert_eq!(greet.name_range.start.column, 3);
+        assert_eq!(
+            &source[greet.name_range.start.byte..greet.name_range.end.byte],
+            "greet"
+        );
+
+        let call = &file.calls[0];
+        assert_eq!(
+            &source[call.range.start.byte..call.range.end.byte],
+            "shout(s)"
+        );
+        assert_eq!(call.range.start.line, 3);
+        assert_eq!(call.range.start.column, "    let s = \"🦀\"; ".len());
+        assert_eq!(
+            call.range.start.column_utf16,
+            "    let s = \"🦀\"; ".encode_utf16().count()
+        );
+        assert_eq!(call.range.start.column - call.range.start.column_utf16, 2);
+    }
+
+    #[test]
+    fn test_call_styles() {
+        let file = extract(
+            "app.ts",
+            "class Cart {}\nfunction checkout() {\n  const cart = new Cart();\n  Math.max(1, 2);\n  cart.add();\n}\n",
+        );
+        let style = |callee: &str| {
+            file.calls
+                .iter()
+                .find(|c| c.callee == callee)
+                .unwrap()
+                .style
+        };
+        assert_eq!(style("Cart"), CallStyle::Constructor);
+        assert_eq!(style("max"), CallStyle::Qualified);
+        assert_eq!(style("add"), CallStyle::Method);
+    }
+
+    #[test]
+    fn test_call_sites_on_edges() {
+        use crate::graph::types::CallStyle;
+
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("lib.rs"),
+            "struct Store;\nimpl Store {\n    fn new() -> Self { Store }\n    fn save(&self) {}\n}\n\
+             fn helper() {}\n\
+             fn run() {\n    helper();\n    let s = Store::new();\n    s.save();\n    helper();\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("app.ts"),
+            "class Cart {}\nfunction checkout() {\n  const cart = new Cart();\n  Math.max(1, 2);\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("models.py"),
+            "class User:\n    pass\n\ndef make():\n    return User()\n",
+        )
+        .unwrap();
+
+        let graph = build_graph(root).unwrap();
+        let sites = |callee: &str, caller: &str| {
+            graph
+                .dependents(callee)
+                .into_iter()
+                .find(|d| d.symbol == caller && d.relationship == EdgeKind::Calls)
+                .unwrap()
+                .call_sites
+        };
+
+        let helper = sites("helper", "run");
+        assert_eq!(
+            helper.iter().map(|s| s.line).collect::<Vec<_>>(),
+            vec![8, 11]
+        );
+        assert!(helper.iter().all(|s| s.style == CallStyle::Direct));
+        let calls_to_helper = graph
+            .inner_graph()
+            .edge_indices()
+            .filter(|&e| {
+                let (_, b) = graph.inner_graph().edge_endpoints(e).unwrap();
+                graph.inner_graph()[b].name == "helper"
+                    && graph.inner_graph()[e].kind == EdgeKind::Calls
             })
-            .filter(|s| !s.is_empty())
-            .collect()
-    } else {
-        Vec::new()
-    };
+            .count();
+        assert_eq!(calls_to_helper, 1);
+        assert_eq!(sites("new", "run")[0].style, CallStyle::Qualified);
+        assert_eq!(sites("save", "run")[0].style, CallStyle::Method);
+        assert_eq!(sites("save", "run")[0].range.start.column, 4);
 
-    if !path.is_empty() {
-        imports.push(ExtractedImport {
-            path,
-            symbols: syms,
-            line: node.start_position().row + 1,
-        });
+        let cart = sites("Cart", "checkout");
+        assert_eq!((cart[0].line, cart[0].style), (3, CallStyle::Constructor));
+        assert_eq!(sites("User", "make")[0].style, CallStyle::Constructor);
+    }
+
+    #[test]
+    fn test_reference_edges() {
+        use crate::graph::types::ReferenceKind;
+
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("lib.rs"),
+            "const LIMIT: u32 = 3;\nstruct Config {\n    retries: u32,\n}\n\
+             fn tune(c: &mut Config, all: Vec<Config>) -> u32 {\n    c.retries = LIMIT;\n    c.retries\n}\n\
+             fn make() -> Config {\n    Config { retries: LIMIT }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("shapes.ts"),
+            "const MAX = 10;\nclass Shape {}\nfunction area(s: Shape): number {\n  if (s instanceof Shape) { return MAX; }\n  return 0;\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("models.py"),
+            "class User:\n    pass\n\nDEFAULT = 1\n\ndef check(u: User):\n    limit = DEFAULT\n    return isinstance(u, User)\n",
+        )
+        .unwrap();
+
+        let graph = build_graph(root).unwrap();
+        let sites = |target: &str, user: &str| {
+            graph
+                .dependents(target)
+                .into_iter()
+                .filter(|d| d.symbol == user)
+                .flat_map(|d| d.reference_sites)
+                .map(|s| (s.line, s.kind))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(sites("LIMIT", "tune"), vec![(6, ReferenceKind::Read)]);
+        assert_eq!(
+            sites("retries", "tune"),
+            vec![(6, ReferenceKind::Write), (7, ReferenceKind::Read)]
+        );
+        assert_eq!(
+            sites("Config", "tune"),
+            vec![(5, ReferenceKind::Type), (5, ReferenceKind::Type)]
+        );
+        assert_eq!(
+            sites("Config", "make"),
+            vec![(9, ReferenceKind::Type), (10, ReferenceKind::Construct)]
+        );
+
+        assert_eq!(
+            sites("Shape", "area"),
+            vec![(3, ReferenceKind::Type), (4, ReferenceKind::TypeCheck)]
+        );
+        assert_eq!(sites("MAX", "area"), vec![(4, ReferenceKind::Read)]);
+
+        assert_eq!(
+            sites("User", "check"),
+            vec![(6, ReferenceKind::Type), (8, ReferenceKind::TypeCheck)]
+        );
+        assert_eq!(sites("DEFAULT", "check"), vec![(7, ReferenceKind::Read)]);
+
+        // Reads and writes share References edges; type mentions use UsesType
+        let kinds = |target: &str, user: &str| {
+            graph
+                .dependents(target)
+                .into_iter()
+                .filter(|d| d.symbol == user)
+                .map(|d| d.relationship)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(kinds("retries", "tune"), vec![EdgeKind::References]);
+        assert!(kinds("Config", "make").contains(&EdgeKind::UsesType));
+
+        // Definition names and callees are not usages
+        assert!(sites("Config", "Config").is_empty());
+        assert!(graph
+            .dependents("Shape")
+            .iter()
+            .all(|d| d.symbol != "Shape"));
     }
 }
@@ -0,0 +1,781 @@
+//! Incremental re-extraction — reuse the last parse of a file.
+//!
+//! `TreeCache` keeps, per file, the source, tree-sitter tree and extraction
+//! of the last time it was extracted. When the file changes, the old tree
+//! is edited to match (`Tree::edit`) and reparsed incrementally, and only
+//! the top-level items covering the changed ranges are re-extracted:
+//! items before them are reused as they were, items after them shifted
+//! by the lines and bytes the edit added or removed.
+//!
+//! Items are widened to whole rows and to the items right after them
+//! without a blank line in between, since attributes and doc comments
+//! belong to the definition that follows. Files that declare a package
+//! or namespace are re-extracted whole (from the incremental tree), since
+//! the package qualifies every name in them, and so are files with a
+//! symbol reaching across the changed items. Near a syntax error the file
+//! is parsed from scratch as well: error recovery depends on the trees
+//! parsed before, so the incremental tree may not be the one a fresh
+//! parse gives.
+
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use tree_sitter::{InputEdit, Node, Tree};
+
+use super::extractor::{extract_tree, parse, point_at, ExtractOptions};
+use super::language::SupportedLanguage;
+use super::tags::TagQuery;
+use crate::graph::types::{ExtractedSymbol, FileExtractions, SourcePoint, SourceRange};
+
+/// Default number of files whose trees are kept.
+pub const DEFAULT_TREE_CACHE_SIZE: usize = 256;
+
+/// Last extraction of recently extracted files, least recently used
+/// evicted first.
+pub struct TreeCache {
+    capacity: usize,
+    entries: Mutex<Entries>,
+    clock: AtomicU64,
+}
+
+/// Cached files, and their paths in order of last use.
+#[derive(Default)]
+struct Entries {
+    files: HashMap<PathBuf, CachedFile>,
+    by_use: BTreeMap<u64, PathBuf>,
+}
+
+impl Entries {
+    fn remove(&mut self, path: &Path) -> Option<CachedFile> {
+        let cached = self.files.remove(path)?;
+        self.by_use.remove(&cached.last_used);
+        Some(cached)
+    }
+
+    /// Insert `cached`, evicting the least recently used files beyond
+    /// `capacity`.
+    fn insert(&mut self, path: PathBuf, cached: CachedFile, capacity: usize) {
+        self.remove(&path);
+        while self.files.len() >= capacity {
+            let Some((_, oldest)) = self.by_use.pop_first() else {
+                break;
+            };
+            self.files.remove(&oldest);
+        }
+        self.by_use.insert(cached.last_used, path.clone());
+        self.files.insert(path, cached);
+    }
+}
+
+struct CachedFile {
+    language: SupportedLanguage,
+    /// Generation of the query and the options it was extracted with: a
+    /// changed query (e.g. reloaded overrides) or options means
+    /// extracting from scratch.
+    generation: u64,
+    options: ExtractOptions,
+    source: String,
+    tree: Tree,
+    extractions: FileExtractions,
+    /// Whether the file declares a `@package`.
+    packaged: bool,
+    last_used: u64,
+}
+
+impl Default for TreeCache {
+    fn default() -> Self {
+        Self::new(DEFAULT_TREE_CACHE_SIZE)
+    }
+}
+
+impl TreeCache {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            entries: Mutex::new(Entries::default()),
+            clock: AtomicU64::new(0),
+        }
+    }
+
+    /// Extract `source` as the new contents of `path`, incrementally when
+    /// its previous contents are cached. Same result as
+    /// `extract_file_with`.
+    pub fn extract(
+        &self,
+        path: &Path,
+        source: &str,
+        query: &TagQuery,
+        options: &ExtractOptions,
+    ) -> crate::error::Result<FileExtractions> {
+        // Taken out rather than borrowed so other files extract in parallel
+        let cached = self.lock().remove(path).filter(|cached| {
+            cached.generation == query.generation()
+                && cached.options == *options
+                && cached.language == query.language()
+        });
+
+        let (tree, extractions, packaged) = match cached {
+            Some(cached) if cached.source == source => {
+                (cached.tree, cached.extractions, cached.packaged)
+            }
+            Some(cached) => reextract(path, source, query, options, cached)?,
+            None => {
+                let tree = parse(path, source, query.language(), &[], None)?;
+                let (extractions, packaged) =
+                    extract_tree(path, source, query, options, &tree, None);
+                (tree, extractions, packaged)
+            }
+        };
+
+        if self.capacity > 0 {
+            let cached = CachedFile {
+                language: query.language(),
+                generation: query.generation(),
+                options: *options,
+                source: source.to_string(),
+                tree,
+                extractions: extractions.clone(),
+                packaged,
+                last_used: self.clock.fetch_add(1, Ordering::Relaxed),
+            };
+            self.lock()
+                .insert(path.to_path_buf(), cached, self.capacity);
+        }
+        Ok(extractions)
+    }
+
+    /// Drop a file's cached parse (e.g. it was deleted).
+    pub fn forget(&self, path: &Path) {
+        self.lock().remove(path);
+    }
+
+    /// Whether `path` has a cached parse.
+    pub fn contains(&self, path: &Path) -> bool {
+        self.lock().files.contains_key(path)
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Entries> {
+        self.entries.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Reparse from the cached tree and re-extract the changed items.
+fn reextract(
+    path: &Path,
+    source: &str,
+    query: &TagQuery,
+    options: &ExtractOptions,
+    cached: CachedFile,
+) -> crate::error::Result<(Tree, FileExtractions, bool)> {
+    let CachedFile {
+        source: old_source,
+        mut tree,
+        extractions: old,
+        packaged,
+        ..
+    } = cached;
+    let edit = input_edit(&old_source, source);
+    tree.edit(&edit);
+    let new_tree = parse(path, source, query.language(), &[], Some(&tree))?;
+
+    let mut changed: Vec<Range<usize>> = tree
+        .changed_ranges(&new_tree)
+        .map(|r| r.start_byte..r.end_byte)
+        .collect();
+    changed.push(edit.start_byte..edit.new_end_byte);
+    let span = affected_span(new_tree.root_node(), &changed);
+
+    // Error recovery depends on the trees parsed before: next to a syntax
+    // error the incremental tree may differ from a fresh parse (e.g. one
+    // error swallowing items the other keeps), so parse from scratch
+    let root = new_tree.root_node();
+    if span
+        .as_ref()
+        .map_or(root.has_error(), |span| error_near(root, span))
+        || skips_text(root, source)
+    {
+        let tree = parse(path, source, query.language(), &[], None)?;
+        let (extractions, packaged) = extract_tree(path, source, query, options, &tree, None);
+        return Ok((tree, extractions, packaged));
+    }
+    let Some(span) = span.filter(|_| !packaged) else {
+        let (extractions, packaged) = extract_tree(path, source, query, options, &new_tree, None);
+        return Ok((new_tree, extractions, packaged));
+    };
+    let (fresh, fresh_packaged) =
+        extract_tree(path, source, query, options, &new_tree, Some(span.clone()));
+
+    // Lines (1-indexed) of the span, widened to cover the edit itself (a
+    // deletion past the last item leaves nothing in the new tree)
+    let first = point_at(source, span.start.min(edit.start_byte)).row + 1;
+    let last = point_at(source, span.end.max(edit.new_end_byte)).row + 1;
+    let shift = Shift {
+        lines: source.matches('\n').count() as isize - old_source.matches('\n').count() as isize,
+        bytes: source.len() as isize - old_source.len() as isize,
+    };
+    let old_end = if span.end <= edit.start_byte {
+        span.end
+    } else {
+        (span.end as isize - shift.bytes) as usize
+    };
+    let old_last = point_at(&old_source, old_end.max(edit.old_end_byte)).row + 1;
+
+    // A symbol reaching across either end of the span (a whole-file
+    // module) would go stale
+    let straddles = |symbols: &[ExtractedSymbol], last: usize| {
+        symbols.iter().any(|s| {
+            (s.line_start < first && s.line_end >= first)
+                || (s.line_start <= last && s.line_end > last)
+        })
+    };
+    if fresh_packaged || straddles(&old.symbols, old_last) || straddles(&fresh.symbols, last) {
+        let (extractions, packaged) = extract_tree(path, source, query, options, &new_tree, None);
+        return Ok((new_tree, extractions, packaged));
+    }
+
+    let extractions = splice(old, fresh, first, last, old_last, shift);
+    Ok((new_tree, extractions, false))
+}
+
+/// The single edit turning `old` into `new`: everything between their
+/// common prefix and common suffix.
+fn input_edit(old: &str, new: &str) -> InputEdit {
+    let mut prefix = old
+        .bytes()
+        .zip(new.bytes())
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !new.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let mut suffix = old
+        .bytes()
+        .rev()
+        .zip(new.bytes().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !new.is_char_boundary(new.len() - suffix) {
+        suffix -= 1;
+    }
+    InputEdit {
+        start_byte: prefix,
+        old_end_byte: old.len() - suffix,
+        new_end_byte: new.len() - suffix,
+        start_position: point_at(new, prefix),
+        old_end_position: point_at(old, old.len() - suffix),
+        new_end_position: point_at(new, new.len() - suffix),
+    }
+}
+
+/// Byte span of the top-level items covering every changed range: from
+/// the item containing (or just before) the first change to the item
+/// containing (or just after) the last, widened to whole rows and to the
+/// items that follow without a blank line. None if the file has no
+/// top-level items to re-extract separately.
+fn affected_span(root: Node, changed: &[Range<usize>]) -> Option<Range<usize>> {
+    let start = changed.iter().map(|r| r.start).min()?;
+    let end = changed.iter().map(|r| r.end).max()?;
+    let mut cursor = root.walk();
+    let items: Vec<Node> = root.children(&mut cursor).collect();
+    if items.is_empty() {
+        return None;
+    }
+
+    let mut first = items
+        .iter()
+        .rposition(|n| n.start_byte() <= start)
+        .unwrap_or(0);
+    let mut last = items
+        .iter()
+        .position(|n| n.end_byte() >= end)
+        .unwrap_or(items.len() - 1)
+        .max(first);
+    let last_end_row = |last: usize| items[last].end_position().row;
+    while first > 0 && items[first - 1].end_position().row >= items[first].start_position().row {
+        first -= 1;
+    }
+    while last + 1 < items.len() && items[last + 1].start_position().row <= last_end_row(last) + 1 {
+        last += 1;
+    }
+    Some(items[first].start_byte()..items[last].end_byte())
+}
+
+/// Whether a top-level item touching `span`, or next to one, has a
+/// syntax error.
+fn error_near(root: Node, span: &Range<usize>) -> bool {
+    let mut cursor = root.walk();
+    let items: Vec<Node> = root.children(&mut cursor).collect();
+    let touching = |n: &Node| n.end_byte() >= span.start && n.start_byte() <= span.end;
+    let (Some(first), Some(last)) = (
+        items.iter().position(touching),
+        items.iter().rposition(touching),
+    ) else {
+        return root.has_error();
+    };
+    let near = &items[first.saturating_sub(1)..(last + 2).min(items.len())];
+    root.is_error() || near.iter().any(|n| n.has_error())
+}
+
+/// Whether the top-level items leave text out: some grammars (Kotlin)
+/// give up on a file without an error node, dropping the rest of it.
+fn skips_text(root: Node, source: &str) -> bool {
+    let mut cursor = root.walk();
+    let mut at = root.start_byte();
+    for item in root.children(&mut cursor) {
+        if !source[at..item.start_byte().max(at)].trim().is_empty() {
+            return true;
+        }
+        at = at.max(item.end_byte());
+    }
+    !source[at..root.end_byte().max(at)].trim().is_empty()
+}
+
+/// Lines and bytes an edit added (or, negative, removed).
+#[derive(Clone, Copy)]
+struct Shift {
+    lines: isize,
+    bytes: isize,
+}
+
+impl Shift {
+    fn line(self, line: usize) -> usize {
+        (line as isize + self.lines) as usize
+    }
+
+    fn range(self, range: &mut SourceRange) {
+        if range.is_empty() {
+            return;
+        }
+        for point in [&mut range.start, &mut range.end] {
+            *point = SourcePoint {
+                byte: (point.byte as isize + self.bytes) as usize,
+                line: self.line(point.line),
+                ..*point
+            };
+        }
+    }
+}
+
+/// Old items before line `first`, the fresh items of lines
+/// `first..=last`, then old items after `old_last` moved by `shift`.
+fn splice(
+    old: FileExtractions,
+    fresh: FileExtractions,
+    first: usize,
+    last: usize,
+    old_last: usize,
+    shift: Shift,
+) -> FileExtractions {
+    fn three<T>(
+        old: Vec<T>,
+        fresh: Vec<T>,
+        line: impl Fn(&T) -> usize,
+        (first, last, old_last): (usize, usize, usize),
+        mut moved: impl FnMut(&mut T),
+    ) -> Vec<T> {
+        let mut before = Vec::new();
+        let mut after = Vec::new();
+        for item in old {
+            if line(&item) < first {
+                before.push(item);
+            } else if line(&item) > old_last {
+                after.push(item);
+            }
+        }
+        let within = fresh
+            .into_iter()
+            .filter(|item| (first..=last).contains(&line(item)));
+        after.iter_mut().for_each(&mut moved);
+        before.into_iter().chain(within).chain(after).collect()
+    }
+    let lines = (first, last, old_last);
+
+    FileExtractions {
+        file_path: old.file_path,
+        symbols: three(
+            old.symbols,
+            fresh.symbols,
+            |s| s.line_start,
+            lines,
+            |s| {
+                s.line_start = shift.line(s.line_start);
+                s.line_end = shift.line(s.line_end);
+                shift.range(&mut s.range);
+                shift.range(&mut s.name_range);
+            },
+        ),
+        imports: three(
+            old.imports,
+            fresh.imports,
+            |i| i.line,
+            lines,
+            |i| i.line = shift.line(i.line),
+        ),
+        calls: three(
+            old.calls,
+            fresh.calls,
+            |c| c.line,
+            lines,
+            |c| {
+                c.line = shift.line(c.line);
+                shift.range(&mut c.range);
+            },
+        ),
+        relations: three(
+            old.relations,
+            fresh.relations,
+            |r| r.line,
+            lines,
+            |r| {
+                r.line = shift.line(r.line);
+                shift.range(&mut r.range);
+            },
+        ),
+        // Counted over the whole new tree
+        syntax_errors: fresh.syntax_errors,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::extract_file_with;
+
+    /// Apply each edit in turn through the cache and check the result
+    /// matches extracting the new source from scratch.
+    fn assert_incremental(path: &str, original: &str, edits: &[(&str, &str)]) {
+        let path = Path::new(path);
+        let lang = SupportedLanguage::from_path(path).unwrap();
+        let query = TagQuery::bundled(lang);
+        let options = ExtractOptions::default();
+        let cache = TreeCache::default();
+
+        let mut source = original.to_string();
+        cache.extract(path, &source, query, &options).unwrap();
+        for (from, to) in edits {
+            assert!(source.contains(from), "{from:?} not in source");
+            source = source.replacen(from, to, 1);
+            let incremental = cache.extract(path, &source, query, &options).unwrap();
+            let full = extract_file_with(path, &source, query, &options).unwrap();
+            assert_eq!(
+                format!("{incremental:?}"),
+                format!("{full:?}"),
+                "after {from:?} -> {to:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_incremental_matches_full_extraction() {
+        let rust = "use std::fmt;\n\n\
+                    /// Adds.\n#[inline]\nfn add(a: u32, b: u32) -> u32 {\n    a + b\n}\n\n\
+                    struct Point {\n    x: u32,\n}\n\n\
+                    impl Point {\n    fn sum(&self) -> u32 {\n        add(self.x, 1)\n    }\n}\n\n\
+                    fn main() { let p = Point { x: 1 }; p.sum(); }\n";
+        assert_incremental(
+            "src/lib.rs",
+            rust,
+            &[
+                // Body edit inside one item
+                ("a + b", "a + b + 1"),
+                // Line added: everything after moves down
+                ("    x: u32,\n", "    x: u32,\n    y: u32,\n"),
+                // Doc comment and attribute of the next item
+                ("/// Adds.", "/// Adds two numbers.\n/// Saturating."),
+                ("#[inline]\n", ""),
+                // New item, then removing one
+                (
+                    "\nstruct Point",
+                    "\nfn helper() -> u32 { add(1, 2) }\n\nstruct Point",
+                ),
+                ("fn main() { let p = Point { x: 1 }; p.sum(); }\n", ""),
+                // Non-ASCII text shifts UTF-16 columns on its own line only
+                ("a + b + 1", "a + b + 1 // é→ü"),
+                // Syntax error, then fixed
+                ("fn helper() -> u32 {", "fn helper( -> u32 {"),
+                ("fn helper( -> u32 {", "fn helper() -> u32 {"),
+            ],
+        );
+
+        let python = "import os\n\nLIMIT = 3\n\n\n@cached\ndef load(path):\n    return os.path.join(path, 'x')\n\n\nclass Store:\n    def save(self):\n        load('a')\n";
+        assert_incremental(
+            "app/store.py",
+            python,
+            &[
+                ("'x'", "'y', LIMIT"),
+                ("@cached\n", "@cached\n@traced\n"),
+                (
+                    "        load('a')\n",
+                    "        load('a')\n        load('b')\n",
+                ),
+            ],
+        );
+
+        // A package qualifies every name: re-extracted whole
+        let java = "package com.acme;\n\nclass A {\n  void run() { go(); }\n}\n\nclass B {}\n";
+        assert_incremental(
+            "A.java",
+            java,
+            &[("go();", "go(); stop();"), ("class B {}", "class C {}")],
+        );
+    }
+
+    /// One sample per language, with several top-level items.
+    const SAMPLES: &[(&str, &str)] = &[
+        (
+            "lib.rs",
+            "use std::fmt;\n\n/// A point.\n#[derive(Debug)]\npub struct Point {\n    x: u32,\n}\n\n\
+             impl Point {\n    fn sum(&self) -> u32 {\n        add(self.x, LIMIT)\n    }\n}\n\n\
+             const LIMIT: u32 = 3;\n\nfn add(a: u32, b: u32) -> u32 {\n    a + b\n}\n",
+        ),
+        (
+            "store.py",
+            "import os\nfrom app import db\n\nLIMIT = 3\n\n\n@cached\ndef load(path):\n    \
+             return os.path.join(path, 'x')\n\n\nclass Store(Base):\n    def save(self):\n        \
+             db.execute(\"SELECT * FROM users WHERE id = %s\", 1)\n        load('a')\n",
+        ),
+        (
+            "app.js",
+            "import { fetch } from './net';\n\nexport const LIMIT = 3;\n\n\
+             class Cart extends Base {\n  total() {\n    return sum(this.items);\n  }\n}\n\n\
+             function sum(items) {\n  return items.reduce((a, b) => a + b, 0);\n}\n",
+        ),
+        (
+            "app.ts",
+            "import { User } from './user';\n\ninterface Named {\n  name: string;\n}\n\n\
+             export class Admin implements Named {\n  name = 'root';\n  greet(u: User): string {\n    \
+             return format(u.name);\n  }\n}\n\nfunction format(s: string): string {\n  return s;\n}\n",
+        ),
+        (
+            "view.tsx",
+            "import React from 'react';\n\ntype Props = { title: string };\n\n\
+             export function Header({ title }: Props) {\n  return <h1>{title}</h1>;\n}\n\n\
+             export const App = () => <Header title=\"x\" />;\n",
+        ),
+        (
+            "store.go",
+            "package store\n\nimport \"fmt\"\n\ntype Store struct {\n\tName string\n}\n\n\
+             func (s *Store) Save() error {\n\treturn fmt.Errorf(\"%s\", s.Name)\n}\n\n\
+             func New() *Store {\n\treturn &Store{Name: \"x\"}\n}\n",
+        ),
+        (
+            "Store.java",
+            "import java.util.List;\n\n@Service\nclass Store extends Base implements Saver {\n  \
+             private int count;\n  void save() { load(); }\n}\n\ninterface Saver {\n  void save();\n}\n",
+        ),
+        (
+            "Store.cs",
+            "using System;\n\nclass Store : Base, ISaver {\n  int count;\n  public void Save() { Load(); }\n}\n\n\
+             interface ISaver {\n  void Save();\n}\n",
+        ),
+        (
+            "store.rb",
+            "require 'json'\n\nmodule Billing\n  LIMIT = 3\nend\n\nclass Store < Base\n  include Billing\n\n  \
+             def save\n    load(LIMIT)\n  end\nend\n\ndef load(n)\n  n\nend\n",
+        ),
+        (
+            "Store.kt",
+            "import kotlin.math.max\n\nconst val LIMIT = 3\n\nclass Store(val name: String) : Base(), Saver {\n    \
+             fun save() { load(LIMIT) }\n}\n\nfun load(n: Int) = max(n, 1)\n",
+        ),
+        (
+            "Store.scala",
+            "import scala.util.Try\n\nval Limit = 3\n\nclass Store(val name: String) extends Base with Saver {\n  \
+             def save(): Unit = load(Limit)\n}\n\nobject Store {\n  def apply(): Store = new Store(\"x\")\n}\n",
+        ),
+        (
+            "store.c",
+            "#include <stdio.h>\n\n#define LIMIT 3\n\nstruct store {\n    int count;\n};\n\n\
+             static int load(int n) {\n    return n;\n}\n\nint save(struct store *s) {\n    return load(s->count);\n}\n",
+        ),
+        (
+            "store.cpp",
+            "#include <vector>\n\nnamespace geo {\nclass Shape {\npublic:\n    double area() const;\n};\n}\n\n\
+             double geo::Shape::area() const {\n    return scale(2.0);\n}\n\nint main() { return 0; }\n",
+        ),
+        (
+            "Store.swift",
+            "import Foundation\n\nprotocol Saver {\n    func save()\n}\n\nclass Store: Base, Saver {\n    \
+             var count = 0\n    func save() { load(count) }\n}\n\nfunc load(_ n: Int) {}\n",
+        ),
+        (
+            "Store.php",
+            "<?php\nnamespace App;\n\nuse App\\Base;\n\nclass Store extends Base {\n    \
+             public function save() { $this->load(); }\n}\n\nfunction helper() { return 1; }\n",
+        ),
+        (
+            "deploy.sh",
+            "#!/bin/bash\n\nLIMIT=3\n\nbuild() {\n  make all\n}\n\ndeploy() {\n  build\n  echo \"$LIMIT\"\n}\n\ndeploy\n",
+        ),
+        (
+            "store.ex",
+            "defmodule App.Store do\n  alias App.Repo\n\n  def save(x) do\n    Repo.insert(x)\n  end\n\n  \
+             defp load(n), do: n\nend\n\ndefmodule App.Other do\n  def run, do: App.Store.save(1)\nend\n",
+        ),
+        (
+            "store.lua",
+            "local json = require(\"json\")\n\nStore = {}\n\nfunction Store.new(name)\n  \
+             return setmetatable({ name = name }, Store)\nend\n\nfunction Store:save()\n  \
+             return json.encode(self)\nend\n\nLIMIT = 3\n",
+        ),
+        (
+            "schema.sql",
+            "-- Teams\nCREATE TABLE teams (id INT PRIMARY KEY);\n\n\
+             CREATE TABLE users (\n  id INT,\n  team_id INT REFERENCES teams(id)\n);\n\n\
+             CREATE INDEX users_team ON users (team_id);\n\nCREATE VIEW team_sizes AS SELECT team_id FROM users;\n",
+        ),
+        (
+            "users.proto",
+            "syntax = \"proto3\";\n\nimport \"common.proto\";\n\nmessage User {\n  string id = 1;\n  \
+             Team team = 2;\n}\n\nservice UserService {\n  rpc GetUser(User) returns (User);\n}\n\n\
+             enum Role {\n  ROLE_UNSPECIFIED = 0;\n}\n",
+        ),
+        (
+            "schema.graphql",
+            "interface Node {\n  id: ID!\n}\n\n\"A post.\"\ntype Post implements Node {\n  id: ID!\n  \
+             author(id: ID): User\n}\n\ntype Query {\n  post(id: ID!): Post\n}\n\n\
+             query Load {\n  post(id: 1) { ...Fields }\n}\n",
+        ),
+    ];
+
+    /// Snippets random edits insert: brackets, separators and keywords
+    /// that open or close items.
+    const INSERTS: &[&str] = &[
+        "\n",
+        "\n\n",
+        "{",
+        "}",
+        "(",
+        ")",
+        ";",
+        " ",
+        "x",
+        "\"",
+        "'",
+        "#",
+        "//",
+        "/*",
+        "*/",
+        "--",
+        "end\n",
+        "fn ",
+        "class ",
+        "def ",
+        "function ",
+        "=",
+        ".",
+        ":",
+        ",",
+        "<",
+        ">",
+        "\"SELECT * FROM users\"",
+    ];
+
+    /// xorshift64: deterministic without a dependency.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, n: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % n as u64) as usize
+        }
+    }
+
+    /// Delete, insert, duplicate or replace a random span (samples are
+    /// ASCII, so every offset is a char boundary).
+    fn random_edit(rng: &mut Rng, source: &str) -> String {
+        let at = rng.below(source.len() + 1);
+        let len = rng.below(17).min(source.len() - at);
+        let insert = INSERTS[rng.below(INSERTS.len())];
+        match rng.below(4) {
+            0 => format!("{}{}", &source[..at], &source[at + len..]),
+            1 => format!("{}{}{}", &source[..at], insert, &source[at..]),
+            2 => {
+                let from = rng.below(source.len());
+                let copied = &source[from..(from + rng.below(41)).min(source.len())];
+                format!("{}{}{}", &source[..at], copied, &source[at..])
+            }
+            _ => format!("{}{}{}", &source[..at], insert, &source[at + len..]),
+        }
+    }
+
+    #[test]
+    fn test_random_edits_match_full_extraction() {
+        let options = ExtractOptions::default();
+        // One thread per language: parsers are per thread anyway
+        std::thread::scope(|scope| {
+            for &(name, original) in SAMPLES {
+                scope.spawn(move || {
+                    let path = Path::new(name);
+                    let query = TagQuery::bundled(SupportedLanguage::from_path(path).unwrap());
+                    for seed in 1..=300u64 {
+                        let mut rng = Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15));
+                        let cache = TreeCache::default();
+                        let mut source = original.to_string();
+                        cache.extract(path, &source, query, &options).unwrap();
+                        for edit in 0..6 {
+                            source = random_edit(&mut rng, &source);
+                            let incremental =
+                                cache.extract(path, &source, query, &options).unwrap();
+                            let full = extract_file_with(path, &source, query, &options).unwrap();
+                            assert_eq!(
+                                format!("{incremental:?}"),
+                                format!("{full:?}"),
+                                "{name}, seed {seed}, edit {edit}:\n{source}"
+                            );
+                        }
+                    }
+                });
+            }
+        });
+    }
+
+    #[test]
+    fn test_tree_cache_is_bounded_and_tracks_queries() {
+        let cache = TreeCache::new(2);
+        let query = TagQuery::bundled(SupportedLanguage::Rust);
+        let options = ExtractOptions::default();
+        // Extracting `a.rs` again makes `b.rs` the least recently used
+        for name in ["a.rs", "b.rs", "a.rs", "c.rs"] {
+            cache
+                .extract(Path::new(name), "fn f() {}\n", query, &options)
+                .unwrap();
+        }
+        assert!(cache.contains(Path::new("a.rs")));
+        assert!(!cache.contains(Path::new("b.rs")));
+        assert!(cache.contains(Path::new("c.rs")));
+
+        // Same path, different language: extracted from scratch
+        let python = TagQuery::bundled(SupportedLanguage::Python);
+        let extraction = cache
+            .extract(Path::new("c.rs"), "def g():\n    pass\n", python, &options)
+            .unwrap();
+        assert_eq!(extraction.symbols[0].name, "g");
+
+        cache.forget(Path::new("c.rs"));
+        assert!(!cache.contains(Path::new("c.rs")));
+
+        // Same source, but other options or a recompiled query: not reused
+        let path = Path::new("d.rs");
+        let source = "fn f() {\n    g();\n}\n";
+        cache.extract(path, source, query, &options).unwrap();
+        let short = ExtractOptions {
+            max_snippet_lines: 1,
+        };
+        let extraction = cache.extract(path, source, query, &short).unwrap();
+        assert_eq!(extraction.symbols[0].code_snippet.lines().count(), 2);
+        let calls_only = TagQuery::new(
+            SupportedLanguage::Rust,
+            "(call_expression function: (identifier) @call.name) @call",
+        )
+        .unwrap();
+        let extraction = cache.extract(path, source, &calls_only, &short).unwrap();
+        assert!(extraction.symbols.is_empty());
+    }
+}
@@ -16,11 +16,102 @@ pub enum SupportedLanguage {
     Java,
     CSharp,
     Ruby,
+    Kotlin,
+    Scala,
+    C,
     Cpp,
     Swift,
+    Php,
+    Bash,
+    Elixir,
+    Lua,
+    Sql,
+    Protobuf,
+    GraphQl,
 }
 
 impl SupportedLanguage {
+    /// Every supported language, in display order.
+    pub const ALL: &'static [SupportedLanguage] = &[
+        SupportedLanguage::Rust,
+        SupportedLanguage::Python,
+        SupportedLanguage::JavaScript,
+        SupportedLanguage::TypeScript,
+        SupportedLanguage::Tsx,
+        SupportedLanguage::Go,
+        SupportedLanguage::Java,
+        SupportedLanguage::CSharp,
+        SupportedLanguage::Ruby,
+        SupportedLanguage::Kotlin,
+        SupportedLanguage::Scala,
+        SupportedLanguage::C,
+        SupportedLanguage::Cpp,
+        SupportedLanguage::Swift,
+        SupportedLanguage::Php,
+        SupportedLanguage::Bash,
+        SupportedLanguage::Elixir,
+        SupportedLanguage::Lua,
+        SupportedLanguage::Sql,
+        SupportedLanguage::Protobuf,
+        SupportedLanguage::GraphQl,
+    ];
+
+    /// Parse a language name as written in config files
+    /// (case-insensitive; accepts common aliases like "ts" or "c++").
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name.to_ascii_lowercase().as_str() {
+            "rust" | "rs" => Some(SupportedLanguage::Rust),
+            "python" | "py" => Some(SupportedLanguage::Python),
+            "javascript" | "js" => Some(SupportedLanguage::JavaScript),
+            "typescript" | "ts" => Some(SupportedLanguage::TypeScript),
+            "tsx" | "jsx" => Some(SupportedLanguage::Tsx),
+            "go" | "golang" => Some(SupportedLanguage::Go),
+            "java" => Some(SupportedLanguage::Java),
+            "csharp" | "c#" | "cs" => Some(SupportedLanguage::CSharp),
+            "ruby" | "rb" => Some(SupportedLanguage::Ruby),
+            "kotlin" | "kt" => Some(SupportedLanguage::Kotlin),
+            "scala" => Some(SupportedLanguage::Scala),
+            "c" => Some(SupportedLanguage::C),
+            "cpp" | "c++" => Some(SupportedLanguage::Cpp),
+            "swift" => Some(SupportedLanguage::Swift),
+            "php" => Some(SupportedLanguage::Php),
+            "bash" | "sh" | "shell" => Some(SupportedLanguage::Bash),
+            "elixir" | "ex" => Some(SupportedLanguage::Elixir),
+            "lua" => Some(SupportedLanguage::Lua),
+            "sql" => Some(SupportedLanguage::Sql),
+            "protobuf" | "proto" => Some(SupportedLanguage::Protobuf),
+            "graphql" | "gql" => Some(SupportedLanguage::GraphQl),
+            _ => None,
+        }
+    }
+
+    /// Canonical config name (the inverse of `from_name`).
+    pub fn config_name(&self) -> &'static str {
+        match self {
+            SupportedLanguage::Rust => "rust",
+            SupportedLanguage::Python => "python",
+            SupportedLanguage::JavaScript => "javascript",
+            SupportedLanguage::TypeScript => "typescript",
+            SupportedLanguage::Tsx => "tsx",
+            SupportedLanguage::Go => "go",
+            SupportedLanguage::Java => "java",
+            SupportedLanguage::CSharp => "csharp",
+            SupportedLanguage::Ruby => "ruby",
+            SupportedLanguage::Kotlin => "kotlin",
+            SupportedLanguage::Scala => "scala",
+            SupportedLanguage::C => "c",
+            SupportedLanguage::Cpp => "cpp",
+            SupportedLanguage::Swift => "swift",
+            SupportedLanguage::Php => "php",
+            SupportedLanguage::Bash => "bash",
+            SupportedLanguage::Elixir => "elixir",
+            SupportedLanguage::Lua => "lua",
+            SupportedLanguage::Sql => "sql",
+            SupportedLanguage::Protobuf => "protobuf",
+            SupportedLanguage::GraphQl => "graphql",
+        }
+    }
+
     /// Detect language from file extension.
     pub fn from_path(path: &Path) -> Option<Self> {
         let ext = path.extension()?.to_str()?;
@@ -34,13 +125,35 @@ impl SupportedLanguage {
             "java" => Some(SupportedLanguage::Java),
             "cs" => Some(SupportedLanguage::CSharp),
             "rb" => Some(SupportedLanguage::Ruby),
-            // "kt" | "kts" => Some(SupportedLanguage::Kotlin),  // Disabled: tree-sitter version conflict
-            "cpp" | "cc" | "cxx" | "hpp" | "h" => Some(SupportedLanguage::Cpp),
+            "kt" | "kts" => Some(SupportedLanguage::Kotlin),
+            "scala" | "sc" => Some(SupportedLanguage::Scala),
+            "c" | "h" => Some(SupportedLanguage::C),
+            "cpp" | "cc" | "cxx" | "c++" | "hpp" | "hh" | "hxx" | "ipp" | "tpp" => {
+                Some(SupportedLanguage::Cpp)
+            }
             "swift" => Some(SupportedLanguage::Swift),
+            "php" | "phtml" => Some(SupportedLanguage::Php),
+            "sh" | "bash" => Some(SupportedLanguage::Bash),
+            "ex" | "exs" => Some(SupportedLanguage::Elixir),
+            "lua" => Some(SupportedLanguage::Lua),
+            "sql" => Some(SupportedLanguage::Sql),
+            "proto" => Some(SupportedLanguage::Protobuf),
+            "graphql" | "graphqls" | "gql" => Some(SupportedLanguage::GraphQl),
             _ => None,
         }
     }
 
+    /// `.h` headers are shared by C and C++: a header detected as C is
+    /// parsed as C++ when its source uses C++-only syntax.
+    pub fn refine_for_source(self, path: &Path, source: &str) -> Self {
+        let is_header = path.extension().is_some_and(|ext| ext == "h");
+        if self == SupportedLanguage::C && is_header && looks_like_cpp(source) {
+            SupportedLanguage::Cpp
+        } else {
+            self
+        }
+    }
+
     /// Get the tree-sitter Language for this language.
     pub fn tree_sitter_language(&self) -> Language {
         match self {
@@ -53,8 +166,18 @@ impl SupportedLanguage {
             SupportedLanguage::Java => tree_sitter_java::LANGUAGE.into(),
             SupportedLanguage::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
             SupportedLanguage::Ruby => tree_sitter_ruby::LANGUAGE.into(),
+            SupportedLanguage::Kotlin => tree_sitter_kotlin_ng::LANGUAGE.into(),
+            SupportedLanguage::Scala => tree_sitter_scala::LANGUAGE.into(),
+            SupportedLanguage::C => tree_sitter_c::LANGUAGE.into(),
             SupportedLanguage::Cpp => tree_sitter_cpp::LANGUAGE.into(),
             SupportedLanguage::Swift => tree_sitter_swift::LANGUAGE.into(),
+            SupportedLanguage::Php => tree_sitter_php::LANGUAGE_PHP.into(),
+            SupportedLanguage::Bash => tree_sitter_bash::LANGUAGE.into(),
+            SupportedLanguage::Elixir => tree_sitter_elixir::LANGUAGE.into(),
+            SupportedLanguage::Lua => tree_sitter_lua::LANGUAGE.into(),
+            SupportedLanguage::Sql => tree_sitter_sequel::LANGUAGE.into(),
+            SupportedLanguage::Protobuf => tree_sitter_proto::LANGUAGE.into(),
+            SupportedLanguage::GraphQl => tree_sitter_graphql::LANGUAGE.into(),
         }
     }
 
@@ -70,8 +193,18 @@ impl SupportedLanguage {
             SupportedLanguage::Java => "Java",
             SupportedLanguage::CSharp => "C#",
             SupportedLanguage::Ruby => "Ruby",
+            SupportedLanguage::Kotlin => "Kotlin",
+            SupportedLanguage::Scala => "Scala",
+            SupportedLanguage::C => "C",
             SupportedLanguage::Cpp => "C++",
             SupportedLanguage::Swift => "Swift",
+            SupportedLanguage::Php => "PHP",
+            SupportedLanguage::Bash => "Bash",
+            SupportedLanguage::Elixir => "Elixir",
+            SupportedLanguage::Lua => "Lua",
+            SupportedLanguage::Sql => "SQL",
+            SupportedLanguage::Protobuf => "Protocol Buffers",
+            SupportedLanguage::GraphQl => "GraphQL",
         }
     }
 
@@ -89,8 +222,11 @@ impl SupportedLanguage {
             (SupportedLanguage::Tsx, SupportedLanguage::TypeScript) => true,
             (SupportedLanguage::Tsx, SupportedLanguage::Tsx) => true,
 
-            // JVM ecosystem
-            (SupportedLanguage::Java, SupportedLanguage::Java) => true,
+            // JVM ecosystem (Java, Kotlin and Scala call each other directly)
+            (
+                SupportedLanguage::Java | SupportedLanguage::Kotlin | SupportedLanguage::Scala,
+                SupportedLanguage::Java | SupportedLanguage::Kotlin | SupportedLanguage::Scala,
+            ) => true,
 
             // Each other language is its own ecosystem
             (SupportedLanguage::Python, SupportedLanguage::Python) => true,
@@ -98,11 +234,59 @@ impl SupportedLanguage {
             (SupportedLanguage::Go, SupportedLanguage::Go) => true,
             (SupportedLanguage::CSharp, SupportedLanguage::CSharp) => true,
             (SupportedLanguage::Ruby, SupportedLanguage::Ruby) => true,
-            (SupportedLanguage::Cpp, SupportedLanguage::Cpp) => true,
+            // C and C++ share headers and link against each other
+            (
+                SupportedLanguage::C | SupportedLanguage::Cpp,
+                SupportedLanguage::C | SupportedLanguage::Cpp,
+            ) => true,
             (SupportedLanguage::Swift, SupportedLanguage::Swift) => true,
+            (SupportedLanguage::Php, SupportedLanguage::Php) => true,
+            (SupportedLanguage::Bash, SupportedLanguage::Bash) => true,
+            (SupportedLanguage::Elixir, SupportedLanguage::Elixir) => true,
+            (SupportedLanguage::Lua, SupportedLanguage::Lua) => true,
+            (SupportedLanguage::Sql, SupportedLanguage::Sql) => true,
+            (SupportedLanguage::Protobuf, SupportedLanguage::Protobuf) => true,
+            (SupportedLanguage::GraphQl, SupportedLanguage::GraphQl) => true,
 
             // Cross-ecosystem: no direct calls possible (but APIs can connect them!)
             _ => false,
         }
     }
 }
+
+/// Whether C-family source uses syntax C doesn't have (classes,
+/// namespaces, templates, access specifiers, `::`). Preprocessor lines and
+/// comments are skipped, so `#ifdef __cplusplus` guards don't count.
+fn looks_like_cpp(source: &str) -> bool {
+    source.lines().map(str::trim_start).any(|line| {
+        if line.starts_with(['#', '*']) || line.starts_with("//") || line.starts_with("/*") {
+            return false;
+        }
+        let first = line
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .next()
+            .unwrap_or("");
+        matches!(first, "class" | "namespace" | "template" | "using")
+            || ["public:", "private:", "protected:"].contains(&line.trim_end())
+            || line.contains("::")
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_headers_switch_to_cpp_on_cpp_syntax() {
+        let header = Path::new("include/point.h");
+        let c = "#ifdef __cplusplus\nextern \"C\" {\n#endif\nstruct point { int x; };\n";
+        let cpp = "namespace geo {\nclass Point {\npublic:\n    int x;\n};\n}\n";
+
+        let lang = SupportedLanguage::from_path(header).unwrap();
+        assert_eq!(lang, SupportedLanguage::C);
+        assert_eq!(lang.refine_for_source(header, c), SupportedLanguage::C);
+        assert_eq!(lang.refine_for_source(header, cpp), SupportedLanguage::Cpp);
+        let source = Path::new("src/point.c");
+        assert_eq!(lang.refine_for_source(source, cpp), SupportedLanguage::C);
+    }
+}
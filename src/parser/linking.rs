@@ -0,0 +1,1323 @@
+//! Per-language rules for linking extracted relations across files.
+//!
+//! Relations are extracted per file (see `tags`) and resolved against the
+//! whole graph by `CodeGraph`. Where languages disagree on what a relation
+//! may point at, the difference is stated here rather than in the graph.
+
+use std::path::Path;
+
+use super::language::SupportedLanguage;
+use crate::graph::types::{
+    EdgeKind, ExtractedRelation, NodeData, NodeKind, ReferenceKind, RelationTarget,
+};
+
+/// How a language's relations pick their targets.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LinkRules {
+    /// Modules can be mixed in and can contain types (Ruby, Elixir).
+    pub module_targets: bool,
+    /// A type's members may be declared in another file, but only of the
+    /// same package (Go method receivers).
+    pub same_package_members: bool,
+    /// A symbol relating to its own name is a declaration looking for its
+    /// definition (C and C++ prototypes).
+    pub declarations: bool,
+    /// Types satisfy interfaces by their method sets (Go).
+    pub implicit_interfaces: bool,
+}
+
+impl LinkRules {
+    /// Rules for a language.
+    pub fn for_language(language: SupportedLanguage) -> Self {
+        match language {
+            SupportedLanguage::Ruby | SupportedLanguage::Elixir => Self {
+                module_targets: true,
+                ..Self::default()
+            },
+            SupportedLanguage::Go => Self {
+                same_package_members: true,
+                implicit_interfaces: true,
+                ..Self::default()
+            },
+            SupportedLanguage::C | SupportedLanguage::Cpp => Self {
+                declarations: true,
+                ..Self::default()
+            },
+            _ => Self::default(),
+        }
+    }
+
+    /// Rules for the language of a file, the defaults when it has none.
+    pub fn for_path(path: &Path) -> Self {
+        SupportedLanguage::from_path(path)
+            .map(Self::for_language)
+            .unwrap_or_default()
+    }
+
+    /// Whether `relation`, made from `source`, can point at `target`.
+    pub fn accepts(
+        &self,
+        relation: &ExtractedRelation,
+        source: &NodeData,
+        target: &NodeData,
+    ) -> bool {
+        let same_dir = target.file_path.parent() == source.file_path.parent();
+        // Reads and writes only reach constants, and variables and fields
+        // of the same package: elsewhere a name like `count` is as likely
+        // a local
+        let usage = match relation.reference {
+            None => true,
+            Some(ReferenceKind::Read | ReferenceKind::Write) => {
+                target.kind == NodeKind::Constant || (target.kind == NodeKind::Variable && same_dir)
+            }
+            Some(_) => is_type_kind(target.kind),
+        };
+        let wants_type = matches!(
+            relation.kind,
+            EdgeKind::Contains | EdgeKind::Implements | EdgeKind::Extends
+        );
+        let type_target = is_type_kind(target.kind)
+            || (self.module_targets
+                && target.kind == NodeKind::Module
+                && relation.kind != EdgeKind::Extends);
+        let member_elsewhere =
+            self.same_package_members && relation.kind == EdgeKind::Contains && !same_dir;
+        // Contained by its own name is an extension (Swift), not a
+        // declaration
+        let declaration = self.declarations
+            && matches!(&relation.target, RelationTarget::Symbol(name) if *name == relation.source)
+            && relation.kind != EdgeKind::Contains
+            && relation.reference.is_none();
+        target.kind != NodeKind::Import
+            && usage
+            && (!wants_type || type_target)
+            && !member_elsewhere
+            && (!declaration || is_same_declaration(source, target))
+    }
+}
+
+/// Kinds a `Contains`, `Implements` or `Extends` relation can point at.
+pub fn is_type_kind(kind: NodeKind) -> bool {
+    matches!(
+        kind,
+        NodeKind::Class
+            | NodeKind::Struct
+            | NodeKind::Enum
+            | NodeKind::Interface
+            | NodeKind::Trait
+            | NodeKind::Type
+    )
+}
+
+/// Whether `other` declares or defines the same function as `declaration`:
+/// the same kind of symbol, with one qualified name a `.`/`#`-separated
+/// suffix of the other (`Shape#area` and `geo.Shape#area`).
+fn is_same_declaration(declaration: &NodeData, other: &NodeData) -> bool {
+    let callable = |kind| matches!(kind, NodeKind::Function | NodeKind::Method);
+    let same_kind =
+        declaration.kind == other.kind || (callable(declaration.kind) && callable(other.kind));
+    let (Some(a), Some(b)) = (&declaration.qualified_name, &other.qualified_name) else {
+        return same_kind;
+    };
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    same_kind
+        && long
+            .strip_suffix(short.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.ends_with(['.', '#']))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::test_support::{assert_matches_fresh_build, has_edge, snapshot};
+    use crate::graph::types::SourceRange;
+    use crate::graph::{build_graph, project_filter, rebuild_file};
+    use crate::parser::TreeCache;
+    use std::fs;
+    use std::path::PathBuf;
+    use tempfile::tempdir;
+
+    fn node(path: &str, kind: NodeKind, name: &str) -> NodeData {
+        NodeData::new_symbol(
+            name.to_string(),
+            kind,
+            PathBuf::from(path),
+            1,
+            1,
+            String::new(),
+        )
+    }
+
+    fn relation(kind: EdgeKind, source: &str, target: &str) -> ExtractedRelation {
+        ExtractedRelation {
+            kind,
+            source: source.to_string(),
+            target: RelationTarget::Symbol(target.to_string()),
+            line: 1,
+            range: SourceRange::default(),
+            reference: None,
+        }
+    }
+
+    #[test]
+    fn test_modules_are_supertypes_only_where_mixed_in() {
+        let include = relation(EdgeKind::Implements, "User", "Payable");
+        let ruby = LinkRules::for_path(Path::new("user.rb"));
+        let rust = LinkRules::for_path(Path::new("user.rs"));
+        let user = node("user.rb", NodeKind::Class, "User");
+        let payable = node("payable.rb", NodeKind::Module, "Payable");
+        assert!(ruby.accepts(&include, &user, &payable));
+        assert!(!rust.accepts(&include, &user, &payable));
+        // Extending still takes a class
+        assert!(!ruby.accepts(
+            &relation(EdgeKind::Extends, "User", "Payable"),
+            &user,
+            &payable
+        ));
+    }
+
+    #[test]
+    fn test_go_members_stay_in_their_package() {
+        let go = LinkRules::for_path(Path::new("store/methods.go"));
+        let contains = relation(EdgeKind::Contains, "Save", "Store");
+        let save = node("store/methods.go", NodeKind::Method, "Save");
+        assert!(go.accepts(
+            &contains,
+            &save,
+            &node("store/types.go", NodeKind::Struct, "Store")
+        ));
+        assert!(!go.accepts(
+            &contains,
+            &save,
+            &node("other/types.go", NodeKind::Struct, "Store")
+        ));
+    }
+
+    #[test]
+    fn test_prototypes_find_their_definitions() {
+        let c = LinkRules::for_path(Path::new("shape.h"));
+        let prototype = relation(EdgeKind::References, "area", "area");
+        let mut declaration = node("shape.h", NodeKind::Function, "area");
+        declaration.qualified_name = Some("Shape#area".to_string());
+        let mut definition = node("shape.cpp", NodeKind::Method, "area");
+        definition.qualified_name = Some("geo.Shape#area".to_string());
+        assert!(c.accepts(&prototype, &declaration, &definition));
+        definition.qualified_name = Some("geo.Circle#area".to_string());
+        assert!(!c.accepts(&prototype, &declaration, &definition));
+    }
+
+    #[test]
+    fn test_go_receivers_interfaces_and_imports_link_across_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("store")).unwrap();
+        fs::write(root.join("go.mod"), "module example.com/app\n\ngo 1.22\n").unwrap();
+        fs::write(
+            root.join("store/types.go"),
+            "package store\n\ntype Store struct {\n\tpath string\n}\n\n\
+             type Saver interface {\n\tSave() error\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("store/methods.go"),
+            "package store\n\nfunc (s *Store) Save() error {\n\treturn nil\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("main.go"),
+            "package main\n\nimport \"example.com/app/store\"\n\nfunc main() {}\n",
+        )
+        .unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        assert!(has_edge(&graph, "Store", "Save", EdgeKind::Contains));
+        assert!(has_edge(&graph, "Store", "Saver", EdgeKind::Implements));
+        let imported: Vec<_> = snapshot(&graph)
+            .1
+            .into_iter()
+            .filter(|(a, _, k)| a.2 == "example.com/app/store" && *k == EdgeKind::Imports)
+            .map(|(_, b, _)| b.0)
+            .collect();
+        assert_eq!(
+            imported,
+            vec![root.join("store/methods.go"), root.join("store/types.go")]
+        );
+
+        // Dropping the method breaks the implicit implementation
+        fs::write(root.join("store/methods.go"), "package store\n").unwrap();
+        rebuild_file(&mut graph, &root.join("store/methods.go"), &filter, &trees).unwrap();
+        assert!(!has_edge(&graph, "Store", "Saver", EdgeKind::Implements));
+        assert_matches_fresh_build(&graph, root);
+
+        // Restoring it relinks to the receiver declared in the other file
+        fs::write(
+            root.join("store/methods.go"),
+            "package store\n\nfunc (s Store) Save() error {\n\treturn nil\n}\n",
+        )
+        .unwrap();
+        rebuild_file(&mut graph, &root.join("store/methods.go"), &filter, &trees).unwrap();
+        assert!(has_edge(&graph, "Store", "Saver", EdgeKind::Implements));
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_java_and_csharp_supertypes_and_qualified_names() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("BaseService.java"),
+            "package com.acme;\n\npublic abstract class BaseService {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("UserService.java"),
+            "package com.acme.users;\n\n\
+             public class UserService extends BaseService implements Finder<User> {\n\
+             \x20   public UserService() {}\n\
+             \x20   @Override\n\
+             \x20   public User findById(long id) { return null; }\n\
+             }\n\n\
+             interface Finder<T> { T findById(long id); }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Greeter.cs"),
+            "namespace Acme.Greeting\n{\n\
+             \x20   public class Greeter : Base, IGreeter { public void Greet() {} }\n\
+             \x20   public class Base {}\n\
+             \x20   public interface IGreeter { void Greet(); }\n\
+             }\n",
+        )
+        .unwrap();
+
+        let graph = build_graph(root).unwrap();
+        let edges = snapshot(&graph).1;
+        assert!(has_edge(
+            &graph,
+            "UserService",
+            "BaseService",
+            EdgeKind::Extends
+        ));
+        assert!(has_edge(
+            &graph,
+            "UserService",
+            "Finder",
+            EdgeKind::Implements
+        ));
+        assert!(has_edge(&graph, "Greeter", "Base", EdgeKind::Extends));
+        assert!(has_edge(
+            &graph,
+            "Greeter",
+            "IGreeter",
+            EdgeKind::Implements
+        ));
+
+        // The constructor is a member of the class, not its stand-in
+        let ctor = edges
+            .iter()
+            .find(|(_, b, k)| {
+                b.1 == NodeKind::Method && b.2 == "UserService" && *k == EdgeKind::Contains
+            })
+            .expect("constructor is contained");
+        assert_eq!(ctor.0 .1, NodeKind::Class);
+        assert!(has_edge(
+            &graph,
+            "UserService",
+            "findById",
+            EdgeKind::Contains
+        ));
+
+        assert_eq!(
+            graph.search("com.acme.users.UserService#findById", 5).len(),
+            1
+        );
+        assert_eq!(graph.search("UserService#findById", 5).len(), 1);
+        assert_eq!(
+            graph.search("Greeting.Greeter#Greet", 5)[0].file,
+            root.join("Greeter.cs")
+        );
+    }
+
+    #[test]
+    fn test_kotlin_supertypes_and_extensions_link_across_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("Shapes.kt"),
+            "package geo\n\nopen class Shape\n\ninterface Drawable {\n    fun draw()\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Circle.kt"),
+            "package geo\n\nclass Circle : Shape(), Drawable {\n    override fun draw() {\n        \
+             render(this)\n    }\n}\n\nfun Circle.area(): Double = 0.0\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Render.java"),
+            "package geo;\n\nclass Render {\n    static void render(Object o) {}\n}\n",
+        )
+        .unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let shapes = root.join("Shapes.kt");
+        let circle = root.join("Circle.kt");
+        assert!(has_edge(
+            &graph,
+            (&circle, "Circle"),
+            (&shapes, "Shape"),
+            EdgeKind::Extends
+        ));
+        assert!(has_edge(
+            &graph,
+            (&circle, "Circle"),
+            (&shapes, "Drawable"),
+            EdgeKind::Implements
+        ));
+        assert!(has_edge(
+            &graph,
+            (&circle, "Circle"),
+            (&circle, "area"),
+            EdgeKind::Contains
+        ));
+        // Kotlin calls into Java
+        assert!(has_edge(
+            &graph,
+            (&circle, "draw"),
+            (&root.join("Render.java"), "render"),
+            EdgeKind::Calls
+        ));
+        assert_eq!(graph.search("geo.Circle#area", 5).len(), 1);
+
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        fs::write(&shapes, "package geo\n\nopen class Shape\n").unwrap();
+        rebuild_file(&mut graph, &shapes, &filter, &trees).unwrap();
+        assert!(!has_edge(
+            &graph,
+            (&circle, "Circle"),
+            (&shapes, "Drawable"),
+            EdgeKind::Implements
+        ));
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_scala_and_lua_link_across_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("geo")).unwrap();
+        fs::write(
+            root.join("Shapes.scala"),
+            "package geo\n\ntrait Shape {\n  def area(): Double\n}\n\nabstract class Base\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Circle.scala"),
+            "package geo\n\nclass Circle(r: Double) extends Base with Shape {\n  \
+             def area(): Double = Render.scale(r)\n}\n\n\
+             extension (c: Circle) def diameter: Double = c.area() * 2\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Render.java"),
+            "package geo;\n\nclass Render {\n    static double scale(double r) { return r; }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("geo/point.lua"),
+            "Point = {}\n\nfunction Point.new(x, y)\n  return setmetatable({x = x, y = y}, Point)\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("main.lua"),
+            "local point = require(\"geo.point\")\n\nlocal function start()\n  return Point.new(1, 2)\nend\n",
+        )
+        .unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let shapes = root.join("Shapes.scala");
+        let circle = root.join("Circle.scala");
+        let point = root.join("geo/point.lua");
+        let main = root.join("main.lua");
+        assert!(has_edge(
+            &graph,
+            (&circle, "Circle"),
+            (&shapes, "Base"),
+            EdgeKind::Extends
+        ));
+        assert!(has_edge(
+            &graph,
+            (&circle, "Circle"),
+            (&shapes, "Shape"),
+            EdgeKind::Implements
+        ));
+        assert!(has_edge(
+            &graph,
+            (&circle, "Circle"),
+            (&circle, "diameter"),
+            EdgeKind::Contains
+        ));
+        // Scala calls into Java
+        assert!(has_edge(
+            &graph,
+            (&circle, "area"),
+            (&root.join("Render.java"), "scale"),
+            EdgeKind::Calls
+        ));
+        assert_eq!(graph.search("geo.Circle#diameter", 5).len(), 1);
+
+        assert!(has_edge(
+            &graph,
+            (&main, "geo.point"),
+            (&point, "point.lua"),
+            EdgeKind::Imports
+        ));
+        assert!(has_edge(
+            &graph,
+            (&point, "Point"),
+            (&point, "new"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&main, "start"),
+            (&point, "new"),
+            EdgeKind::Calls
+        ));
+
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        fs::write(&shapes, "package geo\n\nabstract class Base\n").unwrap();
+        rebuild_file(&mut graph, &shapes, &filter, &trees).unwrap();
+        assert!(!has_edge(
+            &graph,
+            (&circle, "Circle"),
+            (&shapes, "Shape"),
+            EdgeKind::Implements
+        ));
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_schemas_link_to_each_other_and_to_code() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("migrations")).unwrap();
+        fs::create_dir_all(root.join("proto/acme")).unwrap();
+        fs::write(root.join("proto/buf.yaml"), "version: v1\n").unwrap();
+        fs::write(
+            root.join("migrations/001_teams.sql"),
+            "CREATE TABLE teams (id INT PRIMARY KEY);\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("migrations/002_users.sql"),
+            "CREATE TABLE users (id INT, team_id INT REFERENCES teams(id));\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("proto/acme/common.proto"),
+            "syntax = \"proto3\";\npackage acme;\n\nmessage Team { string id = 1; }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("proto/acme/users.proto"),
+            "syntax = \"proto3\";\npackage acme;\nimport \"acme/common.proto\";\n\n\
+             message User { Team team = 1; }\n\n\
+             service UserService {\n  rpc GetUser(User) returns (User);\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("client.go"),
+            "package main\n\nfunc load(c UserServiceClient) *User {\n\treturn c.GetUser(nil)\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("schema.graphql"),
+            "interface Node { id: ID! }\ntype Post implements Node { id: ID! }\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("post.ts"),
+            "export function render(post: Post): string {\n  return post.id;\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("queries.py"),
+            "def members(db, team):\n    return db.execute(\"SELECT * FROM users WHERE team_id = %s\", team)\n",
+        )
+        .unwrap();
+
+        let graph = build_graph(root).unwrap();
+        let teams = root.join("migrations/001_teams.sql");
+        let users = root.join("migrations/002_users.sql");
+        let common = root.join("proto/acme/common.proto");
+        let protos = root.join("proto/acme/users.proto");
+        let client = root.join("client.go");
+        let schema = root.join("schema.graphql");
+        assert!(has_edge(
+            &graph,
+            (&users, "users"),
+            (&teams, "teams"),
+            EdgeKind::References
+        ));
+        assert!(has_edge(
+            &graph,
+            (&protos, "acme/common.proto"),
+            (&common, "common.proto"),
+            EdgeKind::Imports
+        ));
+        assert!(has_edge(
+            &graph,
+            (&protos, "User"),
+            (&common, "Team"),
+            EdgeKind::UsesType
+        ));
+        assert_eq!(graph.search("acme.UserService#GetUser", 5).len(), 1);
+        // Code using generated stubs links to the schema
+        assert!(has_edge(
+            &graph,
+            (&client, "load"),
+            (&protos, "GetUser"),
+            EdgeKind::Calls
+        ));
+        assert!(has_edge(
+            &graph,
+            (&client, "load"),
+            (&protos, "User"),
+            EdgeKind::UsesType
+        ));
+        assert!(has_edge(
+            &graph,
+            (&schema, "Post"),
+            (&schema, "Node"),
+            EdgeKind::Implements
+        ));
+        assert!(has_edge(
+            &graph,
+            (&root.join("post.ts"), "render"),
+            (&schema, "Post"),
+            EdgeKind::UsesType
+        ));
+        // And so do the queries in its strings
+        assert!(has_edge(
+            &graph,
+            (&root.join("queries.py"), "members"),
+            (&users, "users"),
+            EdgeKind::UsesType
+        ));
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_cpp_headers_pair_with_out_of_line_definitions() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("include")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("include/shape.h"),
+            "#pragma once\nnamespace geo {\nclass Shape {\npublic:\n    double area() const;\n};\n\
+             int distance(int a, int b);\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/shape.cpp"),
+            "#include \"shape.h\"\n\nnamespace geo {\n\
+             double Shape::area() const { return 0; }\n\
+             int distance(int a, int b) { return a - b; }\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("compile_commands.json"),
+            serde_json::json!([{
+                "directory": root,
+                "file": "src/shape.cpp",
+                "arguments": ["c++", "-Iinclude", "-c", "src/shape.cpp"]
+            }])
+            .to_string(),
+        )
+        .unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let header = root.join("include/shape.h");
+        let source = root.join("src/shape.cpp");
+        assert!(has_edge(
+            &graph,
+            (&header, "Shape"),
+            (&source, "area"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&header, "area"),
+            (&source, "area"),
+            EdgeKind::References
+        ));
+        assert!(has_edge(
+            &graph,
+            (&header, "distance"),
+            (&source, "distance"),
+            EdgeKind::References
+        ));
+        assert!(has_edge(
+            &graph,
+            (&source, "shape.h"),
+            (&header, "shape.h"),
+            EdgeKind::Imports
+        ));
+        assert_eq!(graph.search("geo.Shape#area", 5).len(), 2);
+
+        // Editing the definition keeps the header paired with it
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        fs::write(
+            &source,
+            "#include \"shape.h\"\n\ndouble geo::Shape::area() const { return 1; }\n",
+        )
+        .unwrap();
+        rebuild_file(&mut graph, &source, &filter, &trees).unwrap();
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_swift_extensions_and_conformance() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("Shape.swift"),
+            "import Foundation\n\nprotocol Drawable {\n    func draw()\n}\n\n\
+             class Shape {\n    var name: String = \"\"\n}\n\n\
+             class Circle: Shape, Drawable {\n    func draw() {}\n}\n\n\
+             struct Point {\n    var x: Int\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("Point+Drawable.swift"),
+            "extension Point: Drawable {\n    func draw() {}\n}\n",
+        )
+        .unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let shapes = root.join("Shape.swift");
+        let extension = root.join("Point+Drawable.swift");
+        assert!(has_edge(
+            &graph,
+            (&shapes, "Circle"),
+            (&shapes, "Shape"),
+            EdgeKind::Extends
+        ));
+        assert!(has_edge(
+            &graph,
+            (&shapes, "Circle"),
+            (&shapes, "Drawable"),
+            EdgeKind::Implements
+        ));
+        assert!(has_edge(
+            &graph,
+            (&shapes, "Point"),
+            (&extension, "Point"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&extension, "Point"),
+            (&extension, "draw"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&extension, "Point"),
+            (&shapes, "Drawable"),
+            EdgeKind::Implements
+        ));
+        assert_eq!(graph.search("Point#draw", 5).len(), 1);
+
+        // Removing the protocol drops conformance until it comes back
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        fs::write(&extension, "extension Point {\n    func draw() {}\n}\n").unwrap();
+        rebuild_file(&mut graph, &extension, &filter, &trees).unwrap();
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_ruby_mixins_namespaces_and_requires() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("app/models")).unwrap();
+        fs::create_dir_all(root.join("app/controllers")).unwrap();
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::create_dir_all(root.join("lib/billing")).unwrap();
+        fs::write(root.join("Gemfile"), "source 'https://rubygems.org'\n").unwrap();
+        fs::write(root.join("config/application.rb"), "module Shop\nend\n").unwrap();
+        fs::write(
+            root.join("app/models/application_record.rb"),
+            "class ApplicationRecord < ActiveRecord::Base\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("app/models/user.rb"),
+            "require \"billing/payable\"\n\nclass User < ApplicationRecord\n  \
+             include Billing::Payable\n  attr_reader :name\n\n  \
+             def self.find_by_email(email)\n    puts email\n  end\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("lib/billing/payable.rb"),
+            "module Billing\n  module Payable\n    def pay; end\n  end\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("lib/billing/invoice.rb"),
+            "class Billing::Invoice\n  def total; end\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("app/controllers/users_controller.rb"),
+            "class UsersController\n  def show\n    User.find(1)\n    User.find(2)\n  end\nend\n",
+        )
+        .unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let user = root.join("app/models/user.rb");
+        let record = root.join("app/models/application_record.rb");
+        let payable = root.join("lib/billing/payable.rb");
+        let invoice = root.join("lib/billing/invoice.rb");
+        let controller = root.join("app/controllers/users_controller.rb");
+        let edges = snapshot(&graph).1;
+        assert!(has_edge(
+            &graph,
+            (&user, "User"),
+            (&record, "ApplicationRecord"),
+            EdgeKind::Extends
+        ));
+        assert!(has_edge(
+            &graph,
+            (&user, "User"),
+            (&payable, "Payable"),
+            EdgeKind::Implements
+        ));
+        assert!(has_edge(
+            &graph,
+            (&user, "User"),
+            (&user, "name"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&user, "User"),
+            (&user, "find_by_email"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&user, "billing/payable"),
+            (&payable, "payable.rb"),
+            EdgeKind::Imports
+        ));
+        assert!(has_edge(
+            &graph,
+            (&payable, "Billing"),
+            (&invoice, "Invoice"),
+            EdgeKind::Contains
+        ));
+        assert_eq!(graph.search("Billing.Invoice#total", 5).len(), 1);
+
+        // Constants link to the files Rails autoloads them from, once per file
+        assert!(has_edge(
+            &graph,
+            (&user, "ApplicationRecord"),
+            (&record, "application_record.rb"),
+            EdgeKind::Imports
+        ));
+        let user_imports: Vec<_> = edges
+            .iter()
+            .filter(|(a, _, k)| a.0 == controller && a.2 == "User" && *k == EdgeKind::Imports)
+            .map(|(_, b, _)| b.0.clone())
+            .collect();
+        assert_eq!(user_imports, vec![user.clone()]);
+        assert!(!edges
+            .iter()
+            .any(|(_, b, k)| *k == EdgeKind::Calls
+                && ["include", "attr_reader"].contains(&b.2.as_str())));
+
+        // Dropping the mixin removes its edge
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        fs::write(&user, "class User < ApplicationRecord\nend\n").unwrap();
+        rebuild_file(&mut graph, &user, &filter, &trees).unwrap();
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_php_namespaces_traits_and_psr4_imports() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        for sub in ["src/Billing", "src/Contracts", "src/Concerns", "src/Models"] {
+            fs::create_dir_all(root.join(sub)).unwrap();
+        }
+        fs::write(
+            root.join("composer.json"),
+            r#"{"autoload": {"psr-4": {"App\\": "src/"}}}"#,
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/Contracts/Billable.php"),
+            "<?php\nnamespace App\\Contracts;\n\ninterface Billable\n{\n    public function bill(): void;\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/Concerns/HasTotals.php"),
+            "<?php\nnamespace App\\Concerns;\n\ntrait HasTotals\n{\n    protected int $total = 0;\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/Models/Document.php"),
+            "<?php\nnamespace App\\Models;\n\nabstract class Document\n{\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/Billing/Invoice.php"),
+            "<?php\nnamespace App\\Billing;\n\nuse App\\Contracts\\Billable;\n\
+             use App\\Concerns\\HasTotals;\nuse App\\Models\\Document;\n\n\
+             class Invoice extends Document implements Billable\n{\n    use HasTotals;\n\n\
+             \x20   public function bill(): void\n    {\n        printf('%d', $this->total);\n    }\n}\n",
+        )
+        .unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let invoice = root.join("src/Billing/Invoice.php");
+        let document = root.join("src/Models/Document.php");
+        let billable = root.join("src/Contracts/Billable.php");
+        let totals = root.join("src/Concerns/HasTotals.php");
+        assert!(has_edge(
+            &graph,
+            (&invoice, "Invoice"),
+            (&document, "Document"),
+            EdgeKind::Extends
+        ));
+        assert!(has_edge(
+            &graph,
+            (&invoice, "Invoice"),
+            (&billable, "Billable"),
+            EdgeKind::Implements
+        ));
+        assert!(has_edge(
+            &graph,
+            (&invoice, "Invoice"),
+            (&totals, "HasTotals"),
+            EdgeKind::Implements
+        ));
+        assert!(has_edge(
+            &graph,
+            (&invoice, "App\\Models\\Document"),
+            (&document, "Document.php"),
+            EdgeKind::Imports
+        ));
+        assert_eq!(graph.search("App.Billing.Invoice#bill", 5).len(), 1);
+
+        // Dropping the trait removes its edge
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        fs::write(
+            &invoice,
+            "<?php\nnamespace App\\Billing;\n\nclass Invoice extends \\App\\Models\\Document\n{\n}\n",
+        )
+        .unwrap();
+        rebuild_file(&mut graph, &invoice, &filter, &trees).unwrap();
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_elixir_and_bash_link_across_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("lib/my_app/billing")).unwrap();
+        fs::create_dir_all(root.join("scripts")).unwrap();
+        fs::write(root.join("mix.exs"), "defmodule MyApp.MixProject do\nend\n").unwrap();
+        fs::write(
+            root.join("lib/my_app/billing.ex"),
+            "defmodule MyApp.Billing do\n  def total(items), do: Enum.sum(items)\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("lib/my_app/printer.ex"),
+            "defprotocol MyApp.Printer do\n  def print(value)\nend\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("lib/my_app/billing/invoice.ex"),
+            "defmodule MyApp.Billing.Invoice do\n  alias MyApp.Billing\n\n  \
+             def send(invoice) do\n    Billing.total(invoice.items)\n  end\nend\n\n\
+             defimpl MyApp.Printer, for: MyApp.Billing.Invoice do\n  \
+             def print(invoice), do: inspect(invoice)\nend\n",
+        )
+        .unwrap();
+        fs::write(root.join("scripts/lib.sh"), "log() {\n  echo \"$1\"\n}\n").unwrap();
+        fs::write(
+            root.join("scripts/deploy.sh"),
+            "source \"$(dirname \"$0\")/lib.sh\"\n\ndeploy() {\n  log deploying\n}\n",
+        )
+        .unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let billing = root.join("lib/my_app/billing.ex");
+        let printer = root.join("lib/my_app/printer.ex");
+        let invoice = root.join("lib/my_app/billing/invoice.ex");
+        let lib = root.join("scripts/lib.sh");
+        let deploy = root.join("scripts/deploy.sh");
+        let edges = snapshot(&graph).1;
+        assert!(has_edge(
+            &graph,
+            (&billing, "Billing"),
+            (&invoice, "Invoice"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&invoice, "send"),
+            (&billing, "total"),
+            EdgeKind::Calls
+        ));
+        assert!(has_edge(
+            &graph,
+            (&invoice, "MyApp.Billing"),
+            (&billing, "billing.ex"),
+            EdgeKind::Imports
+        ));
+        assert!(edges.iter().any(|(a, b, k)| {
+            a.0 == invoice
+                && a.1 == NodeKind::Impl
+                && (&b.0, b.2.as_str()) == (&printer, "Printer")
+                && *k == EdgeKind::Implements
+        }));
+        assert!(has_edge(
+            &graph,
+            (&deploy, "deploy"),
+            (&lib, "log"),
+            EdgeKind::Calls
+        ));
+        assert_eq!(graph.search("MyApp.Billing.Invoice.send", 5).len(), 1);
+
+        // The Elixir function head is not a call of itself
+        assert!(!has_edge(
+            &graph,
+            (&invoice, "send"),
+            (&invoice, "send"),
+            EdgeKind::Calls
+        ));
+
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        fs::write(&billing, "defmodule MyApp.Billing do\nend\n").unwrap();
+        rebuild_file(&mut graph, &billing, &filter, &trees).unwrap();
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_rust_impls_macros_and_inline_modules() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(
+            root.join("src/engine.rs"),
+            "pub struct Engine {\n    pub speed: u32,\n}\n\npub enum Mode {\n    Fast,\n}\n\n\
+             pub trait Runner {\n    type Output;\n    fn run(&self);\n}\n\n\
+             macro_rules! trace {\n    ($e:expr) => { $e };\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/run.rs"),
+            "use crate::engine::{Engine, Runner};\n\n\
+             struct Other;\n\nimpl Other {\n    fn step(&self) {}\n}\n\n\
+             impl Runner for Engine {\n    type Output = u32;\n\n    fn run(&self) {\n        \
+             self.step();\n        trace!(render(1));\n    }\n}\n\n\
+             impl Engine {\n    fn step(&self) {}\n}\n\n\
+             fn render(n: u32) -> u32 {\n    n\n}\n\n\
+             #[cfg(test)]\nmod tests {\n    #[test]\n    fn renders() {\n        \
+             assert_eq!(super::render(1), 1);\n    }\n}\n",
+        )
+        .unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let engine = root.join("src/engine.rs");
+        let run = root.join("src/run.rs");
+        let edges = snapshot(&graph).1;
+        let (class, imp, method) = (NodeKind::Struct, NodeKind::Impl, NodeKind::Method);
+        assert!(has_edge(
+            &graph,
+            (&engine, class, "Engine"),
+            (&run, imp, "Engine"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&run, imp, "Engine"),
+            (&run, method, "run"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&run, imp, "Engine"),
+            (&engine, NodeKind::Trait, "Runner"),
+            EdgeKind::Implements
+        ));
+        assert!(has_edge(
+            &graph,
+            (&engine, NodeKind::Trait, "Runner"),
+            (&engine, method, "run"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&engine, class, "Engine"),
+            (&engine, NodeKind::Variable, "speed"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&engine, NodeKind::Enum, "Mode"),
+            (&engine, NodeKind::Constant, "Fast"),
+            EdgeKind::Contains
+        ));
+
+        // `self.step()` is Engine's step, not Other's
+        let step_lines: Vec<usize> = edges
+            .iter()
+            .filter(|(a, b, k)| {
+                a.2 == "run" && a.0 == run && b.2 == "step" && *k == EdgeKind::Calls
+            })
+            .map(|(_, b, _)| b.3)
+            .collect();
+        assert_eq!(step_lines, vec![19]);
+        // Calls inside macro arguments, and the macro itself
+        assert!(has_edge(
+            &graph,
+            (&run, method, "run"),
+            (&engine, NodeKind::Macro, "trace"),
+            EdgeKind::Calls
+        ));
+        assert!(has_edge(
+            &graph,
+            (&run, method, "run"),
+            (&run, NodeKind::Function, "render"),
+            EdgeKind::Calls
+        ));
+        // Test modules scope their contents
+        assert!(has_edge(
+            &graph,
+            (&run, NodeKind::Module, "tests"),
+            (&run, NodeKind::Function, "renders"),
+            EdgeKind::Contains
+        ));
+        assert_eq!(graph.search("tests.renders", 5).len(), 1);
+
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        fs::write(
+            &engine,
+            "pub struct Engine;\n\npub trait Runner {\n    fn run(&self);\n}\n",
+        )
+        .unwrap();
+        rebuild_file(&mut graph, &engine, &filter, &trees).unwrap();
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_python_decorators_attributes_and_exports() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("app/users")).unwrap();
+        fs::write(root.join("app/db.py"), "def connect():\n    pass\n").unwrap();
+        fs::write(
+            root.join("app/users/models.py"),
+            "from dataclasses import dataclass\nfrom .. import db\n\nMAX_USERS = 100\n\n\n\
+             @dataclass(frozen=True)\nclass User:\n    id: int\n    ROLE = \"user\"\n\n    \
+             @property\n    def label(self):\n        return db.connect()\n\n    \
+             @staticmethod\n    @cached\n    def build():\n        return User(1)\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("app/users/__init__.py"),
+            "from .models import User, MAX_USERS\n\n__all__ = [\"User\", \"MAX_USERS\"]\n",
+        )
+        .unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let db = root.join("app/db.py");
+        let models = root.join("app/users/models.py");
+        let init = root.join("app/users/__init__.py");
+        let (class, file) = (NodeKind::Class, NodeKind::File);
+        assert!(has_edge(
+            &graph,
+            (&models, class, "User"),
+            (&models, NodeKind::Variable, "id"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&models, class, "User"),
+            (&models, NodeKind::Constant, "ROLE"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&models, file, "models.py"),
+            (&models, NodeKind::Constant, "MAX_USERS"),
+            EdgeKind::Defines
+        ));
+        assert!(has_edge(
+            &graph,
+            (&init, file, "__init__.py"),
+            (&models, class, "User"),
+            EdgeKind::Exports
+        ));
+        assert!(has_edge(
+            &graph,
+            (&init, file, "__init__.py"),
+            (&models, NodeKind::Constant, "MAX_USERS"),
+            EdgeKind::Exports
+        ));
+        // Relative imports resolve to the module
+        assert!(has_edge(
+            &graph,
+            (&models, NodeKind::Import, ".."),
+            (&db, file, "db.py"),
+            EdgeKind::Imports
+        ));
+        assert!(has_edge(
+            &graph,
+            (&init, NodeKind::Import, ".models"),
+            (&models, file, "models.py"),
+            EdgeKind::Imports
+        ));
+
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        fs::write(&models, "class User:\n    pass\n").unwrap();
+        rebuild_file(&mut graph, &models, &filter, &trees).unwrap();
+        assert_matches_fresh_build(&graph, root);
+        // Removing the exporting file leaves the exported symbol alone
+        fs::write(&init, "").unwrap();
+        rebuild_file(&mut graph, &init, &filter, &trees).unwrap();
+        assert_matches_fresh_build(&graph, root);
+    }
+
+    #[test]
+    fn test_script_exports_reexports_and_jsx_references() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/ui")).unwrap();
+        fs::write(
+            root.join("src/util.ts"),
+            "export const VERSION = 1;\nexport function slug() {}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/ui/button.tsx"),
+            "export const Button = memo(() => <Icon />);\n\nfunction Icon() {\n  return null;\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/index.ts"),
+            "export * from './util';\nexport { Button } from './ui/button';\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/app.tsx"),
+            "import { Button } from './ui/button';\n\n\
+             @Component({ tag: 'app' })\nexport abstract class View {\n  \
+             @Input() title: string;\n\n  @Watch('title')\n  @Log()\n  changed() {}\n}\n\n\
+             export default function App() {\n  return <Button />;\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("src/legacy.js"),
+            "const { slug } = require('./util');\n\nconst api = {\n  get() {\n    return slug();\n  },\n};\n\n\
+             module.exports = { api };\n",
+        )
+        .unwrap();
+
+        let mut graph = build_graph(root).unwrap();
+        let util = root.join("src/util.ts");
+        let button = root.join("src/ui/button.tsx");
+        let index = root.join("src/index.ts");
+        let app = root.join("src/app.tsx");
+        let legacy = root.join("src/legacy.js");
+        let (file, function) = (NodeKind::File, NodeKind::Function);
+        assert!(has_edge(
+            &graph,
+            (&index, file, "index.ts"),
+            (&util, file, "util.ts"),
+            EdgeKind::Exports
+        ));
+        assert!(has_edge(
+            &graph,
+            (&index, file, "index.ts"),
+            (&button, function, "Button"),
+            EdgeKind::Exports
+        ));
+        assert!(has_edge(
+            &graph,
+            (&util, file, "util.ts"),
+            (&util, NodeKind::Constant, "VERSION"),
+            EdgeKind::Exports
+        ));
+        assert!(has_edge(
+            &graph,
+            (&app, file, "app.tsx"),
+            (&app, NodeKind::Class, "View"),
+            EdgeKind::Exports
+        ));
+        assert!(has_edge(
+            &graph,
+            (&app, function, "App"),
+            (&button, function, "Button"),
+            EdgeKind::References
+        ));
+        assert!(has_edge(
+            &graph,
+            (&button, function, "Button"),
+            (&button, function, "Icon"),
+            EdgeKind::References
+        ));
+        // CommonJS
+        assert!(has_edge(
+            &graph,
+            (&legacy, NodeKind::Import, "./util"),
+            (&util, file, "util.ts"),
+            EdgeKind::Imports
+        ));
+        assert!(has_edge(
+            &graph,
+            (&legacy, file, "legacy.js"),
+            (&legacy, NodeKind::Variable, "api"),
+            EdgeKind::Exports
+        ));
+        assert!(has_edge(
+            &graph,
+            (&legacy, NodeKind::Variable, "api"),
+            (&legacy, NodeKind::Method, "get"),
+            EdgeKind::Contains
+        ));
+        assert!(has_edge(
+            &graph,
+            (&legacy, NodeKind::Method, "get"),
+            (&util, function, "slug"),
+            EdgeKind::Calls
+        ));
+
+        let filter = project_filter(root).unwrap();
+        let trees = TreeCache::default();
+        fs::write(&util, "export function slug() {}\n").unwrap();
+        rebuild_file(&mut graph, &util, &filter, &trees).unwrap();
+        assert_matches_fresh_build(&graph, root);
+        fs::write(&button, "export function Button() {}\n").unwrap();
+        rebuild_file(&mut graph, &button, &filter, &trees).unwrap();
+        assert_matches_fresh_build(&graph, root);
+    }
+}
@@ -0,0 +1,439 @@
+//! Symbol metadata — signature, visibility, modifiers and doc comments,
+//! read off a definition's syntax tree.
+//!
+//! Decorators come from the tag query (`@decorator`); everything else is
+//! found structurally, since every grammar spells modifiers as keyword
+//! tokens (possibly grouped under a `*modifier*` node) ahead of the name,
+//! and doc comments as comment siblings right above the definition.
+
+use tree_sitter::Node;
+
+use super::language::SupportedLanguage;
+use crate::graph::types::{NodeKind, SymbolMetadata, Visibility};
+
+/// Longest signature kept, in bytes.
+const MAX_SIGNATURE_BYTES: usize = 300;
+
+/// Keywords recorded as modifiers.
+const MODIFIERS: &[&str] = &[
+    "abstract",
+    "async",
+    "const",
+    "data",
+    "default",
+    "extern",
+    "final",
+    "get",
+    "inline",
+    "lateinit",
+    "mut",
+    "native",
+    "override",
+    "partial",
+    "readonly",
+    "sealed",
+    "set",
+    "static",
+    "suspend",
+    "synchronized",
+    "transient",
+    "unsafe",
+    "virtual",
+    "volatile",
+];
+
+/// Nodes that wrap a definition without being part of it: the doc
+/// comment sits above the wrapper.
+const WRAPPERS: &[&str] = &[
+    "export_statement",
+    "decorated_definition",
+    "ambient_declaration",
+    "type_declaration",
+    "statement",
+];
+
+/// Metadata for the definition `node`, given its query-captured decorators.
+pub(crate) fn extract(
+    node: Node,
+    name: &str,
+    kind: NodeKind,
+    language: SupportedLanguage,
+    source: &[u8],
+    decorators: Vec<String>,
+) -> SymbolMetadata {
+    let mut visibility = None;
+    let mut modifiers = Vec::new();
+    scan_modifiers(node, source, &mut visibility, &mut modifiers);
+
+    let docstring = docstring(node, language, source);
+    let deprecated = decorators.iter().any(|d| {
+        let head = d.split('(').next().unwrap_or(d).trim().to_ascii_lowercase();
+        head.ends_with("deprecated") || head.ends_with("obsolete")
+    }) || docstring
+        .as_deref()
+        .is_some_and(|doc| doc.to_ascii_lowercase().contains("@deprecated"));
+    if deprecated {
+        modifiers.push("deprecated".to_string());
+    }
+
+    SymbolMetadata {
+        signature: signature(node, source),
+        visibility: visibility.or_else(|| default_visibility(node, name, kind, language, source)),
+        modifiers,
+        docstring,
+        decorators,
+    }
+}
+
+fn is_decoration(node: Node) -> bool {
+    let kind = node.kind();
+    kind.contains("decorator") || kind.contains("annotation") || kind.contains("attribute")
+}
+
+fn is_comment(node: Node) -> bool {
+    node.kind().contains("comment")
+}
+
+// ─── Signatures ─────────────────────────────────────────────────
+
+/// The declaration from its first keyword up to its body, whitespace
+/// collapsed; the first line when it has no body.
+fn signature(node: Node, source: &[u8]) -> Option<String> {
+    let start = signature_start(node).unwrap_or(node.start_byte());
+    let text = match body(node) {
+        Some(body) if body.start_byte() > start => {
+            std::str::from_utf8(&source[start..body.start_byte()]).ok()?
+        }
+        _ => std::str::from_utf8(&source[start..node.end_byte()])
+            .ok()?
+            .lines()
+            .next()?,
+    };
+    let mut signature = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    // Elixir's one-line `def f(a), do: a`
+    if let Some(end) = signature.find(", do:") {
+        signature.truncate(end);
+    }
+    // The token that opens the body: `{`, `:`, `=>`, `=`, Ruby/Elixir `do`
+    loop {
+        let trimmed = signature
+            .trim_end_matches("=>")
+            .trim_end_matches(['{', ':', '=', ';'])
+            .trim_end()
+            .trim_end_matches(" do")
+            .trim_end();
+        if trimmed.len() == signature.len() {
+            break;
+        }
+        signature.truncate(trimmed.len());
+    }
+    if signature.len() > MAX_SIGNATURE_BYTES {
+        let mut end = MAX_SIGNATURE_BYTES;
+        while !signature.is_char_boundary(end) {
+            end -= 1;
+        }
+        signature.truncate(end);
+        signature.push_str(" ...");
+    }
+    (!signature.is_empty()).then_some(signature)
+}
+
+/// Where the declaration proper starts: after any leading decorators,
+/// annotations and comments (Java keeps annotations among its modifiers).
+fn signature_start(node: Node) -> Option<usize> {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        if is_decoration(child) || is_comment(child) || child.kind() == "description" {
+            continue;
+        }
+        if child.kind() == "modifiers" {
+            if let Some(start) = signature_start(child) {
+                return Some(start);
+            }
+            continue;
+        }
+        return Some(child.start_byte());
+    }
+    None
+}
+
+/// The definition's body, or that of the function it's bound to
+/// (`const f = () => {}`).
+fn body(node: Node) -> Option<Node> {
+    if let Some(body) = node.child_by_field_name("body") {
+        return Some(body);
+    }
+    let declarator = node
+        .named_children(&mut node.walk())
+        .find(|c| c.kind() == "variable_declarator")?;
+    let value = declarator.child_by_field_name("value")?;
+    value.child_by_field_name("body")
+}
+
+// ─── Visibility and modifiers ───────────────────────────────────
+
+/// Collect keyword tokens ahead of the definition's name, descending
+/// into `*modifier*` groups (Java `modifiers`, Rust `function_modifiers`).
+fn scan_modifiers(
+    node: Node,
+    source: &[u8],
+    visibility: &mut Option<Visibility>,
+    modifiers: &mut Vec<String>,
+) {
+    let mut cursor = node.walk();
+    for (i, child) in node.children(&mut cursor).enumerate() {
+        if matches!(
+            node.field_name_for_child(i as u32),
+            Some("name" | "body" | "declarator" | "parameters" | "type" | "value")
+        ) {
+            break;
+        }
+        let kind = child.kind();
+        if is_decoration(child) || is_comment(child) {
+            continue;
+        }
+        if kind == "visibility_modifier" || kind == "accessibility_modifier" {
+            let text = child.utf8_text(source).unwrap_or("").trim();
+            *visibility = visibility.or_else(|| parse_visibility(text));
+        } else if kind.contains("modifier")
+            || kind.ends_with("_specifier")
+            || kind == "type_qualifier"
+            || kind == "virtual"
+        {
+            scan_modifiers(child, source, visibility, modifiers);
+        } else if child.child_count() == 0 {
+            let text = child.utf8_text(source).unwrap_or("").trim();
+            if let Some(v) = parse_visibility(text) {
+                *visibility = visibility.or(Some(v));
+            } else if MODIFIERS.contains(&text) && !modifiers.iter().any(|m| m == text) {
+                modifiers.push(text.to_string());
+            }
+        }
+    }
+}
+
+/// `pub` and `public` are public; `pub(crate)` and `internal` are internal.
+fn parse_visibility(text: &str) -> Option<Visibility> {
+    if text == "pub" {
+        return Some(Visibility::Public);
+    }
+    if text.starts_with("pub(") {
+        return Some(Visibility::Internal);
+    }
+    match text.split('(').next().unwrap_or(text).trim() {
+        "public" | "open" => Some(Visibility::Public),
+        "internal" | "package" => Some(Visibility::Internal),
+        "protected" => Some(Visibility::Protected),
+        "private" | "fileprivate" => Some(Visibility::Private),
+        _ => None,
+    }
+}
+
+/// The visibility a language gives a definition that doesn't declare one.
+fn default_visibility(
+    node: Node,
+    name: &str,
+    kind: NodeKind,
+    language: SupportedLanguage,
+    source: &[u8],
+) -> Option<Visibility> {
+    if matches!(kind, NodeKind::Impl | NodeKind::Import | NodeKind::File) {
+        return None;
+    }
+    let inside = |kinds: &[&str]| ancestors(node).any(|a| kinds.contains(&a.kind()));
+    match language {
+        // Items are private unless `pub`; trait members and variants
+        // share their container's visibility
+        SupportedLanguage::Rust => {
+            let inherited = inside(&["trait_item", "enum_variant_list"])
+                || ancestors(node)
+                    .any(|a| a.kind() == "impl_item" && a.child_by_field_name("trait").is_some());
+            (!inherited && kind != NodeKind::Macro).then_some(Visibility::Private)
+        }
+        SupportedLanguage::Go => Some(if name.starts_with(|c: char| c.is_uppercase()) {
+            Visibility::Public
+        } else {
+            Visibility::Internal
+        }),
+        SupportedLanguage::Python => Some(if name.starts_with('_') && !name.ends_with("__") {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        }),
+        SupportedLanguage::Java => Some(if inside(&["interface_body", "annotation_type_body"]) {
+            Visibility::Public
+        } else {
+            Visibility::Internal
+        }),
+        SupportedLanguage::CSharp => Some(if inside(&["interface_declaration"]) {
+            Visibility::Public
+        } else if inside(&[
+            "class_declaration",
+            "struct_declaration",
+            "record_declaration",
+        ]) {
+            Visibility::Private
+        } else {
+            Visibility::Internal
+        }),
+        SupportedLanguage::JavaScript | SupportedLanguage::TypeScript | SupportedLanguage::Tsx => {
+            if name.starts_with('#') {
+                Some(Visibility::Private)
+            } else {
+                inside(&["class_body"]).then_some(Visibility::Public)
+            }
+        }
+        SupportedLanguage::Php => inside(&["declaration_list"]).then_some(Visibility::Public),
+        SupportedLanguage::Swift => Some(Visibility::Internal),
+        SupportedLanguage::Kotlin | SupportedLanguage::Scala => Some(Visibility::Public),
+        // `local` functions and variables are private to the chunk
+        SupportedLanguage::Lua => Some(if node.child(0).is_some_and(|c| c.kind() == "local") {
+            Visibility::Private
+        } else {
+            Visibility::Public
+        }),
+        // `defp`, `defmacrop` and `defguardp` are private to the module
+        SupportedLanguage::Elixir => {
+            let target = node.child_by_field_name("target")?.utf8_text(source).ok()?;
+            target.starts_with("def").then(|| {
+                if target.ends_with('p') && target != "defimpl" {
+                    Visibility::Private
+                } else {
+                    Visibility::Public
+                }
+            })
+        }
+        _ => None,
+    }
+}
+
+fn ancestors(node: Node) -> impl Iterator<Item = Node> {
+    std::iter::successors(node.parent(), |n| n.parent())
+}
+
+// ─── Doc comments ───────────────────────────────────────────────
+
+/// A Python docstring, or the comment block directly above the
+/// definition (skipping attributes and decorators between them).
+fn docstring(node: Node, language: SupportedLanguage, source: &[u8]) -> Option<String> {
+    if language == SupportedLanguage::Python {
+        if let Some(doc) = python_docstring(node, source) {
+            return Some(doc);
+        }
+    }
+    if language == SupportedLanguage::GraphQl {
+        if let Some(doc) = graphql_description(node, source) {
+            return Some(doc);
+        }
+    }
+
+    // Climb out of wrappers such as `export` that hold only this definition
+    let mut anchor = node;
+    while let Some(parent) = anchor.parent() {
+        let held = parent
+            .named_children(&mut parent.walk())
+            .filter(|c| !is_decoration(*c) && !is_comment(*c))
+            .count();
+        if !WRAPPERS.contains(&parent.kind()) || held != 1 {
+            break;
+        }
+        anchor = parent;
+    }
+
+    let mut lines = Vec::new();
+    let mut next_row = anchor.start_position().row;
+    let mut current = anchor.prev_named_sibling();
+    while let Some(sibling) = current {
+        if sibling.end_position().row + 1 < next_row {
+            break;
+        }
+        if is_comment(sibling) {
+            // A trailing comment on the line above belongs to that line
+            if sibling.start_position().column != anchor.start_position().column {
+                break;
+            }
+            lines.push(strip_comment(sibling.utf8_text(source).unwrap_or("")));
+        } else if !is_decoration(sibling) {
+            break;
+        }
+        next_row = sibling.start_position().row;
+        current = sibling.prev_named_sibling();
+    }
+    lines.reverse();
+    let doc = lines.join("\n").trim().to_string();
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// A GraphQL description: the string ahead of the definition.
+fn graphql_description(node: Node, source: &[u8]) -> Option<String> {
+    let description = node.named_child(0).filter(|c| c.kind() == "description")?;
+    let doc = dedent(description.utf8_text(source).ok()?.trim_matches('"'));
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// The string literal opening a Python function or class body.
+fn python_docstring(node: Node, source: &[u8]) -> Option<String> {
+    let first = node.child_by_field_name("body")?.named_child(0)?;
+    if first.kind() != "expression_statement" {
+        return None;
+    }
+    let string = first.named_child(0).filter(|s| s.kind() == "string")?;
+    let content = string
+        .named_children(&mut string.walk())
+        .filter(|c| c.kind() == "string_content")
+        .map(|c| c.utf8_text(source).unwrap_or(""))
+        .collect::<String>();
+    let doc = dedent(&content);
+    (!doc.is_empty()).then_some(doc)
+}
+
+/// A comment's text without its markers: `///`, `//`, `#`, `--`, and
+/// `/** ... */` with the leading `*` of each line.
+fn strip_comment(text: &str) -> String {
+    let text = text.trim();
+    if let Some(block) = text.strip_prefix("/*") {
+        let block = block.trim_start_matches(['*', '!']).trim_end_matches("*/");
+        return block
+            .lines()
+            .map(|line| {
+                let line = line.trim();
+                line.strip_prefix('*').unwrap_or(line).trim()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+            .trim()
+            .to_string();
+    }
+    text.lines()
+        .map(|line| {
+            let line = line.trim();
+            let line = line.trim_start_matches("///").trim_start_matches("//!");
+            let line = line
+                .trim_start_matches("//")
+                .trim_start_matches('#')
+                .trim_start_matches("--");
+            line.strip_prefix(' ').unwrap_or(line).trim_end()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Remove the common indentation of a docstring's continuation lines.
+fn dedent(text: &str) -> String {
+    let mut lines = text.trim().lines();
+    let first = lines.next().unwrap_or("").trim().to_string();
+    let rest: Vec<&str> = lines.collect();
+    let indent = rest
+        .iter()
+        .filter(|l| !l.trim().is_empty())
+        .map(|l| l.len() - l.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    std::iter::once(first)
+        .chain(
+            rest.iter()
+                .map(|l| l.get(indent..).unwrap_or("").trim_end().to_string()),
+        )
+        .collect::<Vec<_>>()
+        .join("\n")
+}
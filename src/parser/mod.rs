@@ -3,8 +3,17 @@
 //! Handles parsing source files into structured symbol data
 //! that feeds into the code graph.
 
+pub mod embedded;
 pub mod extractor;
+pub mod incremental;
 pub mod language;
+pub mod linking;
+mod metadata;
+pub mod resolve;
+pub mod tags;
 
-pub use extractor::extract_file;
+pub use embedded::{extract_embedded, HostFormat};
+pub use extractor::{extract_file, extract_file_with, ExtractOptions};
+pub use incremental::TreeCache;
 pub use language::SupportedLanguage;
+pub use tags::{TagQueries, TagQuery, REPLACE_DIRECTIVE};
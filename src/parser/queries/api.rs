@@ -15,6 +15,8 @@ use super::go::extract_go_apis;
 use super::java::extract_java_apis;
 use super::csharp::extract_csharp_apis;
 use super::ruby::extract_ruby_apis;
+use super::php::extract_php_apis;
+use super::kotlin::extract_kotlin_apis;
 
 /// Extract API endpoints from a parsed AST.
 ///
@@ -45,8 +47,24 @@ pub fn extract_api_endpoints(
         SupportedLanguage::Ruby => {
             extract_ruby_apis(root, source)
         }
+        SupportedLanguage::Php => {
+            extract_php_apis(root, source)
+        }
+        SupportedLanguage::Kotlin => {
+            extract_kotlin_apis(root, source)
+        }
         // Languages without API detection yet
-        SupportedLanguage::Rust | SupportedLanguage::Cpp | SupportedLanguage::Swift => {
+        SupportedLanguage::Rust
+        | SupportedLanguage::C
+        | SupportedLanguage::Cpp
+        | SupportedLanguage::Swift
+        | SupportedLanguage::Scala
+        | SupportedLanguage::Bash
+        | SupportedLanguage::Elixir
+        | SupportedLanguage::Lua
+        | SupportedLanguage::Sql
+        | SupportedLanguage::Protobuf
+        | SupportedLanguage::GraphQl => {
             Vec::new()
         }
     }
@@ -11,4 +11,5 @@ pub mod go;
 pub mod java;
 pub mod csharp;
 pub mod ruby;
-// pub mod kotlin;  // Disabled: tree-sitter version conflict
+pub mod php;
+pub mod kotlin;
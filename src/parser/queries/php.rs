@@ -0,0 +1,278 @@
+//! PHP API endpoint detection via AST traversal.
+//!
+//! Detects Laravel and Symfony routes:
+//!   - Route::get('/api/users', [UserController::class, 'index'])  (Laravel)
+//!   - Route::post('api/users', ...)  (Laravel)
+//!   - #[Route('/api/users', methods: ['GET'])]  (Symfony)
+//!   - #[Route('/api')] on a controller class as a base path  (Symfony)
+
+use tree_sitter::Node;
+use crate::graph::types::{ExtractedApiEndpoint, ApiEndpointKind};
+
+/// Extract API endpoints from PHP AST.
+pub fn extract_php_apis(root: &Node, source: &[u8]) -> Vec<ExtractedApiEndpoint> {
+    let mut endpoints = Vec::new();
+    let mut base_path = String::new();
+    extract_from_node(root, source, &mut endpoints, None, &mut base_path);
+    endpoints
+}
+
+fn extract_from_node(
+    node: &Node,
+    source: &[u8],
+    endpoints: &mut Vec<ExtractedApiEndpoint>,
+    current_scope: Option<&str>,
+    base_path: &mut String,
+) {
+    let kind = node.kind();
+
+    // Track class-level #[Route] for base path
+    if kind == "class_declaration" {
+        *base_path = find_route_attribute(node, source)
+            .and_then(|attribute| extract_path_from_attribute(&attribute, source))
+            .unwrap_or_default();
+    }
+
+    // Track function/method scope
+    let new_scope = if kind == "method_declaration" || kind == "function_definition" {
+        node.child_by_field_name("name")
+            .and_then(|n| n.utf8_text(source).ok())
+            .map(|s| s.to_string())
+    } else {
+        None
+    };
+    let scope = new_scope.as_deref().or(current_scope);
+
+    // Symfony: #[Route] on a controller method
+    if kind == "method_declaration" {
+        if let Some(mut endpoint) = extract_route_from_method(node, source, scope) {
+            // Prepend base path
+            if !base_path.is_empty() && !endpoint.url.starts_with(&*base_path) {
+                endpoint.url = format!("{}{}", base_path.trim_end_matches('/'), endpoint.url);
+            }
+            endpoints.push(endpoint);
+        }
+    }
+
+    // Laravel: Route::get('/api/users', ...)
+    if kind == "scoped_call_expression" {
+        if let Some(endpoint) = extract_laravel_route(node, source, scope) {
+            endpoints.push(endpoint);
+        }
+    }
+
+    // Recurse
+    let count = node.child_count();
+    for i in 0..count {
+        if let Some(child) = node.child(i) {
+            extract_from_node(&child, source, endpoints, scope, base_path);
+        }
+    }
+}
+
+fn extract_laravel_route(
+    node: &Node,
+    source: &[u8],
+    scope: Option<&str>,
+) -> Option<ExtractedApiEndpoint> {
+    let class = node.child_by_field_name("scope")
+        .and_then(|n| n.utf8_text(source).ok())?;
+    if class.trim_start_matches('\\') != "Route" {
+        return None;
+    }
+
+    let method = node.child_by_field_name("name")
+        .and_then(|n| n.utf8_text(source).ok())?;
+    let http_method = match method {
+        "get" => Some("GET"),
+        "post" => Some("POST"),
+        "put" => Some("PUT"),
+        "patch" => Some("PATCH"),
+        "delete" => Some("DELETE"),
+        "any" | "match" => None, // Could be any method
+        _ => return None,
+    };
+
+    let args = node.child_by_field_name("arguments")?;
+    // Route::match(['GET', 'POST'], '/url', ...) takes the methods first
+    let url = if method == "match" {
+        nth_string_arg(&args, source, 1)?
+    } else {
+        nth_string_arg(&args, source, 0)?
+    };
+    // Laravel route paths don't need a leading slash
+    let url = format!("/{}", url.trim_start_matches('/'));
+
+    if !is_api_url(&url) {
+        return None;
+    }
+
+    Some(ExtractedApiEndpoint {
+        url: normalize_url(&url),
+        method: http_method.map(|s| s.to_string()),
+        kind: ApiEndpointKind::Defines,
+        scope: scope.map(|s| s.to_string()),
+        line: node.start_position().row + 1,
+    })
+}
+
+fn extract_route_from_method(
+    node: &Node,
+    source: &[u8],
+    scope: Option<&str>,
+) -> Option<ExtractedApiEndpoint> {
+    let attribute = find_route_attribute(node, source)?;
+    let url = extract_path_from_attribute(&attribute, source)?;
+
+    if !is_api_url(&url) {
+        return None;
+    }
+
+    Some(ExtractedApiEndpoint {
+        url: normalize_url(&url),
+        method: extract_route_method(&attribute, source).or(Some("GET".to_string())),
+        kind: ApiEndpointKind::Defines,
+        scope: scope.map(|s| s.to_string()),
+        line: node.start_position().row + 1,
+    })
+}
+
+/// The `#[Route(...)]` attribute of a class or method, if any.
+fn find_route_attribute<'a>(node: &Node<'a>, source: &[u8]) -> Option<Node<'a>> {
+    let attributes = node.child_by_field_name("attributes")?;
+    let group_count = attributes.child_count();
+    for i in 0..group_count {
+        let Some(group) = attributes.child(i) else { continue };
+        let count = group.child_count();
+        for j in 0..count {
+            if let Some(attribute) = group.child(j) {
+                if attribute.kind() != "attribute" {
+                    continue;
+                }
+                let name = attribute.named_child(0)
+                    .and_then(|n| n.utf8_text(source).ok())
+                    .unwrap_or("");
+                // Route, or a qualified Symfony\...\Route
+                if name.rsplit('\\').next() == Some("Route") {
+                    return Some(attribute);
+                }
+            }
+        }
+    }
+    None
+}
+
+fn extract_path_from_attribute(node: &Node, source: &[u8]) -> Option<String> {
+    let args = node.child_by_field_name("parameters")?;
+
+    let count = args.child_count();
+    for i in 0..count {
+        if let Some(argument) = args.child(i) {
+            if argument.kind() != "argument" {
+                continue;
+            }
+            // '/api/users' or path: '/api/users'
+            let name = argument.child_by_field_name("name")
+                .and_then(|n| n.utf8_text(source).ok());
+            if name.is_none() || name == Some("path") {
+                let value = argument.named_child(argument.named_child_count() - 1)?;
+                if value.kind() == "string" || value.kind() == "encapsed_string" {
+                    let text = value.utf8_text(source).ok()?;
+                    return Some(strip_quotes(text));
+                }
+            }
+        }
+    }
+    None
+}
+
+fn extract_route_method(node: &Node, source: &[u8]) -> Option<String> {
+    let args = node.child_by_field_name("parameters")?;
+
+    let count = args.child_count();
+    for i in 0..count {
+        if let Some(argument) = args.child(i) {
+            let name = argument.child_by_field_name("name")
+                .and_then(|n| n.utf8_text(source).ok());
+            if name == Some("methods") {
+                let value = argument.utf8_text(source).ok()?.to_uppercase();
+
+                if value.contains("GET") { return Some("GET".to_string()); }
+                if value.contains("POST") { return Some("POST".to_string()); }
+                if value.contains("PUT") { return Some("PUT".to_string()); }
+                if value.contains("DELETE") { return Some("DELETE".to_string()); }
+                if value.contains("PATCH") { return Some("PATCH".to_string()); }
+            }
+        }
+    }
+    None
+}
+
+fn nth_string_arg(args: &Node, source: &[u8], n: usize) -> Option<String> {
+    let mut seen = 0;
+    let count = args.child_count();
+    for i in 0..count {
+        if let Some(argument) = args.child(i) {
+            if argument.kind() != "argument" {
+                continue;
+            }
+            if seen == n {
+                let value = argument.named_child(0)?;
+                return match value.kind() {
+                    "string" | "encapsed_string" => {
+                        let text = value.utf8_text(source).ok()?;
+                        Some(strip_quotes(text))
+                    }
+                    _ => None,
+                };
+            }
+            seen += 1;
+        }
+    }
+    None
+}
+
+fn strip_quotes(s: &str) -> String {
+    let s = s.trim();
+    if s.len() < 2 {
+        return s.to_string();
+    }
+
+    if (s.starts_with('"') && s.ends_with('"'))
+        || (s.starts_with('\'') && s.ends_with('\''))
+    {
+        s[1..s.len()-1].to_string()
+    } else {
+        s.to_string()
+    }
+}
+
+fn normalize_url(url: &str) -> String {
+    let mut result = String::new();
+    let mut chars = url.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        if c == '{' {
+            // Laravel/Symfony route parameter: {id}, {slug?}
+            while let Some(c2) = chars.next() {
+                if c2 == '}' {
+                    break;
+                }
+            }
+            result.push_str(":param");
+        } else {
+            result.push(c);
+        }
+    }
+
+    result
+}
+
+fn is_api_url(url: &str) -> bool {
+    let url = url.to_lowercase();
+    url.starts_with("/api/")
+        || url.starts_with("/v1/")
+        || url.starts_with("/v2/")
+        || url.contains("/api/")
+        || (url.starts_with('/') && url.len() > 1 && !url.contains('.'))
+}
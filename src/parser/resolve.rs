@@ -0,0 +1,983 @@
+//! Import resolution — maps import paths to files or directories inside
+//! the project using each ecosystem's manifest.
+//!
+//! - Go: imports under the module path declared in the nearest `go.mod`
+//!   resolve to the package directory below that `go.mod`
+//! - C/C++: `#include`s resolve against the including file's directory,
+//!   then the `-I`/`-iquote`/`-isystem` directories from the nearest
+//!   `compile_commands.json` (or `build/compile_commands.json`)
+//! - Ruby: `require_relative` paths resolve against the requiring file's
+//!   directory; `require` paths against the `lib/` of the nearest
+//!   `Gemfile` or `*.gemspec` project and, in a Rails app, its `app/*`
+//!   autoload directories (`require "user"` -> `app/models/user.rb`).
+//!   There, constants resolve to the file they're autoloaded from
+//!   (`Admin::UsersController` -> `app/controllers/admin/users_controller.rb`)
+//! - PHP: `use` names resolve through the PSR-4 `autoload` (and
+//!   `autoload-dev`) prefixes of the nearest `composer.json`; a grouped
+//!   `use` resolves to its namespace's directory
+//! - Bash: `source`d paths resolve against the script's directory,
+//!   including the `$(dirname "$0")/` and `${BASH_SOURCE%/*}/` idioms
+//! - Elixir: modules resolve to their Mix file under the nearest
+//!   `mix.exs` (`MyApp.Accounts.User` -> `lib/my_app/accounts/user.ex`)
+//! - Python: relative imports resolve against the importing file's
+//!   package (`from ..core import util` -> `../core.py` or
+//!   `../core/__init__.py`; `from . import db` -> `./db.py`)
+//! - JavaScript/TypeScript: relative specifiers resolve against the
+//!   importing file's directory, trying the usual extensions and `index`
+//!   files (`./button` -> `./button.tsx`, `./ui` -> `./ui/index.ts`)
+//! - Lua: `require`d modules resolve like the default `package.path`,
+//!   from the requiring file's directory or any above it up to the
+//!   nearest `*.rockspec` or `.luarc.json` project
+//!   (`geo.point` -> `geo/point.lua` or `geo/point/init.lua`)
+//! - Protobuf: imports resolve against the importing file's directory or
+//!   any above it up to the nearest `buf.yaml` module, standing in for
+//!   `protoc`'s include paths
+//!
+//! Lua and Protobuf searches also stop at a `.git` or `.anchor`
+//! directory, and stay in the file's own directory when there is no root
+//! above it.
+//!
+//! Except for Go, every path an import was tried against is kept in its
+//! `candidates`, so the graph can relink it when one of them is created
+//! or deleted later.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+use super::language::SupportedLanguage;
+use crate::graph::types::ExtractedImport;
+
+/// Fill in `resolved` for the imports of the file at `path`.
+pub fn resolve_imports(path: &Path, lang: SupportedLanguage, imports: &mut [ExtractedImport]) {
+    match lang {
+        SupportedLanguage::Go => resolve_go(path, imports),
+        SupportedLanguage::C | SupportedLanguage::Cpp => resolve_includes(path, imports),
+        SupportedLanguage::Ruby => resolve_requires(path, imports),
+        SupportedLanguage::Php => resolve_psr4(path, imports),
+        SupportedLanguage::Bash => resolve_sources(path, imports),
+        SupportedLanguage::Elixir => resolve_mix_modules(path, imports),
+        SupportedLanguage::Python => resolve_relative_modules(path, imports),
+        SupportedLanguage::JavaScript | SupportedLanguage::TypeScript | SupportedLanguage::Tsx => {
+            resolve_relative_specifiers(path, imports)
+        }
+        SupportedLanguage::Lua => resolve_lua_modules(path, imports),
+        SupportedLanguage::Protobuf => resolve_proto_imports(path, imports),
+        _ => {}
+    }
+}
+
+fn resolve_go(path: &Path, imports: &mut [ExtractedImport]) {
+    let Some((module_dir, module)) = find_go_module(path) else {
+        return;
+    };
+    for import in imports {
+        if import.path == module {
+            import.resolved = Some(module_dir.clone());
+        } else if let Some(rest) = import
+            .path
+            .strip_prefix(module.as_str())
+            .and_then(|rest| rest.strip_prefix('/'))
+        {
+            import.resolved = Some(module_dir.join(rest));
+        }
+    }
+}
+
+/// The nearest `go.mod` above `path`: (its directory, the module path),
+/// parsed once per modification.
+fn find_go_module(path: &Path) -> Option<(PathBuf, String)> {
+    static CACHE: MtimeCache<String> = OnceLock::new();
+
+    let go_mod = path
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join("go.mod"))
+        .find(|candidate| candidate.is_file())?;
+    let module = cached(&CACHE, &go_mod, |go_mod| {
+        let contents = fs::read_to_string(go_mod).ok()?;
+        let module = contents
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("module "))?;
+        Some(module.trim().trim_matches('"').to_string())
+    })?;
+    Some((go_mod.parent()?.to_path_buf(), module))
+}
+
+/// Try `candidates` in order: the import resolves to the first that
+/// `exists`, and keeps all of them.
+fn resolve_first(
+    import: &mut ExtractedImport,
+    candidates: Vec<PathBuf>,
+    exists: impl Fn(&Path) -> bool,
+) {
+    import.resolved = candidates
+        .iter()
+        .find(|candidate| exists(candidate))
+        .cloned();
+    import.candidates = candidates;
+}
+
+fn resolve_includes(path: &Path, imports: &mut [ExtractedImport]) {
+    let db = find_compile_db(path);
+    let include_dirs = db
+        .as_ref()
+        .map(|db| db.include_dirs(path))
+        .unwrap_or_default();
+    let own_dir = path.parent().map(Path::to_path_buf);
+
+    for import in imports {
+        let candidates = own_dir
+            .iter()
+            .chain(include_dirs)
+            .map(|dir| normalize(&dir.join(&import.path)))
+            .collect();
+        resolve_first(import, candidates, Path::is_file);
+    }
+}
+
+fn resolve_requires(path: &Path, imports: &mut [ExtractedImport]) {
+    let mut load_path: Vec<PathBuf> = path.parent().map(Path::to_path_buf).into_iter().collect();
+    let mut autoload = Vec::new();
+    if let Some(root) = find_ruby_project(path) {
+        load_path.push(root.join("lib"));
+        // Rails autoloads constants from every directory under app/
+        if root.join("config/application.rb").is_file() {
+            autoload = fs::read_dir(root.join("app"))
+                .into_iter()
+                .flatten()
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|dir| dir.is_dir())
+                .collect();
+            autoload.sort();
+            load_path.extend(autoload.iter().cloned());
+        }
+    }
+
+    for import in imports {
+        if let Some(file) = autoload_file(&import.path) {
+            // A file's references to its own constants aren't imports
+            let candidates = autoload
+                .iter()
+                .map(|dir| dir.join(&file))
+                .filter(|candidate| candidate != path)
+                .collect();
+            resolve_first(import, candidates, Path::is_file);
+            continue;
+        }
+        let file = if import.path.ends_with(".rb") {
+            import.path.clone()
+        } else {
+            format!("{}.rb", import.path)
+        };
+        let candidates = load_path
+            .iter()
+            .map(|dir| normalize(&dir.join(&file)))
+            .collect();
+        resolve_first(import, candidates, Path::is_file);
+    }
+}
+
+/// The file a constant is autoloaded from, relative to an autoload
+/// directory (`Admin::UsersController` -> `admin/users_controller.rb`).
+/// None for `require` paths.
+fn autoload_file(constant: &str) -> Option<String> {
+    let constant = constant.trim_start_matches("::");
+    if !constant.starts_with(|c: char| c.is_ascii_uppercase()) {
+        return None;
+    }
+    let segments: Vec<String> = constant.split("::").map(underscore).collect();
+    Some(format!("{}.rb", segments.join("/")))
+}
+
+/// The directory of the nearest `Gemfile` or `*.gemspec` above `path`.
+/// Each directory is listed once per modification.
+fn find_ruby_project(path: &Path) -> Option<PathBuf> {
+    static CACHE: MtimeCache<bool> = OnceLock::new();
+
+    path.ancestors().skip(1).find_map(|dir| {
+        let is_root = cached(&CACHE, dir, |dir| {
+            let listing = fs::read_dir(dir).ok()?;
+            Some(listing.flatten().any(|entry| {
+                entry.file_name() == "Gemfile"
+                    || entry.path().extension().is_some_and(|ext| ext == "gemspec")
+            }))
+        })?;
+        is_root.then(|| dir.to_path_buf())
+    })
+}
+
+fn resolve_psr4(path: &Path, imports: &mut [ExtractedImport]) {
+    let Some(prefixes) = find_psr4_prefixes(path) else {
+        return;
+    };
+    for import in imports {
+        let name = import.path.trim_start_matches('\\');
+        // The longest namespace prefix wins; under each, the class file,
+        // then the namespace directory (a grouped `use`)
+        let mut bases: Vec<(usize, PathBuf)> = prefixes
+            .iter()
+            .filter_map(|(prefix, dir)| {
+                let rest = if prefix.is_empty() {
+                    name
+                } else {
+                    name.strip_prefix(prefix.as_str())?
+                };
+                Some((prefix.len(), dir.join(rest.replace('\\', "/"))))
+            })
+            .collect();
+        bases.sort_by_key(|(len, _)| std::cmp::Reverse(*len));
+        let candidates = bases
+            .into_iter()
+            .flat_map(|(_, base)| [base.with_extension("php"), base])
+            .collect();
+        resolve_first(import, candidates, |candidate| {
+            if candidate.extension().is_some_and(|ext| ext == "php") {
+                candidate.is_file()
+            } else {
+                candidate.is_dir()
+            }
+        });
+    }
+}
+
+/// PSR-4 (namespace prefix, directory) pairs from the nearest
+/// `composer.json` above `path`, parsed once per modification. Prefixes
+/// keep their trailing `\`.
+fn find_psr4_prefixes(path: &Path) -> Option<Arc<Vec<(String, PathBuf)>>> {
+    static CACHE: MtimeCache<Arc<Vec<(String, PathBuf)>>> = OnceLock::new();
+
+    let composer_path = path
+        .ancestors()
+        .skip(1)
+        .map(|dir| dir.join("composer.json"))
+        .find(|candidate| candidate.is_file())?;
+    cached(&CACHE, &composer_path, |composer_path| {
+        let root = composer_path.parent()?;
+        let contents = fs::read_to_string(composer_path).ok()?;
+        parse_psr4(root, &contents).map(Arc::new)
+    })
+}
+
+/// The PSR-4 prefixes of a `composer.json` in `root`.
+fn parse_psr4(root: &Path, contents: &str) -> Option<Vec<(String, PathBuf)>> {
+    let composer: serde_json::Value = serde_json::from_str(contents).ok()?;
+
+    let mut prefixes = Vec::new();
+    for section in ["autoload", "autoload-dev"] {
+        let Some(psr4) = composer[section]["psr-4"].as_object() else {
+            continue;
+        };
+        for (prefix, dirs) in psr4 {
+            // A prefix maps to one directory or a list of them
+            let dirs = match dirs {
+                serde_json::Value::Array(dirs) => dirs.iter().filter_map(|d| d.as_str()).collect(),
+                dir => dir.as_str().into_iter().collect::<Vec<_>>(),
+            };
+            for dir in dirs {
+                prefixes.push((prefix.clone(), normalize(&root.join(dir))));
+            }
+        }
+    }
+    Some(prefixes)
+}
+
+fn resolve_sources(path: &Path, imports: &mut [ExtractedImport]) {
+    let Some(dir) = path.parent() else {
+        return;
+    };
+    for import in imports {
+        let candidates = script_relative(&import.path)
+            .map(|relative| normalize(&dir.join(relative)))
+            .into_iter()
+            .collect();
+        resolve_first(import, candidates, Path::is_file);
+    }
+}
+
+/// A sourced path relative to the script, or `None` when it depends on
+/// other variables.
+fn script_relative(path: &str) -> Option<&str> {
+    if !path.contains('$') {
+        return Some(path);
+    }
+    const SCRIPT_DIR: &[&str] = &[
+        "$(dirname \"$0\")/",
+        "$(dirname $0)/",
+        "$(dirname \"${BASH_SOURCE[0]}\")/",
+        "${BASH_SOURCE%/*}/",
+    ];
+    SCRIPT_DIR
+        .iter()
+        .find_map(|prefix| path.strip_prefix(prefix))
+}
+
+fn resolve_mix_modules(path: &Path, imports: &mut [ExtractedImport]) {
+    let Some(root) = path
+        .ancestors()
+        .skip(1)
+        .find(|dir| dir.join("mix.exs").is_file())
+    else {
+        return;
+    };
+    for import in imports {
+        let base = import
+            .path
+            .split('.')
+            .map(underscore)
+            .fold(root.join("lib"), |dir, segment| dir.join(segment));
+        let mut candidates = vec![base.with_extension("ex")];
+        // `alias MyApp.{Repo, Mailer}` names a directory of modules
+        if !import.symbols.is_empty() {
+            candidates.push(base);
+        }
+        resolve_first(import, candidates, |candidate| {
+            if candidate.extension().is_some_and(|ext| ext == "ex") {
+                candidate.is_file()
+            } else {
+                candidate.is_dir()
+            }
+        });
+    }
+}
+
+/// Elixir's `Macro.underscore` (and Rails' `String#underscore`) for one
+/// alias segment: `MyApp` -> `my_app`, `HTTPClient` -> `http_client`.
+fn underscore(segment: &str) -> String {
+    let chars: Vec<char> = segment.chars().collect();
+    let mut out = String::with_capacity(segment.len() + 4);
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_ascii_uppercase() && i > 0 {
+            let prev = chars[i - 1];
+            let next_lower = chars.get(i + 1).is_some_and(|n| n.is_ascii_lowercase());
+            if prev.is_ascii_lowercase()
+                || prev.is_ascii_digit()
+                || (prev.is_ascii_uppercase() && next_lower)
+            {
+                out.push('_');
+            }
+        }
+        out.push(c.to_ascii_lowercase());
+    }
+    out
+}
+
+fn resolve_relative_modules(path: &Path, imports: &mut [ExtractedImport]) {
+    let Some(dir) = path.parent() else {
+        return;
+    };
+    for import in imports {
+        let rest = import.path.trim_start_matches('.');
+        let dots = import.path.len() - rest.len();
+        if dots == 0 {
+            continue;
+        }
+        // One dot is the file's own package, each further dot its parent
+        let package = (1..dots).fold(dir.to_path_buf(), |dir, _| dir.join(".."));
+        let base = rest
+            .split('.')
+            .filter(|s| !s.is_empty())
+            .fold(package, |dir, segment| dir.join(segment));
+        // `from . import db` imports the module `db` when there is one
+        let module = match import.symbols.as_slice() {
+            [symbol] if rest.is_empty() => Some(base.join(symbol)),
+            _ => None,
+        };
+        let candidates = module
+            .into_iter()
+            .chain([base])
+            .flat_map(|base| [base.with_extension("py"), base.join("__init__.py")])
+            .map(|candidate| normalize(&candidate))
+            .collect();
+        resolve_first(import, candidates, Path::is_file);
+    }
+}
+
+fn resolve_lua_modules(path: &Path, imports: &mut [ExtractedImport]) {
+    static CACHE: MtimeCache<bool> = OnceLock::new();
+
+    // Scripts are started from the project root, which is the requiring
+    // file's directory or one above it
+    let dirs = dirs_to_root(path, &CACHE, |name| {
+        name == ".luarc.json" || name.ends_with(".rockspec")
+    });
+    for import in imports {
+        let module: PathBuf = import.path.split('.').collect();
+        let candidates = dirs
+            .iter()
+            .flat_map(|dir| {
+                let base = dir.join(&module);
+                [base.with_extension("lua"), base.join("init.lua")]
+            })
+            .collect();
+        resolve_first(import, candidates, Path::is_file);
+    }
+}
+
+fn resolve_proto_imports(path: &Path, imports: &mut [ExtractedImport]) {
+    static CACHE: MtimeCache<bool> = OnceLock::new();
+
+    let dirs = dirs_to_root(path, &CACHE, |name| name == "buf.yaml");
+    for import in imports {
+        let candidates = dirs.iter().map(|dir| dir.join(&import.path)).collect();
+        resolve_first(import, candidates, Path::is_file);
+    }
+}
+
+/// Extensions a JavaScript/TypeScript specifier may leave off, in the
+/// order bundlers try them.
+const SCRIPT_EXTENSIONS: &[&str] = &["ts", "tsx", "d.ts", "js", "jsx", "mjs", "cjs"];
+
+fn resolve_relative_specifiers(path: &Path, imports: &mut [ExtractedImport]) {
+    let Some(dir) = path.parent() else {
+        return;
+    };
+    for import in imports {
+        if !import.path.starts_with("./") && !import.path.starts_with("../") && import.path != "." {
+            continue;
+        }
+        let base = normalize(&dir.join(&import.path));
+        // The specifier as written, when it names a script (`./util.js`)
+        let mut candidates = Vec::new();
+        if base
+            .extension()
+            .is_some_and(|ext| SCRIPT_EXTENSIONS.iter().any(|e| ext == *e))
+        {
+            candidates.push(base.clone());
+        }
+        // TypeScript imports its own modules by their compiled `.js` name
+        if let Some(stem) = import.path.strip_suffix(".js") {
+            let stem = normalize(&dir.join(stem));
+            candidates.extend(["ts", "tsx"].iter().map(|ext| stem.with_extension(ext)));
+        }
+        let file_name = base
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default();
+        candidates.extend(
+            SCRIPT_EXTENSIONS
+                .iter()
+                .map(|ext| base.with_file_name(format!("{}.{}", file_name, ext))),
+        );
+        candidates.extend(
+            SCRIPT_EXTENSIONS
+                .iter()
+                .map(|ext| base.join(format!("index.{}", ext))),
+        );
+        resolve_first(import, candidates, Path::is_file);
+    }
+}
+
+/// Include directories from a compilation database.
+#[derive(Debug, Default)]
+struct CompileDb {
+    /// Source file -> its include directories, in command-line order.
+    by_file: HashMap<PathBuf, Vec<PathBuf>>,
+    /// Every include directory in the database, for files it doesn't
+    /// list (headers).
+    all: Vec<PathBuf>,
+}
+
+impl CompileDb {
+    fn parse(contents: &str) -> Option<Self> {
+        let entries: Vec<serde_json::Value> = serde_json::from_str(contents).ok()?;
+        let mut db = CompileDb::default();
+        for entry in entries {
+            let directory = PathBuf::from(entry["directory"].as_str().unwrap_or(""));
+            let Some(file) = entry["file"].as_str() else {
+                continue;
+            };
+            let args: Vec<String> = match entry["arguments"].as_array() {
+                Some(args) => args
+                    .iter()
+                    .filter_map(|a| Some(a.as_str()?.to_string()))
+                    .collect(),
+                None => entry["command"]
+                    .as_str()
+                    .unwrap_or("")
+                    .split_whitespace()
+                    .map(str::to_string)
+                    .collect(),
+            };
+
+            let mut dirs = Vec::new();
+            let mut args = args.iter();
+            while let Some(arg) = args.next() {
+                let dir = match arg.as_str() {
+                    "-I" | "-iquote" | "-isystem" => args.next().map(String::as_str),
+                    _ => ["-I", "-iquote", "-isystem"]
+                        .iter()
+                        .find_map(|flag| arg.strip_prefix(flag))
+                        .filter(|dir| !dir.is_empty()),
+                };
+                if let Some(dir) = dir {
+                    dirs.push(normalize(&directory.join(dir)));
+                }
+            }
+            for dir in &dirs {
+                if !db.all.contains(dir) {
+                    db.all.push(dir.clone());
+                }
+            }
+            db.by_file.insert(normalize(&directory.join(file)), dirs);
+        }
+        Some(db)
+    }
+
+    fn include_dirs(&self, path: &Path) -> &[PathBuf] {
+        self.by_file.get(path).unwrap_or(&self.all)
+    }
+}
+
+/// The nearest compilation database above `path`, parsed once per
+/// modification.
+fn find_compile_db(path: &Path) -> Option<Arc<CompileDb>> {
+    static CACHE: MtimeCache<Arc<CompileDb>> = OnceLock::new();
+
+    let db_path = path.ancestors().skip(1).find_map(|dir| {
+        [
+            dir.join("compile_commands.json"),
+            dir.join("build/compile_commands.json"),
+        ]
+        .into_iter()
+        .find(|candidate| candidate.is_file())
+    })?;
+    cached(&CACHE, &db_path, |db_path| {
+        Some(Arc::new(CompileDb::parse(
+            &fs::read_to_string(db_path).ok()?,
+        )?))
+    })
+}
+
+/// The directories from `path`'s up to the nearest project root: one
+/// holding a `.git` or `.anchor` directory, or a file `is_root` accepts
+/// by name. Each directory is listed once per modification. Just
+/// `path`'s directory when no root is above it.
+fn dirs_to_root(path: &Path, cache: &MtimeCache<bool>, is_root: fn(&str) -> bool) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+    for dir in path.ancestors().skip(1) {
+        dirs.push(dir.to_path_buf());
+        let found = cached(cache, dir, |dir| {
+            let listing = fs::read_dir(dir).ok()?;
+            Some(listing.flatten().any(|entry| {
+                let name = entry.file_name();
+                let name = name.to_string_lossy();
+                name == ".git" || name == ".anchor" || is_root(&name)
+            }))
+        });
+        if found.unwrap_or(false) {
+            return dirs;
+        }
+    }
+    dirs.truncate(1);
+    dirs
+}
+
+/// Values derived from a file or directory, keyed by its path and
+/// stamped with its modification time.
+type MtimeCache<T> = OnceLock<Mutex<HashMap<PathBuf, (SystemTime, T)>>>;
+
+/// `load(path)`, reused from `cache` until `path` is modified.
+fn cached<T: Clone>(
+    cache: &MtimeCache<T>,
+    path: &Path,
+    load: impl FnOnce(&Path) -> Option<T>,
+) -> Option<T> {
+    let modified = fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    let mut cache = cache.get_or_init(Mutex::default).lock().ok()?;
+    if let Some((cached_at, value)) = cache.get(path) {
+        if *cached_at == modified {
+            return Some(value.clone());
+        }
+    }
+    let value = load(path)?;
+    cache.insert(path.to_path_buf(), (modified, value.clone()));
+    Some(value)
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            other => normalized.push(other),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tempfile::tempdir;
+
+    fn import(path: &str) -> ExtractedImport {
+        ExtractedImport {
+            path: path.to_string(),
+            symbols: Vec::new(),
+            line: 1,
+            resolved: None,
+            candidates: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_go_imports_resolve_against_go_mod() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("go.mod"),
+            "module github.com/acme/app\n\ngo 1.22\n",
+        )
+        .unwrap();
+
+        let mut imports = vec![
+            import("fmt"),
+            import("github.com/acme/app/internal/auth"),
+            import("github.com/acme/application"),
+        ];
+        resolve_imports(
+            &root.join("cmd/main.go"),
+            SupportedLanguage::Go,
+            &mut imports,
+        );
+        assert_eq!(imports[0].resolved, None);
+        assert_eq!(imports[1].resolved, Some(root.join("internal/auth")));
+        assert_eq!(imports[2].resolved, None);
+    }
+
+    #[test]
+    fn test_c_includes_resolve_against_compile_commands() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("include/geo")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("build")).unwrap();
+        fs::write(root.join("include/geo/shape.h"), "").unwrap();
+        fs::write(root.join("src/local.h"), "").unwrap();
+        fs::write(
+            root.join("build/compile_commands.json"),
+            serde_json::json!([{
+                "directory": root.join("build"),
+                "file": "../src/shape.cpp",
+                "command": "c++ -I../include -Wall -c ../src/shape.cpp"
+            }])
+            .to_string(),
+        )
+        .unwrap();
+
+        let mut imports = vec![import("geo/shape.h"), import("local.h"), import("vector")];
+        resolve_imports(
+            &root.join("src/shape.cpp"),
+            SupportedLanguage::Cpp,
+            &mut imports,
+        );
+        assert_eq!(imports[0].resolved, Some(root.join("include/geo/shape.h")));
+        assert_eq!(imports[1].resolved, Some(root.join("src/local.h")));
+        assert_eq!(imports[2].resolved, None);
+    }
+
+    #[test]
+    fn test_ruby_requires_resolve_against_lib_and_rails_autoload() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("app/models")).unwrap();
+        fs::create_dir_all(root.join("app/controllers/admin")).unwrap();
+        fs::create_dir_all(root.join("config")).unwrap();
+        fs::create_dir_all(root.join("lib/billing")).unwrap();
+        fs::write(root.join("Gemfile"), "source 'https://rubygems.org'\n").unwrap();
+        fs::write(root.join("config/application.rb"), "").unwrap();
+        fs::write(root.join("app/models/user.rb"), "").unwrap();
+        fs::write(root.join("app/controllers/helpers.rb"), "").unwrap();
+        fs::write(root.join("app/controllers/admin/users_controller.rb"), "").unwrap();
+        fs::write(root.join("lib/billing/invoice.rb"), "").unwrap();
+
+        let mut imports = vec![
+            import("helpers"),
+            import("billing/invoice"),
+            import("user"),
+            import("json"),
+            import("User"),
+            import("Admin::UsersController"),
+            import("JSON"),
+        ];
+        let controller = root.join("app/controllers/users_controller.rb");
+        resolve_imports(&controller, SupportedLanguage::Ruby, &mut imports);
+        assert_eq!(
+            imports[0].resolved,
+            Some(root.join("app/controllers/helpers.rb"))
+        );
+        assert_eq!(
+            imports[1].resolved,
+            Some(root.join("lib/billing/invoice.rb"))
+        );
+        assert_eq!(imports[2].resolved, Some(root.join("app/models/user.rb")));
+        assert_eq!(imports[3].resolved, None);
+
+        // Constants resolve to the file Rails autoloads them from
+        assert_eq!(imports[4].resolved, Some(root.join("app/models/user.rb")));
+        assert_eq!(
+            imports[5].resolved,
+            Some(root.join("app/controllers/admin/users_controller.rb"))
+        );
+        assert_eq!(imports[6].resolved, None);
+    }
+
+    #[test]
+    fn test_php_uses_resolve_through_psr4() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/Models")).unwrap();
+        fs::create_dir_all(root.join("src/Contracts")).unwrap();
+        fs::create_dir_all(root.join("tests/Support")).unwrap();
+        fs::write(root.join("src/Models/User.php"), "").unwrap();
+        fs::write(root.join("tests/Support/Factory.php"), "").unwrap();
+        fs::write(
+            root.join("composer.json"),
+            serde_json::json!({
+                "autoload": {"psr-4": {"App\\": "src/"}},
+                "autoload-dev": {"psr-4": {"Tests\\": ["tests/"]}}
+            })
+            .to_string(),
+        )
+        .unwrap();
+
+        let mut imports = vec![
+            import("App\\Models\\User"),
+            import("App\\Contracts"),
+            import("\\Tests\\Support\\Factory"),
+            import("Psr\\Log\\LoggerInterface"),
+        ];
+        resolve_imports(
+            &root.join("src/Billing/Invoice.php"),
+            SupportedLanguage::Php,
+            &mut imports,
+        );
+        assert_eq!(imports[0].resolved, Some(root.join("src/Models/User.php")));
+        assert_eq!(imports[1].resolved, Some(root.join("src/Contracts")));
+        assert_eq!(
+            imports[2].resolved,
+            Some(root.join("tests/Support/Factory.php"))
+        );
+        assert_eq!(imports[3].resolved, None);
+
+        // The parsed prefixes are reused until composer.json changes
+        let composer = root.join("composer.json");
+        fs::write(
+            &composer,
+            r#"{"autoload": {"psr-4": {"Domain\\": "src/"}}}"#,
+        )
+        .unwrap();
+        let later = fs::metadata(&composer).unwrap().modified().unwrap() + Duration::from_secs(1);
+        fs::File::options()
+            .write(true)
+            .open(&composer)
+            .unwrap()
+            .set_modified(later)
+            .unwrap();
+        let mut imports = vec![import("Domain\\Models\\User"), import("App\\Models\\User")];
+        resolve_imports(
+            &root.join("src/Billing/Invoice.php"),
+            SupportedLanguage::Php,
+            &mut imports,
+        );
+        assert_eq!(imports[0].resolved, Some(root.join("src/Models/User.php")));
+        assert_eq!(imports[1].resolved, None);
+    }
+
+    #[test]
+    fn test_bash_sources_resolve_against_script_dir() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("scripts/lib")).unwrap();
+        fs::write(root.join("scripts/lib/common.sh"), "").unwrap();
+        fs::write(root.join("scripts/env.sh"), "").unwrap();
+
+        let mut imports = vec![
+            import("./lib/common.sh"),
+            import("$(dirname \"$0\")/env.sh"),
+            import("$HOME/.env"),
+        ];
+        resolve_imports(
+            &root.join("scripts/deploy.sh"),
+            SupportedLanguage::Bash,
+            &mut imports,
+        );
+        assert_eq!(
+            imports[0].resolved,
+            Some(root.join("scripts/lib/common.sh"))
+        );
+        assert_eq!(imports[1].resolved, Some(root.join("scripts/env.sh")));
+        assert_eq!(imports[2].resolved, None);
+    }
+
+    #[test]
+    fn test_elixir_modules_resolve_to_mix_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("lib/my_app/accounts")).unwrap();
+        fs::write(root.join("mix.exs"), "").unwrap();
+        fs::write(root.join("lib/my_app/accounts/user.ex"), "").unwrap();
+        fs::write(root.join("lib/my_app/http_client.ex"), "").unwrap();
+
+        let mut grouped = import("MyApp");
+        grouped.symbols = vec!["Repo".to_string(), "Mailer".to_string()];
+        let mut imports = vec![
+            import("MyApp.Accounts.User"),
+            import("MyApp.HTTPClient"),
+            grouped,
+            import("Ecto.Query"),
+        ];
+        resolve_imports(
+            &root.join("lib/my_app/billing.ex"),
+            SupportedLanguage::Elixir,
+            &mut imports,
+        );
+        assert_eq!(
+            imports[0].resolved,
+            Some(root.join("lib/my_app/accounts/user.ex"))
+        );
+        assert_eq!(
+            imports[1].resolved,
+            Some(root.join("lib/my_app/http_client.ex"))
+        );
+        assert_eq!(imports[2].resolved, Some(root.join("lib/my_app")));
+        assert_eq!(imports[3].resolved, None);
+    }
+
+    #[test]
+    fn test_lua_requires_resolve_like_package_path() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("game");
+        fs::create_dir_all(root.join("geo/shapes")).unwrap();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::write(root.join("game-1.0-1.rockspec"), "").unwrap();
+        fs::write(root.join("geo/point.lua"), "").unwrap();
+        fs::write(root.join("geo/shapes/init.lua"), "").unwrap();
+        // Outside the project
+        fs::write(dir.path().join("json.lua"), "").unwrap();
+
+        let mut imports = vec![import("geo.point"), import("geo.shapes"), import("json")];
+        resolve_imports(
+            &root.join("src/main.lua"),
+            SupportedLanguage::Lua,
+            &mut imports,
+        );
+        assert_eq!(imports[0].resolved, Some(root.join("geo/point.lua")));
+        assert_eq!(imports[1].resolved, Some(root.join("geo/shapes/init.lua")));
+        assert_eq!(imports[2].resolved, None);
+        assert_eq!(
+            imports[2].candidates,
+            vec![
+                root.join("src/json.lua"),
+                root.join("src/json/init.lua"),
+                root.join("json.lua"),
+                root.join("json/init.lua"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_proto_imports_resolve_within_buf_module() {
+        let dir = tempdir().unwrap();
+        let root = dir.path().join("proto");
+        fs::create_dir_all(root.join("acme/users")).unwrap();
+        fs::write(root.join("buf.yaml"), "version: v1\n").unwrap();
+        fs::write(root.join("acme/common.proto"), "").unwrap();
+        // Outside the module
+        fs::create_dir_all(dir.path().join("google/protobuf")).unwrap();
+        fs::write(dir.path().join("google/protobuf/empty.proto"), "").unwrap();
+
+        let mut imports = vec![
+            import("acme/common.proto"),
+            import("google/protobuf/empty.proto"),
+        ];
+        resolve_imports(
+            &root.join("acme/users/users.proto"),
+            SupportedLanguage::Protobuf,
+            &mut imports,
+        );
+        assert_eq!(imports[0].resolved, Some(root.join("acme/common.proto")));
+        assert_eq!(imports[1].resolved, None);
+        assert_eq!(imports[1].candidates.len(), 3);
+    }
+
+    #[test]
+    fn test_python_relative_imports_resolve_against_package() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("app/core")).unwrap();
+        fs::create_dir_all(root.join("app/users")).unwrap();
+        fs::write(root.join("app/core/__init__.py"), "").unwrap();
+        fs::write(root.join("app/users/db.py"), "").unwrap();
+        fs::write(root.join("app/users/base.py"), "").unwrap();
+        fs::write(root.join("app/users/__init__.py"), "").unwrap();
+
+        let mut db = import(".");
+        db.symbols = vec!["db".to_string()];
+        let mut names = import(".");
+        names.symbols = vec!["User".to_string(), "Account".to_string()];
+        let mut imports = vec![
+            db,
+            names,
+            import(".base"),
+            import("..core"),
+            import("os.path"),
+        ];
+        resolve_imports(
+            &root.join("app/users/models.py"),
+            SupportedLanguage::Python,
+            &mut imports,
+        );
+        assert_eq!(imports[0].resolved, Some(root.join("app/users/db.py")));
+        assert_eq!(
+            imports[1].resolved,
+            Some(root.join("app/users/__init__.py"))
+        );
+        assert_eq!(imports[2].resolved, Some(root.join("app/users/base.py")));
+        assert_eq!(imports[3].resolved, Some(root.join("app/core/__init__.py")));
+        assert_eq!(imports[4].resolved, None);
+    }
+
+    #[test]
+    fn test_script_specifiers_resolve_with_extensions_and_index_files() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src/ui")).unwrap();
+        fs::write(root.join("src/button.tsx"), "").unwrap();
+        fs::write(root.join("src/ui/index.ts"), "").unwrap();
+        fs::write(root.join("src/util.ts"), "").unwrap();
+        fs::write(root.join("lib.js"), "").unwrap();
+
+        let mut imports = vec![
+            import("./button"),
+            import("./ui"),
+            import("./util.js"),
+            import("../lib"),
+            import("react"),
+        ];
+        resolve_imports(
+            &root.join("src/app.tsx"),
+            SupportedLanguage::Tsx,
+            &mut imports,
+        );
+        assert_eq!(imports[0].resolved, Some(root.join("src/button.tsx")));
+        assert_eq!(imports[1].resolved, Some(root.join("src/ui/index.ts")));
+        assert_eq!(imports[2].resolved, Some(root.join("src/util.ts")));
+        assert_eq!(imports[3].resolved, Some(root.join("lib.js")));
+        assert_eq!(imports[4].resolved, None);
+
+        // Every path tried is kept, so creating `src/button.ts` later
+        // relinks the import to it
+        assert_eq!(imports[0].candidates[0], root.join("src/button.ts"));
+        assert!(imports[0].candidates.contains(&root.join("src/button.tsx")));
+        assert!(imports[4].candidates.is_empty());
+    }
+}
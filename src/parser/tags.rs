@@ -0,0 +1,372 @@
+//! Declarative extraction rules — tree-sitter tag queries.
+//!
+//! Each language ships a `.scm` query (see `src/parser/tags/`) that says
+//! what to extract, using these captures:
+//! - `@definition.<kind>` — a symbol definition, with `@name` for its name.
+//!   Kinds: `function`, `method`, `class`, `struct`, `enum`, `interface`,
+//!   `trait`, `impl`, `module`, `constant`, `variable`, `type`, `macro`. An
+//!   optional `@parent` names the definition's container explicitly
+//!   (e.g. a Go method's receiver type) instead of the enclosing scope
+//! - `@scope` — a node that becomes the parent of definitions and the
+//!   caller of calls nested inside it, named by `@scope.name` or `@name`
+//! - `@import` — an import, with `@import.path` and any `@import.symbol`s.
+//!   A path captured in pieces (Scala `import a.b.c`) spans all of them
+//! - `@call` — a call site, with `@call.name` for the callee. Without
+//!   `@call.name` it marks a node that only parses as a call (an Elixir
+//!   function head), overriding later `@call` patterns
+//! - `@relation.<kind>` — a type the match's `@name` points at, or the
+//!   enclosing scope (or file) without `@name`. Kinds: `extends`,
+//!   `implements`, `references`, and `exports` for a name the file itself
+//!   exports (Python `__all__`). An export of an `@import.path` re-exports
+//!   the module the import resolves to (`export * from './util'`)
+//! - `@reference.<kind>` — a usage of a named symbol other than a call,
+//!   attributed to the enclosing scope (or file). Kinds: `read` and
+//!   `write` of a constant, variable or field, and `type` (annotations,
+//!   generic arguments), `check` (`instanceof`) and `construct` (struct
+//!   literals) of a type. Definition names and callees are never usages
+//! - `@decorator` — a decorator on the match's definition, kept with the
+//!   symbol (Python `@property`)
+//! - `@package` — marks a file-level namespace definition (Java/Go
+//!   `package`, C# `namespace X;`) that prefixes the qualified names of
+//!   the definitions after it
+//!
+//! Captures starting with `_` are free for use in predicates. A project
+//! can add patterns to a language's query with
+//! `.anchor/queries/<language>.scm`; a file whose first line is
+//! `; anchor: replace` is used instead of the bundled query.
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+use tree_sitter::Query;
+
+use super::language::SupportedLanguage;
+use crate::error::{AnchorError, Result};
+use crate::graph::types::{EdgeKind, NodeKind, ReferenceKind};
+
+/// What a capture in a tag query stands for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum CaptureRole {
+    Definition(NodeKind),
+    Name,
+    Parent,
+    Scope,
+    ScopeName,
+    Import,
+    ImportPath,
+    ImportSymbol,
+    Call,
+    CallName,
+    Relation(EdgeKind),
+    Reference(ReferenceKind),
+    Package,
+    Decorator,
+    /// `_`-prefixed helper capture, only used by predicates.
+    Ignored,
+}
+
+impl CaptureRole {
+    fn parse(name: &str) -> Option<Self> {
+        let role = match name {
+            "name" => CaptureRole::Name,
+            "parent" => CaptureRole::Parent,
+            "scope" => CaptureRole::Scope,
+            "scope.name" => CaptureRole::ScopeName,
+            "import" => CaptureRole::Import,
+            "import.path" => CaptureRole::ImportPath,
+            "import.symbol" => CaptureRole::ImportSymbol,
+            "call" => CaptureRole::Call,
+            "call.name" => CaptureRole::CallName,
+            "package" => CaptureRole::Package,
+            "decorator" => CaptureRole::Decorator,
+            _ if name.starts_with('_') => CaptureRole::Ignored,
+            _ => {
+                if let Some(kind) = name.strip_prefix("relation.") {
+                    CaptureRole::Relation(relation_kind(kind)?)
+                } else if let Some(kind) = name.strip_prefix("reference.") {
+                    CaptureRole::Reference(reference_kind(kind)?)
+                } else {
+                    CaptureRole::Definition(definition_kind(name.strip_prefix("definition.")?)?)
+                }
+            }
+        };
+        Some(role)
+    }
+}
+
+/// Map a `@definition.<kind>` suffix to a node kind.
+fn definition_kind(kind: &str) -> Option<NodeKind> {
+    Some(match kind {
+        "function" => NodeKind::Function,
+        "method" => NodeKind::Method,
+        "class" => NodeKind::Class,
+        "struct" => NodeKind::Struct,
+        "enum" => NodeKind::Enum,
+        "interface" => NodeKind::Interface,
+        "trait" => NodeKind::Trait,
+        "impl" => NodeKind::Impl,
+        "module" => NodeKind::Module,
+        "constant" => NodeKind::Constant,
+        "variable" => NodeKind::Variable,
+        "type" => NodeKind::Type,
+        "macro" => NodeKind::Macro,
+        _ => return None,
+    })
+}
+
+/// Map a `@relation.<kind>` suffix to an edge kind.
+fn relation_kind(kind: &str) -> Option<EdgeKind> {
+    Some(match kind {
+        "extends" => EdgeKind::Extends,
+        "implements" => EdgeKind::Implements,
+        "references" => EdgeKind::References,
+        "exports" => EdgeKind::Exports,
+        _ => return None,
+    })
+}
+
+/// Map a `@reference.<kind>` suffix to a reference kind.
+fn reference_kind(kind: &str) -> Option<ReferenceKind> {
+    Some(match kind {
+        "read" => ReferenceKind::Read,
+        "write" => ReferenceKind::Write,
+        "type" => ReferenceKind::Type,
+        "check" => ReferenceKind::TypeCheck,
+        "construct" => ReferenceKind::Construct,
+        _ => return None,
+    })
+}
+
+/// Generations handed out to compiled queries.
+static GENERATIONS: AtomicU64 = AtomicU64::new(0);
+
+/// A compiled tag query for one language.
+pub struct TagQuery {
+    language: SupportedLanguage,
+    query: Query,
+    roles: Vec<CaptureRole>,
+    /// Unique to this compilation: what was extracted with another query
+    /// (e.g. before overrides were reloaded) is stale.
+    generation: u64,
+}
+
+impl TagQuery {
+    /// Compile `source` against `language`'s grammar. Fails on syntax
+    /// errors, unknown node kinds and unknown capture names.
+    pub fn new(language: SupportedLanguage, source: &str) -> std::result::Result<Self, String> {
+        let query =
+            Query::new(&language.tree_sitter_language(), source).map_err(|e| e.to_string())?;
+        let roles = query
+            .capture_names()
+            .iter()
+            .map(|name| {
+                CaptureRole::parse(name).ok_or_else(|| format!("unknown capture @{}", name))
+            })
+            .collect::<std::result::Result<_, _>>()?;
+        Ok(Self {
+            language,
+            query,
+            roles,
+            generation: GENERATIONS.fetch_add(1, Ordering::Relaxed),
+        })
+    }
+
+    /// The query bundled with Anchor for a language.
+    pub fn bundled(language: SupportedLanguage) -> &'static TagQuery {
+        static BUNDLED: [OnceLock<TagQuery>; SupportedLanguage::ALL.len()] =
+            [const { OnceLock::new() }; SupportedLanguage::ALL.len()];
+
+        let slot = SupportedLanguage::ALL
+            .iter()
+            .position(|&l| l == language)
+            .expect("language missing from SupportedLanguage::ALL");
+        BUNDLED[slot].get_or_init(|| {
+            TagQuery::new(language, bundled_source(language)).unwrap_or_else(|e| {
+                panic!("bundled {} query is invalid: {}", language.config_name(), e)
+            })
+        })
+    }
+
+    pub fn language(&self) -> SupportedLanguage {
+        self.language
+    }
+
+    pub(crate) fn generation(&self) -> u64 {
+        self.generation
+    }
+
+    pub(crate) fn query(&self) -> &Query {
+        &self.query
+    }
+
+    pub(crate) fn role(&self, capture_index: u32) -> CaptureRole {
+        self.roles[capture_index as usize]
+    }
+}
+
+/// Source of the bundled query for a language.
+pub fn bundled_source(language: SupportedLanguage) -> &'static str {
+    match language {
+        SupportedLanguage::Rust => include_str!("tags/rust.scm"),
+        SupportedLanguage::Python => include_str!("tags/python.scm"),
+        SupportedLanguage::JavaScript => concat!(
+            include_str!("tags/javascript.scm"),
+            include_str!("tags/javascript-classes.scm"),
+            include_str!("tags/jsx.scm")
+        ),
+        SupportedLanguage::TypeScript => {
+            concat!(
+                include_str!("tags/javascript.scm"),
+                include_str!("tags/typescript.scm")
+            )
+        }
+        SupportedLanguage::Tsx => concat!(
+            include_str!("tags/javascript.scm"),
+            include_str!("tags/typescript.scm"),
+            include_str!("tags/jsx.scm")
+        ),
+        SupportedLanguage::Go => include_str!("tags/go.scm"),
+        SupportedLanguage::Java => include_str!("tags/java.scm"),
+        SupportedLanguage::CSharp => include_str!("tags/csharp.scm"),
+        SupportedLanguage::Ruby => include_str!("tags/ruby.scm"),
+        SupportedLanguage::Kotlin => include_str!("tags/kotlin.scm"),
+        SupportedLanguage::Scala => include_str!("tags/scala.scm"),
+        SupportedLanguage::C => include_str!("tags/c.scm"),
+        SupportedLanguage::Cpp => concat!(include_str!("tags/c.scm"), include_str!("tags/cpp.scm")),
+        SupportedLanguage::Swift => include_str!("tags/swift.scm"),
+        SupportedLanguage::Php => include_str!("tags/php.scm"),
+        SupportedLanguage::Bash => include_str!("tags/bash.scm"),
+        SupportedLanguage::Elixir => include_str!("tags/elixir.scm"),
+        SupportedLanguage::Lua => include_str!("tags/lua.scm"),
+        SupportedLanguage::Sql => include_str!("tags/sql.scm"),
+        SupportedLanguage::Protobuf => include_str!("tags/proto.scm"),
+        SupportedLanguage::GraphQl => include_str!("tags/graphql.scm"),
+    }
+}
+
+/// First line of an override that replaces the bundled query instead of
+/// extending it.
+pub const REPLACE_DIRECTIVE: &str = "; anchor: replace";
+
+/// Compile an override `source`, on top of the bundled query unless it
+/// opts out with [`REPLACE_DIRECTIVE`].
+fn override_query(
+    language: SupportedLanguage,
+    source: &str,
+) -> std::result::Result<TagQuery, String> {
+    if source.lines().next().map(str::trim) == Some(REPLACE_DIRECTIVE) {
+        return TagQuery::new(language, source);
+    }
+    let combined = format!("{}\n{}", bundled_source(language), source);
+    // Report errors against the override alone, whose positions are the
+    // ones its author can find.
+    TagQuery::new(language, &combined)
+        .map_err(|e| TagQuery::new(language, source).err().unwrap_or(e))
+}
+
+/// The tag queries in effect for a project: bundled ones, extended or
+/// replaced by any project overrides.
+#[derive(Clone, Default)]
+pub struct TagQueries {
+    overrides: HashMap<SupportedLanguage, Arc<TagQuery>>,
+}
+
+impl TagQueries {
+    /// Load overrides from a directory of `<language>.scm` files. An
+    /// override's patterns are appended to the bundled query, so they
+    /// match after the bundled ones, unless its first line is
+    /// [`REPLACE_DIRECTIVE`]. A missing directory means no overrides; a
+    /// query that fails to compile is an error.
+    pub fn load(dir: &Path) -> Result<Self> {
+        let mut overrides = HashMap::new();
+        for &language in SupportedLanguage::ALL {
+            let path = dir.join(format!("{}.scm", language.config_name()));
+            let source = match std::fs::read_to_string(&path) {
+                Ok(source) => source,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e.into()),
+            };
+            let query = override_query(language, &source)
+                .map_err(|e| AnchorError::QueryError(path.clone(), e))?;
+            overrides.insert(language, Arc::new(query));
+        }
+        Ok(Self { overrides })
+    }
+
+    /// The query to extract `language` with.
+    pub fn get(&self, language: SupportedLanguage) -> &TagQuery {
+        match self.overrides.get(&language) {
+            Some(query) => query,
+            None => TagQuery::bundled(language),
+        }
+    }
+
+    /// Languages whose bundled query is overridden.
+    pub fn overridden(&self) -> Vec<SupportedLanguage> {
+        let mut languages: Vec<_> = self.overrides.keys().copied().collect();
+        languages.sort_by_key(|l| l.config_name());
+        languages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_bundled_queries_compile() {
+        for &language in SupportedLanguage::ALL {
+            assert_eq!(TagQuery::bundled(language).language(), language);
+        }
+    }
+
+    #[test]
+    fn test_unknown_capture_is_rejected() {
+        let err = TagQuery::new(
+            SupportedLanguage::Rust,
+            "(function_item) @definition.banana",
+        )
+        .err()
+        .unwrap();
+        assert!(err.contains("definition.banana"));
+    }
+
+    #[test]
+    fn test_overrides_load_from_dir() {
+        let dir = tempdir().unwrap();
+        let pattern = "(class_definition name: (identifier) @name) @definition.class";
+        let bundled = TagQuery::bundled(SupportedLanguage::Python)
+            .query()
+            .pattern_count();
+        std::fs::write(dir.path().join("python.scm"), pattern).unwrap();
+        let queries = TagQueries::load(dir.path()).unwrap();
+        assert_eq!(queries.overridden(), vec![SupportedLanguage::Python]);
+        assert_eq!(
+            queries
+                .get(SupportedLanguage::Python)
+                .query()
+                .pattern_count(),
+            bundled + 1
+        );
+
+        std::fs::write(
+            dir.path().join("python.scm"),
+            format!("{}\n{}", REPLACE_DIRECTIVE, pattern),
+        )
+        .unwrap();
+        let queries = TagQueries::load(dir.path()).unwrap();
+        assert_eq!(
+            queries
+                .get(SupportedLanguage::Python)
+                .query()
+                .pattern_count(),
+            1
+        );
+
+        std::fs::write(dir.path().join("rust.scm"), "(no_such_node) @call").unwrap();
+        let err = TagQueries::load(dir.path()).err().unwrap();
+        assert!(matches!(err, AnchorError::QueryError(..)));
+    }
+}
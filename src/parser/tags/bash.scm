@@ -0,0 +1,14 @@
+; Bash tag query.
+
+(function_definition name: (word) @name) @definition.function @scope
+
+; `source lib.sh` and `. lib.sh`
+(command
+  name: (command_name (word) @_source)
+  .
+  argument: [(word) (string) (raw_string) (concatenation)] @import.path
+  (#any-of? @_source "source" ".")) @import
+
+(command
+  name: (command_name (word) @call.name)
+  (#not-any-of? @call.name "source" ".")) @call
@@ -0,0 +1,84 @@
+; C tag query. The C++ query extends this one.
+
+; Function definitions, possibly returning pointers
+(function_definition
+  declarator: [
+    (function_declarator declarator: (identifier) @name)
+    (pointer_declarator declarator: (function_declarator declarator: (identifier) @name))
+    (pointer_declarator declarator: (pointer_declarator declarator: (function_declarator declarator: (identifier) @name)))
+  ]) @definition.function @scope
+
+; Prototypes are functions too, and reference their definition
+(declaration
+  declarator: [
+    (function_declarator declarator: (identifier) @name @relation.references)
+    (pointer_declarator declarator: (function_declarator declarator: (identifier) @name @relation.references))
+    (pointer_declarator declarator: (pointer_declarator declarator: (function_declarator declarator: (identifier) @name @relation.references)))
+  ]) @definition.function
+
+(struct_specifier name: (type_identifier) @name body: (_)) @definition.struct @scope
+(union_specifier name: (type_identifier) @name body: (_)) @definition.struct @scope
+(enum_specifier name: (type_identifier) @name body: (_)) @definition.enum @scope
+(enumerator name: (identifier) @name) @definition.constant
+(type_definition declarator: (type_identifier) @name) @definition.type
+
+(field_declaration
+  declarator: [
+    (field_identifier) @name
+    (pointer_declarator declarator: (field_identifier) @name)
+    (array_declarator declarator: (field_identifier) @name)
+  ]) @definition.variable
+
+; Globals: declarations at file level (or inside include guards)
+(translation_unit
+  (declaration
+    declarator: [
+      (identifier) @name
+      (init_declarator declarator: (identifier) @name)
+      (pointer_declarator declarator: (identifier) @name)
+      (init_declarator declarator: (pointer_declarator declarator: (identifier) @name))
+      (array_declarator declarator: (identifier) @name)
+      (init_declarator declarator: (array_declarator declarator: (identifier) @name))
+    ]) @definition.variable)
+(preproc_ifdef
+  (declaration
+    declarator: [
+      (identifier) @name
+      (init_declarator declarator: (identifier) @name)
+      (pointer_declarator declarator: (identifier) @name)
+      (init_declarator declarator: (pointer_declarator declarator: (identifier) @name))
+      (array_declarator declarator: (identifier) @name)
+      (init_declarator declarator: (array_declarator declarator: (identifier) @name))
+    ]) @definition.variable)
+(preproc_if
+  (declaration
+    declarator: [
+      (identifier) @name
+      (init_declarator declarator: (identifier) @name)
+      (pointer_declarator declarator: (identifier) @name)
+      (init_declarator declarator: (pointer_declarator declarator: (identifier) @name))
+      (array_declarator declarator: (identifier) @name)
+      (init_declarator declarator: (array_declarator declarator: (identifier) @name))
+    ]) @definition.variable)
+(preproc_else
+  (declaration
+    declarator: [
+      (identifier) @name
+      (init_declarator declarator: (identifier) @name)
+      (pointer_declarator declarator: (identifier) @name)
+      (init_declarator declarator: (pointer_declarator declarator: (identifier) @name))
+      (array_declarator declarator: (identifier) @name)
+      (init_declarator declarator: (array_declarator declarator: (identifier) @name))
+    ]) @definition.variable)
+
+; Macros with a value (include guards are skipped)
+(preproc_def name: (identifier) @name value: (_)) @definition.macro
+(preproc_function_def name: (identifier) @name) @definition.macro
+
+(preproc_include path: (_) @import.path) @import
+
+(call_expression
+  function: [
+    (identifier) @call.name
+    (field_expression field: (_) @call.name)
+  ]) @call
@@ -0,0 +1,45 @@
+; C++ tag query, appended to the C query.
+
+(namespace_definition name: (_) @name) @definition.module @scope
+
+(class_specifier name: (type_identifier) @name body: (_)) @definition.class @scope
+(enum_specifier name: (type_identifier) @name body: (_)) @definition.enum @scope
+(alias_declaration name: (type_identifier) @name) @definition.type
+
+; Functions and methods defined in place (references, operators, destructors)
+(function_definition
+  declarator: [
+    (function_declarator declarator: [(field_identifier) (destructor_name) (operator_name)] @name)
+    (pointer_declarator declarator: (function_declarator declarator: [(identifier) (field_identifier)] @name))
+    (reference_declarator (function_declarator declarator: [(identifier) (field_identifier) (operator_name)] @name))
+  ]) @definition.function @scope
+
+; Out-of-line members (`Foo::bar`, `ns::Foo::bar`) belong to their class
+(function_definition
+  declarator: [
+    (function_declarator declarator: (qualified_identifier scope: (_) @parent name: [(identifier) (destructor_name) (operator_name)] @name))
+    (function_declarator declarator: (qualified_identifier name: (qualified_identifier scope: (_) @parent name: [(identifier) (destructor_name) (operator_name)] @name)))
+    (pointer_declarator declarator: (function_declarator declarator: (qualified_identifier scope: (_) @parent name: (identifier) @name)))
+    (reference_declarator (function_declarator declarator: (qualified_identifier scope: (_) @parent name: [(identifier) (operator_name)] @name)))
+  ]) @definition.function @scope
+
+; Member declarations in a class body
+(field_declaration
+  declarator: [
+    (function_declarator declarator: [(field_identifier) (destructor_name) (operator_name)] @name @relation.references)
+    (pointer_declarator declarator: (function_declarator declarator: (field_identifier) @name @relation.references))
+    (reference_declarator (function_declarator declarator: (field_identifier) @name @relation.references))
+  ]) @definition.method
+(declaration
+  declarator: (function_declarator declarator: (destructor_name) @name @relation.references)) @definition.method
+
+(class_specifier name: (type_identifier) @name
+  (base_class_clause [(type_identifier) (qualified_identifier) (template_type)] @relation.extends))
+(struct_specifier name: (type_identifier) @name
+  (base_class_clause [(type_identifier) (qualified_identifier) (template_type)] @relation.extends))
+
+(call_expression
+  function: [
+    (qualified_identifier name: (_) @call.name)
+    (template_function name: (_) @call.name)
+  ]) @call
@@ -0,0 +1,92 @@
+; C# tag query.
+
+(file_scoped_namespace_declaration name: (_) @name) @definition.module @package
+(namespace_declaration name: (_) @name) @definition.module @scope
+
+(class_declaration name: (identifier) @name) @definition.class @scope
+(record_declaration "struct" name: (identifier) @name) @definition.struct @scope
+(record_declaration name: (identifier) @name) @definition.class @scope
+(struct_declaration name: (identifier) @name) @definition.struct @scope
+(interface_declaration name: (identifier) @name) @definition.interface @scope
+(enum_declaration name: (identifier) @name) @definition.enum @scope
+(enum_member_declaration name: (identifier) @name) @definition.constant
+(delegate_declaration name: (identifier) @name) @definition.type
+
+(method_declaration name: (identifier) @name) @definition.method @scope
+(constructor_declaration name: (identifier) @name) @definition.method @scope
+(local_function_statement name: (identifier) @name) @definition.function @scope
+
+(property_declaration name: (identifier) @name) @definition.variable @scope
+(event_declaration name: (identifier) @name) @definition.variable @scope
+(event_field_declaration
+  (variable_declaration (variable_declarator name: (identifier) @name))) @definition.variable
+(field_declaration
+  (modifier "const")
+  (variable_declaration (variable_declarator name: (identifier) @name))) @definition.constant
+(field_declaration
+  (variable_declaration (variable_declarator name: (identifier) @name))) @definition.variable
+
+; Supertypes. A base list doesn't say which base is a class, so by
+; convention an `I`-prefixed name is an interface.
+((class_declaration name: (identifier) @name
+  (base_list [(identifier) (qualified_name) (generic_name)] @relation.implements))
+  (#match? @relation.implements "^([A-Za-z_][A-Za-z0-9_]*\\.)*I[A-Z]"))
+((class_declaration name: (identifier) @name
+  (base_list [(identifier) (qualified_name) (generic_name)] @relation.extends))
+  (#not-match? @relation.extends "^([A-Za-z_][A-Za-z0-9_]*\\.)*I[A-Z]"))
+((record_declaration name: (identifier) @name
+  (base_list [(identifier) (qualified_name) (generic_name)] @relation.implements))
+  (#match? @relation.implements "^([A-Za-z_][A-Za-z0-9_]*\\.)*I[A-Z]"))
+(record_declaration name: (identifier) @name
+  (base_list (primary_constructor_base_type type: (_) @relation.extends)))
+(struct_declaration name: (identifier) @name
+  (base_list (_) @relation.implements))
+(interface_declaration name: (identifier) @name
+  (base_list (_) @relation.extends))
+
+; Attributes reference their attribute class
+([
+  (class_declaration (attribute_list (attribute name: (_) @relation.references)) name: (identifier) @name)
+  (record_declaration (attribute_list (attribute name: (_) @relation.references)) name: (identifier) @name)
+  (struct_declaration (attribute_list (attribute name: (_) @relation.references)) name: (identifier) @name)
+  (interface_declaration (attribute_list (attribute name: (_) @relation.references)) name: (identifier) @name)
+  (enum_declaration (attribute_list (attribute name: (_) @relation.references)) name: (identifier) @name)
+  (method_declaration (attribute_list (attribute name: (_) @relation.references)) name: (identifier) @name)
+  (constructor_declaration (attribute_list (attribute name: (_) @relation.references)) name: (identifier) @name)
+  (property_declaration (attribute_list (attribute name: (_) @relation.references)) name: (identifier) @name)
+  (field_declaration (attribute_list (attribute name: (_) @relation.references))
+    (variable_declaration (variable_declarator name: (identifier) @name)))
+])
+
+; Attributes are kept as decorators: `[HttpGet("{id}")]`, `[Obsolete]`
+(class_declaration (attribute_list (attribute) @decorator) name: (identifier) @name) @definition.class
+(struct_declaration (attribute_list (attribute) @decorator) name: (identifier) @name) @definition.struct
+(interface_declaration (attribute_list (attribute) @decorator) name: (identifier) @name) @definition.interface
+(enum_declaration (attribute_list (attribute) @decorator) name: (identifier) @name) @definition.enum
+(method_declaration (attribute_list (attribute) @decorator) name: (identifier) @name) @definition.method
+(constructor_declaration (attribute_list (attribute) @decorator) name: (identifier) @name) @definition.method
+(property_declaration (attribute_list (attribute) @decorator) name: (identifier) @name) @definition.variable
+
+(using_directive [(identifier) (qualified_name)] @import.path) @import
+
+(invocation_expression function: (_) @call.name) @call
+(object_creation_expression type: (_) @call.name) @call
+
+; Usages other than calls: `is` checks, writes, member and constant reads,
+; and the types of declarations, parameters and generic arguments
+(is_pattern_expression pattern: (constant_pattern (identifier) @reference.check))
+(is_pattern_expression pattern: (declaration_pattern type: (identifier) @reference.check))
+(assignment_expression left: (identifier) @reference.write)
+(assignment_expression left: (member_access_expression name: (identifier) @reference.write))
+(postfix_unary_expression (identifier) @reference.write)
+(prefix_unary_expression (identifier) @reference.write)
+(member_access_expression name: (identifier) @reference.read)
+((identifier) @reference.read
+  (#match? @reference.read "^[A-Z][A-Z0-9_]*$"))
+(variable_declaration type: (identifier) @reference.type)
+(parameter type: (identifier) @reference.type)
+(property_declaration type: (identifier) @reference.type)
+(method_declaration returns: (identifier) @reference.type)
+(generic_name (identifier) @reference.type)
+(type_argument_list (identifier) @reference.type)
+(cast_expression type: (identifier) @reference.type)
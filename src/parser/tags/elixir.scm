@@ -0,0 +1,79 @@
+; Elixir tag query. Definitions are macro calls (`defmodule`, `def`, ...).
+
+(call
+  target: (identifier) @_def
+  (arguments . (alias) @name)
+  (#eq? @_def "defmodule")) @definition.module @scope
+(call
+  target: (identifier) @_def
+  (arguments . (alias) @name)
+  (#eq? @_def "defprotocol")) @definition.interface @scope
+
+; `defimpl Proto, for: Type` belongs to the type
+(call
+  target: (identifier) @_def
+  (arguments
+    (alias) @relation.implements
+    (keywords (pair key: (keyword) @_for value: (alias) @name @parent)))
+  (#eq? @_def "defimpl")
+  (#match? @_for "^for: *$")) @definition.impl @scope
+
+; Function heads parse as calls: `def name(args)`, `def name(args) when guard`
+; and the zero-arity `def name`
+(call
+  target: (identifier) @_def
+  (arguments .
+    [(call target: (identifier) @name) @call
+     (binary_operator left: (call target: (identifier) @name) @call operator: "when")
+     (identifier) @name])
+  (#any-of? @_def "def" "defp" "defdelegate" "defguard" "defguardp")) @definition.function @scope
+(call
+  target: (identifier) @_def
+  (arguments .
+    [(call target: (identifier) @name) @call
+     (binary_operator left: (call target: (identifier) @name) @call operator: "when")
+     (identifier) @name])
+  (#any-of? @_def "defmacro" "defmacrop")) @definition.macro @scope
+
+(unary_operator
+  operator: "@"
+  operand: (call
+    target: (identifier) @_attr
+    (arguments
+      (binary_operator
+        left: [(identifier) @name (call target: (identifier) @name)]
+        operator: "::")))
+  (#any-of? @_attr "type" "typep" "opaque")) @definition.type
+
+; Module attributes (`@doc`, `@spec`) aren't calls
+(unary_operator operator: "@" operand: (call) @call)
+
+(call
+  target: (identifier) @_def
+  (arguments . (alias) @name)
+  (do_block
+    (unary_operator
+      operator: "@"
+      operand: (call
+        target: (identifier) @_behaviour
+        (arguments . (alias) @relation.implements))))
+  (#eq? @_def "defmodule")
+  (#eq? @_behaviour "behaviour"))
+
+; `alias MyApp.User`, `import Ecto.Query`, `alias MyApp.{Repo, Mailer}`
+(call
+  target: (identifier) @_import
+  (arguments . (alias) @import.path)
+  (#any-of? @_import "alias" "import" "require" "use")) @import
+(call
+  target: (identifier) @_import
+  (arguments . (dot left: (alias) @import.path right: (tuple (alias) @import.symbol)))
+  (#any-of? @_import "alias" "import" "require" "use")) @import
+
+(call
+  target: (identifier) @call.name
+  (#not-any-of? @call.name
+    "defmodule" "defprotocol" "defimpl" "def" "defp" "defdelegate" "defguard"
+    "defguardp" "defmacro" "defmacrop" "defstruct" "defexception"
+    "alias" "import" "require" "use")) @call
+(call target: (dot right: (identifier) @call.name)) @call
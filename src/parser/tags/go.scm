@@ -0,0 +1,50 @@
+; Go tag query.
+
+(package_clause (package_identifier) @name) @definition.module @package
+
+(function_declaration name: (identifier) @name) @definition.function @scope
+
+; Methods belong to their receiver type, which may be declared in
+; another file of the same package.
+(method_declaration
+  receiver: (parameter_list
+    (parameter_declaration
+      type: [
+        (type_identifier) @parent
+        (pointer_type (type_identifier) @parent)
+        (generic_type type: (type_identifier) @parent)
+        (pointer_type (generic_type type: (type_identifier) @parent))
+      ]))
+  name: (field_identifier) @name) @definition.method @scope
+
+(type_spec name: (type_identifier) @name type: (struct_type)) @definition.struct @scope
+(type_spec name: (type_identifier) @name type: (interface_type)) @definition.interface @scope
+(type_spec name: (type_identifier) @name) @definition.type
+(type_alias name: (type_identifier) @name) @definition.type
+(method_elem name: (field_identifier) @name) @definition.method
+(const_spec name: (identifier) @name) @definition.constant
+
+(import_spec path: (_) @import.path) @import
+
+(call_expression function: (_) @call.name) @call
+
+; Usages other than calls: composite literals, type assertions, writes,
+; field reads, identifiers read in expressions, and every type mentioned
+(composite_literal type: (type_identifier) @reference.construct)
+(composite_literal type: (qualified_type name: (type_identifier) @reference.construct))
+(type_assertion_expression type: (type_identifier) @reference.check)
+(type_case type: (type_identifier) @reference.check)
+(assignment_statement left: (expression_list (identifier) @reference.write))
+(assignment_statement left: (expression_list (selector_expression field: (field_identifier) @reference.write)))
+(inc_statement (identifier) @reference.write)
+(dec_statement (identifier) @reference.write)
+(selector_expression field: (field_identifier) @reference.read)
+(assignment_statement right: (expression_list (identifier) @reference.read))
+(short_var_declaration right: (expression_list (identifier) @reference.read))
+(return_statement (expression_list (identifier) @reference.read))
+(argument_list (identifier) @reference.read)
+(binary_expression left: (identifier) @reference.read)
+(binary_expression right: (identifier) @reference.read)
+(const_spec value: (expression_list (identifier) @reference.read))
+(var_spec value: (expression_list (identifier) @reference.read))
+(type_identifier) @reference.type
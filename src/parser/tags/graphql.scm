@@ -0,0 +1,40 @@
+; GraphQL tag query. Object types are classes whose fields are methods
+; when they take arguments or are roots (`Query`, `Mutation`,
+; `Subscription`) and fields otherwise; input types are structs.
+
+(object_type_definition (name) @name) @definition.class @scope
+(interface_type_definition (name) @name) @definition.interface @scope
+(input_object_type_definition (name) @name) @definition.struct @scope
+(enum_type_definition (name) @name) @definition.enum @scope
+(enum_value_definition (enum_value (name) @name)) @definition.constant
+(union_type_definition (name) @name) @definition.type
+(scalar_type_definition (name) @name) @definition.type
+
+; `extend type User { ... }` adds fields to a type declared elsewhere
+(object_type_extension (name) @scope.name) @scope
+(interface_type_extension (name) @scope.name) @scope
+(input_object_type_extension (name) @scope.name) @scope
+
+(object_type_definition
+  (name) @_root
+  (fields_definition (field_definition (name) @name) @definition.method)
+  (#any-of? @_root "Query" "Mutation" "Subscription"))
+(object_type_extension
+  (name) @_root
+  (fields_definition (field_definition (name) @name) @definition.method)
+  (#any-of? @_root "Query" "Mutation" "Subscription"))
+(field_definition (name) @name (arguments_definition)) @definition.method
+(field_definition (name) @name) @definition.variable
+(input_fields_definition (input_value_definition (name) @name) @definition.variable)
+
+(implements_interfaces (named_type (name) @relation.implements))
+
+; Operations and fragments in documents call the root fields they
+; select, and the fragments they spread
+(operation_definition (name) @name) @definition.function @scope
+(fragment_definition (fragment_name (name) @name)) @definition.function @scope
+(operation_definition
+  (selection_set (selection (field (name) @call.name) @call)))
+(fragment_spread (fragment_name (name) @call.name) @call)
+
+(named_type (name) @reference.type)
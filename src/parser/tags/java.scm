@@ -0,0 +1,72 @@
+; Java tag query.
+
+(package_declaration [(identifier) (scoped_identifier)] @name) @definition.module @package
+
+(class_declaration name: (identifier) @name) @definition.class @scope
+(record_declaration name: (identifier) @name) @definition.class @scope
+(interface_declaration name: (identifier) @name) @definition.interface @scope
+(annotation_type_declaration name: (identifier) @name) @definition.interface @scope
+(enum_declaration name: (identifier) @name) @definition.enum @scope
+(enum_constant name: (identifier) @name) @definition.constant
+
+(method_declaration name: (identifier) @name) @definition.method @scope
+(constructor_declaration name: (identifier) @name) @definition.method @scope
+(compact_constructor_declaration name: (identifier) @name) @definition.method @scope
+(annotation_type_element_declaration name: (identifier) @name) @definition.method
+
+; `static final` fields (and interface fields) are constants
+(field_declaration
+  (modifiers "static" "final")
+  declarator: (variable_declarator name: (identifier) @name)) @definition.constant
+(field_declaration declarator: (variable_declarator name: (identifier) @name)) @definition.variable
+(constant_declaration declarator: (variable_declarator name: (identifier) @name)) @definition.constant
+
+; Supertypes
+(class_declaration name: (identifier) @name
+  superclass: (superclass (_) @relation.extends))
+(class_declaration name: (identifier) @name
+  interfaces: (super_interfaces (type_list (_) @relation.implements)))
+(record_declaration name: (identifier) @name
+  interfaces: (super_interfaces (type_list (_) @relation.implements)))
+(enum_declaration name: (identifier) @name
+  interfaces: (super_interfaces (type_list (_) @relation.implements)))
+(interface_declaration name: (identifier) @name
+  (extends_interfaces (type_list (_) @relation.extends)))
+
+; Annotations reference their annotation type
+([
+  (class_declaration (modifiers [(marker_annotation name: (_) @relation.references) (annotation name: (_) @relation.references)]) name: (identifier) @name)
+  (record_declaration (modifiers [(marker_annotation name: (_) @relation.references) (annotation name: (_) @relation.references)]) name: (identifier) @name)
+  (interface_declaration (modifiers [(marker_annotation name: (_) @relation.references) (annotation name: (_) @relation.references)]) name: (identifier) @name)
+  (enum_declaration (modifiers [(marker_annotation name: (_) @relation.references) (annotation name: (_) @relation.references)]) name: (identifier) @name)
+  (method_declaration (modifiers [(marker_annotation name: (_) @relation.references) (annotation name: (_) @relation.references)]) name: (identifier) @name)
+  (constructor_declaration (modifiers [(marker_annotation name: (_) @relation.references) (annotation name: (_) @relation.references)]) name: (identifier) @name)
+  (field_declaration (modifiers [(marker_annotation name: (_) @relation.references) (annotation name: (_) @relation.references)]) declarator: (variable_declarator name: (identifier) @name))
+])
+
+; Annotations are kept as decorators: `@Override`, `@GetMapping("/users")`
+(class_declaration (modifiers [(marker_annotation) (annotation)] @decorator) name: (identifier) @name) @definition.class
+(record_declaration (modifiers [(marker_annotation) (annotation)] @decorator) name: (identifier) @name) @definition.class
+(interface_declaration (modifiers [(marker_annotation) (annotation)] @decorator) name: (identifier) @name) @definition.interface
+(enum_declaration (modifiers [(marker_annotation) (annotation)] @decorator) name: (identifier) @name) @definition.enum
+(method_declaration (modifiers [(marker_annotation) (annotation)] @decorator) name: (identifier) @name) @definition.method
+(constructor_declaration (modifiers [(marker_annotation) (annotation)] @decorator) name: (identifier) @name) @definition.method
+(field_declaration
+  (modifiers [(marker_annotation) (annotation)] @decorator)
+  declarator: (variable_declarator name: (identifier) @name)) @definition.variable
+
+(import_declaration [(identifier) (scoped_identifier)] @import.path) @import
+
+(method_invocation name: (identifier) @call.name) @call
+(object_creation_expression type: (_) @call.name) @call
+
+; Usages other than calls: instanceof checks, writes, field and constant
+; reads, and every type mentioned
+(instanceof_expression right: (type_identifier) @reference.check)
+(assignment_expression left: (identifier) @reference.write)
+(assignment_expression left: (field_access field: (identifier) @reference.write))
+(update_expression (identifier) @reference.write)
+(field_access field: (identifier) @reference.read)
+((identifier) @reference.read
+  (#match? @reference.read "^[A-Z][A-Z0-9_]*$"))
+(type_identifier) @reference.type
@@ -0,0 +1,20 @@
+; JavaScript class members and heritage (TypeScript has its own).
+
+(field_definition
+  property: [(property_identifier) (private_property_identifier)] @name) @definition.variable
+
+(class_declaration name: (_) @name (class_heritage (_) @relation.extends))
+
+; `@observable count = 0`, `@action save() {}`, `@injectable() class Store {}`
+(class_declaration
+  decorator: (decorator) @decorator
+  name: (_) @name) @definition.class
+(export_statement
+  decorator: (decorator) @decorator
+  declaration: (class_declaration name: (_) @name) @definition.class)
+(method_definition
+  decorator: (decorator) @decorator
+  name: (_) @name) @definition.method
+(field_definition
+  decorator: (decorator) @decorator
+  property: [(property_identifier) (private_property_identifier)] @name) @definition.variable
@@ -0,0 +1,143 @@
+; JavaScript tag query (also the base of the TypeScript query).
+
+(function_declaration name: (identifier) @name) @definition.function @scope
+(generator_function_declaration name: (identifier) @name) @definition.function @scope
+(class_declaration name: (_) @name) @definition.class @scope
+(method_definition name: (_) @name) @definition.method @scope
+
+; const f = () => {} / const f = function () {}
+(lexical_declaration
+  (variable_declarator
+    name: (identifier) @name
+    value: [(arrow_function) (function_expression)])) @definition.function @scope
+(variable_declaration
+  (variable_declarator
+    name: (identifier) @name
+    value: [(arrow_function) (function_expression)])) @definition.function @scope
+
+; React components wrapped in `memo`/`forwardRef`
+(lexical_declaration
+  (variable_declarator
+    name: (identifier) @name
+    value: (call_expression
+      function: [(identifier) (member_expression)] @_wrap
+      arguments: (arguments . [(arrow_function) (function_expression)])))
+  (#match? @_wrap "(^|\\.)(memo|forwardRef)$")) @definition.function @scope
+
+; Object literals hold their methods: `const api = { fetch() {}, post: () => {} }`
+(pair
+  key: (property_identifier) @name
+  value: [(arrow_function) (function_expression)]) @definition.method @scope
+(lexical_declaration
+  (variable_declarator name: (identifier) @name value: (object))
+  (#match? @name "^[A-Z_]+$")) @definition.constant @scope
+(lexical_declaration
+  (variable_declarator name: (identifier) @name value: (object))) @definition.variable @scope
+
+; const FOO = ... (all caps) / let foo = ...
+(lexical_declaration
+  (variable_declarator name: (identifier) @name value: (_))
+  (#match? @name "^[A-Z_]+$")) @definition.constant
+(variable_declaration
+  (variable_declarator name: (identifier) @name value: (_))
+  (#match? @name "^[A-Z_]+$")) @definition.constant
+(lexical_declaration
+  (variable_declarator name: (identifier) @name value: (_))) @definition.variable
+(variable_declaration
+  (variable_declarator name: (identifier) @name value: (_))) @definition.variable
+
+; CommonJS: `exports.f = () => {}`, `module.exports.f = function () {}`
+(assignment_expression
+  left: (member_expression
+    object: (identifier) @_exports
+    property: (property_identifier) @name @relation.exports)
+  right: [(arrow_function) (function_expression)]
+  (#eq? @_exports "exports")) @definition.function @scope
+(assignment_expression
+  left: (member_expression
+    object: (member_expression
+      object: (identifier) @_module
+      property: (property_identifier) @_exports)
+    property: (property_identifier) @name @relation.exports)
+  right: [(arrow_function) (function_expression)]
+  (#eq? @_module "module")
+  (#eq? @_exports "exports")) @definition.function @scope
+
+; `module.exports = App` and `module.exports = { Store, api: api }`
+(assignment_expression
+  left: (member_expression
+    object: (identifier) @_module
+    property: (property_identifier) @_exports)
+  right: [
+    (identifier) @relation.exports
+    (object [
+      (shorthand_property_identifier) @relation.exports
+      (pair value: (identifier) @relation.exports)
+    ])
+  ]
+  (#eq? @_module "module")
+  (#eq? @_exports "exports"))
+
+; `export function f`, `export class C`, `export const x`, `export default App`,
+; `export { a, b as c }`
+(export_statement
+  declaration: [
+    (function_declaration name: (_) @relation.exports)
+    (generator_function_declaration name: (_) @relation.exports)
+    (class_declaration name: (_) @relation.exports)
+    (lexical_declaration (variable_declarator name: (identifier) @relation.exports))
+    (variable_declaration (variable_declarator name: (identifier) @relation.exports))
+  ])
+(export_statement value: (identifier) @relation.exports)
+(export_statement (export_clause (export_specifier name: (_) @relation.exports)))
+
+(import_statement source: (string (string_fragment) @import.path)) @import
+(import_statement
+  (import_clause (named_imports (import_specifier name: (_) @import.symbol)))
+  source: (string (string_fragment) @import.path)) @import
+
+; Re-exports import what they export; `export * from './util'` exports the
+; whole module
+(export_statement source: (string (string_fragment) @import.path)) @import
+(export_statement
+  (export_clause (export_specifier name: (_) @import.symbol))
+  source: (string (string_fragment) @import.path)) @import
+(export_statement
+  "*"
+  source: (string (string_fragment) @relation.exports))
+(export_statement
+  (namespace_export)
+  source: (string (string_fragment) @relation.exports))
+
+; `const x = require('./x')`, `const { a, b } = require('./x')`
+(call_expression
+  function: (identifier) @_require
+  arguments: (arguments . (string (string_fragment) @import.path))
+  (#eq? @_require "require")) @import
+(variable_declarator
+  name: (object_pattern (shorthand_property_identifier_pattern) @import.symbol)
+  value: (call_expression
+    function: (identifier) @_require
+    arguments: (arguments . (string (string_fragment) @import.path))) @import
+  (#eq? @_require "require"))
+
+(call_expression function: (_) @call.name) @call
+(new_expression constructor: (_) @call.name) @call
+
+; Usages other than calls: instanceof checks, writes, and property and
+; constant reads
+(binary_expression
+  operator: "instanceof"
+  right: [
+    (identifier) @reference.check
+    (member_expression property: (property_identifier) @reference.check)
+  ])
+(assignment_expression left: (identifier) @reference.write)
+(assignment_expression left: (member_expression property: (property_identifier) @reference.write))
+(augmented_assignment_expression left: (identifier) @reference.write)
+(augmented_assignment_expression left: (member_expression property: (property_identifier) @reference.write))
+(update_expression argument: (identifier) @reference.write)
+(update_expression argument: (member_expression property: (property_identifier) @reference.write))
+(member_expression property: (property_identifier) @reference.read)
+((identifier) @reference.read
+  (#match? @reference.read "^[A-Z][A-Z0-9_]*$"))
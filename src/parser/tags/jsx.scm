@@ -0,0 +1,9 @@
+; JSX additions, appended to the JavaScript and TSX queries.
+
+; `<Button />` and `<Ui.Card>` reference the component they render
+(jsx_opening_element
+  name: [(identifier) (member_expression)] @relation.references
+  (#match? @relation.references "^[A-Z]"))
+(jsx_self_closing_element
+  name: [(identifier) (member_expression)] @relation.references
+  (#match? @relation.references "^[A-Z]"))
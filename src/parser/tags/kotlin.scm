@@ -0,0 +1,74 @@
+; Kotlin tag query.
+
+(package_header (qualified_identifier) @name) @definition.module @package
+
+(class_declaration "interface" name: (identifier) @name) @definition.interface @scope
+(class_declaration (modifiers (class_modifier "enum")) name: (identifier) @name) @definition.enum @scope
+(class_declaration name: (identifier) @name) @definition.class @scope
+(object_declaration name: (identifier) @name) @definition.class @scope
+(enum_entry (identifier) @name) @definition.constant
+(type_alias type: (identifier) @name) @definition.type
+
+; An extension function is a member of its receiver type, which may be
+; declared in another file (or not be ours at all)
+(function_declaration
+  [(user_type (identifier) @parent) (nullable_type (user_type (identifier) @parent))]
+  .
+  "."
+  .
+  name: (identifier) @name) @definition.function @scope
+(function_declaration name: (identifier) @name) @definition.function @scope
+(secondary_constructor "constructor" @name) @definition.method @scope
+
+; Properties of files, classes and objects (and `val`/`var` constructor
+; parameters), not locals; `const val` is a constant
+([(source_file (property_declaration
+    (modifiers (property_modifier "const"))
+    (variable_declaration (identifier) @name)) @definition.constant)
+  (class_body (property_declaration
+    (modifiers (property_modifier "const"))
+    (variable_declaration (identifier) @name)) @definition.constant)])
+([(source_file (property_declaration (variable_declaration (identifier) @name)) @definition.variable)
+  (class_body (property_declaration (variable_declaration (identifier) @name)) @definition.variable)])
+(class_parameter ["val" "var"] (identifier) @name) @definition.variable
+
+; Supertypes. A supertype constructed in the header is a class; the rest
+; are interfaces (or, for an interface, the interfaces it extends)
+(class_declaration
+  name: (identifier) @name
+  (delegation_specifiers
+    (delegation_specifier (constructor_invocation (user_type) @relation.extends))))
+(class_declaration
+  name: (identifier) @name
+  (delegation_specifiers
+    (delegation_specifier [(user_type) (explicit_delegation (user_type))] @relation.implements)))
+(object_declaration
+  name: (identifier) @name
+  (delegation_specifiers
+    (delegation_specifier (constructor_invocation (user_type) @relation.extends))))
+(object_declaration
+  name: (identifier) @name
+  (delegation_specifiers
+    (delegation_specifier [(user_type) (explicit_delegation (user_type))] @relation.implements)))
+
+; Annotations are kept as decorators: `@GetMapping("/users")`
+(class_declaration (modifiers (annotation) @decorator) name: (identifier) @name) @definition.class
+(object_declaration (modifiers (annotation) @decorator) name: (identifier) @name) @definition.class
+(function_declaration (modifiers (annotation) @decorator) name: (identifier) @name) @definition.function
+(property_declaration
+  (modifiers (annotation) @decorator)
+  (variable_declaration (identifier) @name)) @definition.variable
+
+(import (qualified_identifier) @import.path) @import
+
+(call_expression . [(identifier) (navigation_expression)] @call.name) @call
+
+; Usages other than calls: `is` checks, writes, constant and property
+; reads, and every type mentioned
+(is_expression right: (user_type (identifier) @reference.check))
+(assignment left: (identifier) @reference.write)
+(assignment left: (navigation_expression (identifier) @reference.write .))
+(navigation_expression "." . (identifier) @reference.read)
+((identifier) @reference.read
+  (#match? @reference.read "^[A-Z][A-Z0-9_]*$"))
+(user_type (identifier) @reference.type)
@@ -0,0 +1,62 @@
+; Lua tag query.
+
+; A table assigned at the top of a chunk is a module or class: functions
+; defined on it (`function Point.new`, `function Point:area`) and in its
+; constructor are its members
+([(chunk (assignment_statement
+    (variable_list . name: (identifier) @name)
+    (expression_list . value: (table_constructor))) @definition.class @scope)
+  (chunk (variable_declaration (assignment_statement
+    (variable_list . name: (identifier) @name)
+    (expression_list . value: (table_constructor)))) @definition.class @scope)])
+(table_constructor (field name: (identifier) @name value: (function_definition)) @definition.method @scope)
+
+(function_declaration
+  name: [(dot_index_expression table: (identifier) @parent field: (identifier) @name)
+         (method_index_expression table: (identifier) @parent method: (identifier) @name)]) @definition.method @scope
+(function_declaration name: (identifier) @name) @definition.function @scope
+(assignment_statement
+  (variable_list . name: (dot_index_expression table: (identifier) @parent field: (identifier) @name))
+  (expression_list . value: (function_definition))) @definition.method @scope
+([(assignment_statement
+    (variable_list . name: (identifier) @name)
+    (expression_list . value: (function_definition))) @definition.function @scope
+  (variable_declaration (assignment_statement
+    (variable_list . name: (identifier) @name)
+    (expression_list . value: (function_definition)))) @definition.function @scope])
+
+; Variables of the chunk, not locals; an all-caps one is a constant. A
+; `require`d module is an import rather than a variable
+([(chunk (assignment_statement
+    (variable_list . name: (identifier) @name)
+    (expression_list . value: (_) @_value)) @definition.constant)
+  (chunk (variable_declaration (assignment_statement
+    (variable_list . name: (identifier) @name)
+    (expression_list . value: (_) @_value))) @definition.constant)]
+  (#match? @name "^[A-Z][A-Z0-9_]*$")
+  (#not-match? @_value "^require\\b"))
+([(chunk (assignment_statement
+    (variable_list . name: (identifier) @name)
+    (expression_list . value: (_) @_value)) @definition.variable)
+  (chunk (variable_declaration (assignment_statement
+    (variable_list . name: (identifier) @name)
+    (expression_list . value: (_) @_value))) @definition.variable)]
+  (#not-match? @_value "^require\\b"))
+
+; `require "geo.point"` and `require("geo.point")`
+(function_call
+  name: (identifier) @_require
+  arguments: (arguments . (string content: (string_content) @import.path))
+  (#eq? @_require "require")) @import
+
+(function_call
+  name: [(identifier) (dot_index_expression field: (identifier)) (method_index_expression method: (identifier))] @call.name) @call
+
+; Usages other than calls: writes (but not `local` declarations), and
+; field and constant reads
+([(chunk (assignment_statement (variable_list name: (identifier) @reference.write)))
+  (block (assignment_statement (variable_list name: (identifier) @reference.write)))])
+(assignment_statement (variable_list name: (dot_index_expression field: (identifier) @reference.write)))
+(dot_index_expression field: (identifier) @reference.read)
+((identifier) @reference.read
+  (#match? @reference.read "^[A-Z][A-Z0-9_]*$"))
@@ -0,0 +1,62 @@
+; PHP tag query.
+
+(namespace_definition name: (namespace_name) @name @package) @definition.module
+
+(class_declaration name: (name) @name) @definition.class @scope
+(interface_declaration name: (name) @name) @definition.interface @scope
+(trait_declaration name: (name) @name) @definition.trait @scope
+(enum_declaration name: (name) @name) @definition.enum @scope
+
+(function_definition name: (name) @name) @definition.function @scope
+(method_declaration name: (name) @name) @definition.method @scope
+
+(property_declaration
+  (property_element name: (variable_name (name) @name))) @definition.variable
+(const_declaration (const_element (name) @name)) @definition.constant
+(enum_case name: (name) @name) @definition.constant
+
+; Supertypes; a `use`d trait is mixed in
+(class_declaration
+  name: (name) @name
+  (base_clause [(name) (qualified_name)] @relation.extends))
+(class_declaration
+  name: (name) @name
+  (class_interface_clause [(name) (qualified_name)] @relation.implements))
+(interface_declaration
+  name: (name) @name
+  (base_clause [(name) (qualified_name)] @relation.extends))
+([(class_declaration
+    name: (name) @name
+    body: (declaration_list (use_declaration [(name) (qualified_name)] @relation.implements)))
+  (trait_declaration
+    name: (name) @name
+    body: (declaration_list (use_declaration [(name) (qualified_name)] @relation.implements)))
+  (enum_declaration
+    name: (name) @name
+    body: (enum_declaration_list (use_declaration [(name) (qualified_name)] @relation.implements)))])
+
+; PHP 8 attributes: `#[Route('/users')]`
+(class_declaration
+  attributes: (attribute_list (attribute_group (attribute) @decorator))
+  name: (name) @name) @definition.class
+(method_declaration
+  attributes: (attribute_list (attribute_group (attribute) @decorator))
+  name: (name) @name) @definition.method
+(function_definition
+  attributes: (attribute_list (attribute_group (attribute) @decorator))
+  name: (name) @name) @definition.function
+
+; `use App\Models\User;` and `use App\Contracts\{Payable, Taxable};`
+(namespace_use_declaration
+  (namespace_use_clause
+    [(qualified_name (name) @import.symbol) (name) @import.symbol] @import.path)) @import
+(namespace_use_declaration
+  (namespace_name) @import.path
+  body: (namespace_use_group
+    (namespace_use_clause . (name) @import.symbol))) @import
+
+(function_call_expression function: (_) @call.name) @call
+(member_call_expression name: (name) @call.name) @call
+(nullsafe_member_call_expression name: (name) @call.name) @call
+(scoped_call_expression name: (name) @call.name) @call
+(object_creation_expression [(name) (qualified_name)] @call.name) @call
@@ -0,0 +1,18 @@
+; Protocol Buffers tag query. Messages are structs with their fields;
+; services are interfaces with their RPCs as methods.
+
+(package (full_ident) @name) @definition.module @package
+
+(message (message_name (identifier) @name)) @definition.struct @scope
+(enum (enum_name (identifier) @name)) @definition.enum @scope
+(enum_field (identifier) @name) @definition.constant
+(field . (type) . (identifier) @name) @definition.variable
+(map_field (identifier) @name) @definition.variable
+(oneof_field (identifier) @name) @definition.variable
+(service (service_name (identifier) @name)) @definition.interface @scope
+(rpc (rpc_name (identifier) @name)) @definition.method @scope
+
+(import path: (string) @import.path) @import
+
+; Message and enum types used by fields and RPCs
+(message_or_enum_type) @reference.type
@@ -0,0 +1,77 @@
+; Python tag query.
+
+(function_definition name: (identifier) @name) @definition.function @scope
+(class_definition name: (identifier) @name) @definition.class @scope
+
+; `@property`, `@staticmethod`, `@app.get("/users")`, `@dataclass`
+(decorated_definition
+  (decorator) @decorator
+  definition: (function_definition name: (identifier) @name) @definition.function)
+(decorated_definition
+  (decorator) @decorator
+  definition: (class_definition name: (identifier) @name) @definition.class)
+
+; Module-level assignments, and class attributes including annotated
+; dataclass/pydantic fields (`id: int`); UPPER_CASE names are constants
+(module
+  (expression_statement
+    (assignment left: (identifier) @name)) @definition.constant
+  (#match? @name "^[A-Z][A-Z0-9_]*$"))
+(module
+  (expression_statement
+    (assignment left: (identifier) @name)) @definition.variable)
+(class_definition
+  body: (block
+    (expression_statement
+      (assignment left: (identifier) @name)) @definition.constant)
+  (#match? @name "^[A-Z][A-Z0-9_]*$"))
+(class_definition
+  body: (block
+    (expression_statement
+      (assignment left: (identifier) @name)) @definition.variable))
+
+; `__all__ = ["User", "Account"]`
+(module
+  (expression_statement
+    (assignment
+      left: (identifier) @_all
+      right: [
+        (list (string (string_content) @relation.exports))
+        (tuple (string (string_content) @relation.exports))
+      ]))
+  (#eq? @_all "__all__"))
+
+; `from . import x` and `from ..core import util` keep their dots
+(import_statement name: (dotted_name) @import.path) @import
+(import_statement name: (aliased_import name: (dotted_name) @import.path)) @import
+(import_from_statement module_name: (_) @import.path) @import
+(import_from_statement
+  module_name: (_) @import.path
+  name: [
+    (dotted_name) @import.symbol
+    (aliased_import name: (dotted_name) @import.symbol)
+  ]) @import
+
+(call function: (_) @call.name) @call
+
+; Usages other than calls: isinstance/issubclass checks, annotations,
+; writes, and attribute and constant reads
+(call
+  function: (identifier) @_check
+  arguments: (argument_list . (_) . [
+    (identifier) @reference.check
+    (attribute attribute: (identifier) @reference.check)
+    (tuple [(identifier) (attribute attribute: (identifier))] @reference.check)
+  ])
+  (#any-of? @_check "isinstance" "issubclass"))
+(type (identifier) @reference.type)
+(type (attribute attribute: (identifier) @reference.type))
+(type (generic_type (identifier) @reference.type))
+(type (generic_type (type_parameter (type (identifier) @reference.type))))
+(assignment left: (identifier) @reference.write)
+(assignment left: (attribute attribute: (identifier) @reference.write))
+(augmented_assignment left: (identifier) @reference.write)
+(augmented_assignment left: (attribute attribute: (identifier) @reference.write))
+(attribute attribute: (identifier) @reference.read)
+((identifier) @reference.read
+  (#match? @reference.read "^[A-Z][A-Z0-9_]*$"))
@@ -0,0 +1,53 @@
+; Ruby tag query.
+
+; `class Billing::Invoice` is `Invoice` inside `Billing`, which may be
+; reopened from another file
+(class name: (constant) @name) @definition.class @scope
+(class
+  name: (scope_resolution scope: (_) @parent name: (constant) @name)) @definition.class @scope
+(module name: (constant) @name) @definition.module @scope
+(module
+  name: (scope_resolution scope: (_) @parent name: (constant) @name)) @definition.module @scope
+
+(method name: (_) @name) @definition.function @scope
+(singleton_method name: (_) @name) @definition.method @scope
+
+(call
+  method: (identifier) @_attr
+  arguments: (argument_list (simple_symbol) @name)
+  (#any-of? @_attr "attr_reader" "attr_writer" "attr_accessor")) @definition.variable
+
+; Superclasses and mixins
+(class
+  name: [(constant) @name (scope_resolution name: (constant) @name)]
+  superclass: (superclass [(constant) (scope_resolution)] @relation.extends))
+([(class
+    name: [(constant) @name (scope_resolution name: (constant) @name)]
+    body: (body_statement
+      (call
+        method: (identifier) @_mixin
+        arguments: (argument_list [(constant) (scope_resolution)] @relation.implements))))
+  (module
+    name: [(constant) @name (scope_resolution name: (constant) @name)]
+    body: (body_statement
+      (call
+        method: (identifier) @_mixin
+        arguments: (argument_list [(constant) (scope_resolution)] @relation.implements))))]
+  (#any-of? @_mixin "include" "extend" "prepend"))
+
+(call
+  method: (identifier) @_require
+  arguments: (argument_list (string (string_content) @import.path))
+  (#any-of? @_require "require" "require_relative" "require_dependency" "load")) @import
+
+; Constants a Rails app autoloads instead of requiring them (`User.find`,
+; `< ApplicationController`)
+(call receiver: [(constant) (scope_resolution)] @import.path) @import
+(superclass [(constant) (scope_resolution)] @import.path) @import
+
+; Declarations (requires, mixins, attributes) aren't calls
+(call
+  method: (identifier) @call.name
+  (#not-any-of? @call.name
+    "require" "require_relative" "require_dependency" "load" "include" "extend" "prepend"
+    "attr_reader" "attr_writer" "attr_accessor")) @call
@@ -0,0 +1,104 @@
+; Rust tag query.
+
+(function_item name: (identifier) @name) @definition.function @scope
+(function_signature_item name: (identifier) @name) @definition.function
+(struct_item name: (type_identifier) @name) @definition.struct @scope
+(enum_item name: (type_identifier) @name) @definition.enum @scope
+(trait_item name: (type_identifier) @name) @definition.trait @scope
+(const_item name: (identifier) @name) @definition.constant
+(static_item name: (identifier) @name) @definition.constant
+(type_item name: (type_identifier) @name) @definition.type
+(associated_type name: (type_identifier) @name) @definition.type
+(mod_item name: (identifier) @name) @definition.module @scope
+(macro_definition name: (identifier) @name) @definition.macro
+
+(field_declaration name: (field_identifier) @name) @definition.variable
+(enum_variant name: (identifier) @name) @definition.constant
+
+; `#[derive(Debug)]`, `#[test]`, `#[serde(default)]`: an item's outer
+; attributes precede it
+((attribute_item (attribute) @decorator)
+  .
+  [
+    (function_item name: (identifier) @name) @definition.function
+    (function_signature_item name: (identifier) @name) @definition.function
+    (struct_item name: (type_identifier) @name) @definition.struct
+    (enum_item name: (type_identifier) @name) @definition.enum
+    (trait_item name: (type_identifier) @name) @definition.trait
+    (mod_item name: (identifier) @name) @definition.module
+    (macro_definition name: (identifier) @name) @definition.macro
+    (field_declaration name: (field_identifier) @name) @definition.variable
+    (enum_variant name: (identifier) @name) @definition.constant
+  ])
+((attribute_item (attribute) @decorator)
+  .
+  (attribute_item)
+  .
+  [
+    (function_item name: (identifier) @name) @definition.function
+    (function_signature_item name: (identifier) @name) @definition.function
+    (struct_item name: (type_identifier) @name) @definition.struct
+    (enum_item name: (type_identifier) @name) @definition.enum
+    (trait_item name: (type_identifier) @name) @definition.trait
+    (mod_item name: (identifier) @name) @definition.module
+    (macro_definition name: (identifier) @name) @definition.macro
+    (field_declaration name: (field_identifier) @name) @definition.variable
+    (enum_variant name: (identifier) @name) @definition.constant
+  ])
+((attribute_item (attribute) @decorator)
+  .
+  (attribute_item)
+  .
+  (attribute_item)
+  .
+  [
+    (function_item name: (identifier) @name) @definition.function
+    (function_signature_item name: (identifier) @name) @definition.function
+    (struct_item name: (type_identifier) @name) @definition.struct
+    (enum_item name: (type_identifier) @name) @definition.enum
+    (trait_item name: (type_identifier) @name) @definition.trait
+    (mod_item name: (identifier) @name) @definition.module
+    (macro_definition name: (identifier) @name) @definition.macro
+    (field_declaration name: (field_identifier) @name) @definition.variable
+    (enum_variant name: (identifier) @name) @definition.constant
+  ])
+
+; An impl block belongs to its type, which may be declared in another
+; file; `impl Trait for Type` also implements the trait
+(impl_item
+  type: [
+    (type_identifier) @name
+    (generic_type type: (type_identifier) @name)
+    (scoped_type_identifier name: (type_identifier) @name)
+    (generic_type type: (scoped_type_identifier name: (type_identifier) @name))
+  ] @parent) @definition.impl @scope
+(impl_item
+  trait: (_) @relation.implements
+  type: [
+    (type_identifier) @name
+    (generic_type type: (type_identifier) @name)
+    (scoped_type_identifier name: (type_identifier) @name)
+    (generic_type type: (scoped_type_identifier name: (type_identifier) @name))
+  ])
+
+(use_declaration argument: (_) @import.path) @import
+
+(call_expression function: (_) @call.name) @call
+(macro_invocation macro: (_) @call.name) @call
+
+; Macro arguments are token trees: `println!("{}", render(x))`
+(token_tree (identifier) @call.name @call . (token_tree))
+
+; Usages other than calls: struct literals, writes, field and constant
+; reads, and every type mentioned in annotations or generic arguments
+(struct_expression name: (type_identifier) @reference.construct)
+(struct_expression name: (scoped_type_identifier name: (type_identifier) @reference.construct))
+(assignment_expression left: (identifier) @reference.write)
+(assignment_expression left: (field_expression field: (field_identifier) @reference.write))
+(compound_assignment_expr left: (identifier) @reference.write)
+(compound_assignment_expr left: (field_expression field: (field_identifier) @reference.write))
+(field_expression field: (field_identifier) @reference.read)
+(scoped_identifier name: (identifier) @reference.read)
+((identifier) @reference.read
+  (#match? @reference.read "^[A-Z][A-Z0-9_]*$"))
+(type_identifier) @reference.type
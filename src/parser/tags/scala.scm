@@ -0,0 +1,81 @@
+; Scala tag query.
+
+(package_clause name: (package_identifier) @name) @definition.module @package
+
+(class_definition name: (identifier) @name) @definition.class @scope
+(object_definition name: (identifier) @name) @definition.class @scope
+(trait_definition name: (identifier) @name) @definition.trait @scope
+(enum_definition name: (identifier) @name) @definition.enum @scope
+(simple_enum_case name: (identifier) @name) @definition.constant
+(full_enum_case name: (identifier) @name) @definition.constant
+(type_definition name: (type_identifier) @name) @definition.type
+
+; Extension methods are members of the type they extend, which may be
+; declared in another file (or not be ours at all)
+(extension_definition
+  parameters: (parameters (parameter type: [(type_identifier) (generic_type type: (type_identifier))] @parent))
+  body: (function_definition name: (identifier) @name) @definition.function @scope)
+(extension_definition
+  parameters: (parameters (parameter type: [(type_identifier) (generic_type type: (type_identifier))] @parent))
+  body: (_ (function_definition name: (identifier) @name) @definition.function @scope))
+(function_definition name: (identifier) @name) @definition.function @scope
+(function_declaration name: (identifier) @name) @definition.function
+
+; Fields of files, classes and objects (and `val`/`var` constructor
+; parameters), not locals; a capitalised `val` is a constant
+([(compilation_unit (val_definition pattern: (identifier) @name) @definition.constant)
+  (template_body (val_definition pattern: (identifier) @name) @definition.constant)]
+  (#match? @name "^[A-Z]"))
+([(compilation_unit [(val_definition pattern: (identifier) @name) (var_definition pattern: (identifier) @name)] @definition.variable)
+  (template_body [(val_definition pattern: (identifier) @name) (var_definition pattern: (identifier) @name)] @definition.variable)
+  (template_body [(val_declaration name: (identifier) @name) (var_declaration name: (identifier) @name)] @definition.variable)])
+(class_parameter ["val" "var"] name: (identifier) @name) @definition.variable
+
+; Supertypes. The first is the superclass (or, for a trait, the traits it
+; extends); the traits mixed in after it `with` are implemented
+(class_definition
+  name: (identifier) @name
+  extend: (extends_clause . type: [(type_identifier) (generic_type type: (type_identifier))] @relation.extends))
+(class_definition
+  name: (identifier) @name
+  extend: (extends_clause [(type_identifier) (generic_type)] [(type_identifier) (generic_type type: (type_identifier))] @relation.implements))
+(object_definition
+  name: (identifier) @name
+  extend: (extends_clause . type: [(type_identifier) (generic_type type: (type_identifier))] @relation.extends))
+(object_definition
+  name: (identifier) @name
+  extend: (extends_clause [(type_identifier) (generic_type)] [(type_identifier) (generic_type type: (type_identifier))] @relation.implements))
+(trait_definition
+  name: (identifier) @name
+  extend: (extends_clause type: [(type_identifier) (generic_type type: (type_identifier))] @relation.extends))
+(enum_definition
+  name: (identifier) @name
+  extend: (extends_clause type: [(type_identifier) (generic_type type: (type_identifier))] @relation.implements))
+
+; Annotations are kept as decorators: `@deprecated`, `@main`
+(class_definition (annotation) @decorator name: (identifier) @name) @definition.class
+(object_definition (annotation) @decorator name: (identifier) @name) @definition.class
+(trait_definition (annotation) @decorator name: (identifier) @name) @definition.trait
+(function_definition (annotation) @decorator name: (identifier) @name) @definition.function
+
+; `import a.b.c` and `import a.b.{C, D}`: the path is captured piece by piece
+(import_declaration
+  (identifier) @import.path ("." (identifier) @import.path)*
+  (namespace_selectors [(identifier) @import.symbol (arrow_renamed_identifier name: (identifier) @import.symbol)])?) @import
+
+(call_expression function: [(identifier) (field_expression field: (identifier))] @call.name) @call
+(call_expression function: (generic_function function: [(identifier) (field_expression field: (identifier))] @call.name)) @call
+(instance_expression [(type_identifier) (generic_type type: (type_identifier))] @call.name) @call
+
+; Usages other than calls: `isInstanceOf` checks, writes, constant and
+; field reads, and every type mentioned
+(generic_function
+  function: (field_expression field: (identifier) @_check)
+  type_arguments: (type_arguments (type_identifier) @reference.check)
+  (#eq? @_check "isInstanceOf"))
+(assignment_expression left: (identifier) @reference.write)
+(assignment_expression left: (field_expression field: (identifier) @reference.write))
+(field_expression field: (identifier) @reference.read)
+((identifier) @reference.read
+  (#match? @reference.read "^[A-Z][A-Za-z0-9_]*$"))
+(type_identifier) @reference.type
@@ -0,0 +1,41 @@
+; SQL (DDL) tag query. Tables are structs with their columns as fields;
+; an index is a constant of the table it indexes, a view a type.
+
+(create_table (object_reference name: (identifier) @name)) @definition.struct @scope
+(column_definitions (column_definition name: (identifier) @name) @definition.variable)
+(alter_table
+  (object_reference name: (identifier) @parent)
+  (add_column (column_definition name: (identifier) @name) @definition.variable))
+(create_index
+  column: (identifier) @name
+  (object_reference name: (identifier) @parent)) @definition.constant
+(create_view (object_reference name: (identifier) @name)) @definition.type @scope
+(create_materialized_view (object_reference name: (identifier) @name)) @definition.type @scope
+(create_type (object_reference name: (identifier) @name) (keyword_enum)) @definition.enum
+(create_type (object_reference name: (identifier) @name)) @definition.type
+(create_function (object_reference name: (identifier) @name)) @definition.function @scope
+(create_trigger
+  (keyword_trigger) . (object_reference name: (identifier) @name)) @definition.function @scope
+
+; Foreign keys point at the tables they reference
+(create_table
+  (object_reference name: (identifier) @name)
+  (column_definitions
+    (column_definition (keyword_references) . (object_reference name: (identifier) @relation.references))))
+(create_table
+  (object_reference name: (identifier) @name)
+  (column_definitions
+    (constraints (constraint (keyword_references) . (object_reference name: (identifier) @relation.references)))))
+
+(invocation (object_reference name: (identifier) @call.name)) @call
+(create_trigger
+  (keyword_function) . (object_reference name: (identifier) @call.name)) @call
+(create_trigger
+  (keyword_procedure) . (object_reference name: (identifier) @call.name)) @call
+
+; Every table a statement reads or writes
+(relation (object_reference name: (identifier) @reference.type))
+(insert (object_reference name: (identifier) @reference.type))
+(from (object_reference name: (identifier) @reference.type))
+(create_trigger
+  (keyword_on) . (object_reference name: (identifier) @reference.type))
@@ -0,0 +1,39 @@
+; Swift tag query.
+
+(class_declaration declaration_kind: "class" name: (type_identifier) @name) @definition.class @scope
+(class_declaration declaration_kind: "actor" name: (type_identifier) @name) @definition.class @scope
+(class_declaration declaration_kind: "struct" name: (type_identifier) @name) @definition.struct @scope
+(class_declaration declaration_kind: "enum" name: (type_identifier) @name) @definition.enum @scope
+(protocol_declaration name: (type_identifier) @name) @definition.interface @scope
+(typealias_declaration name: (type_identifier) @name) @definition.type
+
+; An extension is an impl block contained by the type it extends, which
+; may be declared in another file
+(class_declaration
+  declaration_kind: "extension"
+  name: (user_type (type_identifier) @name @parent)) @definition.impl @scope
+
+(function_declaration name: (simple_identifier) @name) @definition.function @scope
+(init_declaration name: "init" @name) @definition.method @scope
+(deinit_declaration "deinit" @name) @definition.method @scope
+(protocol_function_declaration name: (simple_identifier) @name) @definition.method
+
+(property_declaration name: (pattern bound_identifier: (simple_identifier) @name)) @definition.variable @scope
+(protocol_property_declaration name: (pattern bound_identifier: (simple_identifier) @name)) @definition.variable
+(enum_entry name: (simple_identifier) @name) @definition.constant
+
+; Conformance. Swift doesn't mark which inherited type is a superclass:
+; an implemented class becomes `Extends` once resolved.
+(class_declaration
+  name: [(type_identifier) (user_type (type_identifier))] @name
+  (inheritance_specifier inherits_from: (user_type) @relation.implements))
+(protocol_declaration
+  name: (type_identifier) @name
+  (inheritance_specifier inherits_from: (user_type) @relation.extends))
+
+(import_declaration (identifier) @import.path) @import
+
+(call_expression (simple_identifier) @call.name) @call
+(call_expression
+  (navigation_expression
+    suffix: (navigation_suffix suffix: (simple_identifier) @call.name))) @call
@@ -0,0 +1,74 @@
+; TypeScript additions, appended to the JavaScript query.
+
+(interface_declaration name: (_) @name) @definition.interface
+(type_alias_declaration name: (_) @name) @definition.type
+(enum_declaration name: (_) @name) @definition.enum
+(abstract_class_declaration name: (_) @name) @definition.class @scope
+(abstract_method_signature name: (_) @name) @definition.method
+(public_field_definition name: (_) @name) @definition.variable
+
+; `namespace Geo {}` and `declare module "foo" {}`
+(internal_module name: (_) @name) @definition.module @scope
+(module name: (identifier) @name) @definition.module @scope
+(module name: (string (string_fragment) @name)) @definition.module @scope
+
+(class_declaration
+  name: (_) @name
+  (class_heritage (extends_clause value: (_) @relation.extends)))
+(abstract_class_declaration
+  name: (_) @name
+  (class_heritage (extends_clause value: (_) @relation.extends)))
+(class_declaration
+  name: (_) @name
+  (class_heritage (implements_clause (_) @relation.implements)))
+(abstract_class_declaration
+  name: (_) @name
+  (class_heritage (implements_clause (_) @relation.implements)))
+
+(export_statement
+  declaration: [
+    (abstract_class_declaration name: (_) @relation.exports)
+    (interface_declaration name: (_) @relation.exports)
+    (type_alias_declaration name: (_) @relation.exports)
+    (enum_declaration name: (_) @relation.exports)
+    (internal_module name: (_) @relation.exports)
+  ])
+
+; Decorators: `@Component({...}) export class`, `@Input() name: string`.
+; A method's decorators precede it in the class body
+(class_declaration
+  decorator: (decorator) @decorator
+  name: (_) @name) @definition.class
+(abstract_class_declaration
+  decorator: (decorator) @decorator
+  name: (_) @name) @definition.class
+(export_statement
+  decorator: (decorator) @decorator
+  declaration: [
+    (class_declaration name: (_) @name)
+    (abstract_class_declaration name: (_) @name)
+  ] @definition.class)
+(public_field_definition
+  decorator: (decorator) @decorator
+  name: (_) @name) @definition.variable
+(class_body
+  (decorator) @decorator
+  .
+  [(method_definition name: (_) @name) (abstract_method_signature name: (_) @name)] @definition.method)
+(class_body
+  (decorator) @decorator
+  .
+  (decorator)
+  .
+  [(method_definition name: (_) @name) (abstract_method_signature name: (_) @name)] @definition.method)
+(class_body
+  (decorator) @decorator
+  .
+  (decorator)
+  .
+  (decorator)
+  .
+  [(method_definition name: (_) @name) (abstract_method_signature name: (_) @name)] @definition.method)
+
+; Types mentioned in annotations and generic arguments
+(type_identifier) @reference.type
@@ -6,9 +6,10 @@
 //! - `create`: "I'm adding something like this - show me patterns"
 
 use std::fs;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::Path;
 
-use crate::graph::{CodeGraph, DependencyInfo, SearchResult};
+use crate::graph::{CodeGraph, DependencyInfo, SearchResult, SourceRange};
 
 use super::types::{ContextResponse, Edit, Reference, Signature, Symbol};
 
@@ -112,8 +113,13 @@ fn change(
 
     // Build suggested edits with ACTUAL usage extraction
     for dep in &dependents {
-        if let Some(edit) = build_edit(graph, query, dep, &new_sig, &sig_diff) {
-            response.edits.push(edit);
+        let edits = build_call_site_edits(query, dep, &new_sig, &sig_diff);
+        if edits.is_empty() {
+            response
+                .edits
+                .extend(build_edit(graph, query, dep, &new_sig, &sig_diff));
+        } else {
+            response.edits.extend(edits);
         }
     }
 
@@ -152,7 +158,77 @@ fn extract_signature_from_code(code: &str) -> Option<Signature> {
     None
 }
 
-/// Build an Edit with actual usage extracted from source.
+/// One Edit per recorded call site, reading each call straight from
+/// the caller's span of the file. Sites that no longer name the target
+/// (the file changed since indexing) are skipped.
+fn build_call_site_edits(
+    target_symbol: &str,
+    dep: &DependencyInfo,
+    new_sig: &Option<Signature>,
+    sig_diff: &Option<(Vec<super::types::Param>, Vec<super::types::Param>)>,
+) -> Vec<Edit> {
+    if dep.call_sites.is_empty() {
+        return vec![];
+    }
+    let Ok(span) = read_span(&dep.file, &dep.range) else {
+        return vec![];
+    };
+    let base = dep.range.start.byte - dep.range.start.column;
+    let lines: Vec<&[u8]> = span.split(|&b| b == b'\n').collect();
+
+    dep.call_sites
+        .iter()
+        .filter_map(|site| {
+            let call = span.get(
+                site.range.start.byte.checked_sub(base)?..site.range.end.byte.checked_sub(base)?,
+            )?;
+            let usage = String::from_utf8_lossy(call)
+                .split_whitespace()
+                .collect::<Vec<_>>()
+                .join(" ");
+            if !usage.contains(target_symbol) {
+                return None;
+            }
+            let line_content = lines.get(site.line.checked_sub(dep.range.start.line)?)?;
+            let (suggested, new_args, removed_args) = suggest(&usage, new_sig, sig_diff);
+            Some(Edit {
+                file: dep.file.to_string_lossy().to_string(),
+                line: site.line,
+                in_symbol: dep.symbol.clone(),
+                usage,
+                line_content: String::from_utf8_lossy(line_content).trim().to_string(),
+                suggested,
+                new_args,
+                removed_args,
+                context: get_context_lines(&dep.file, &dep.range, site.line, 2),
+            })
+        })
+        .collect()
+}
+
+/// The suggested call, added and removed arguments for a usage, when
+/// the signature changes.
+fn suggest(
+    usage: &str,
+    new_sig: &Option<Signature>,
+    sig_diff: &Option<(Vec<super::types::Param>, Vec<super::types::Param>)>,
+) -> (Option<String>, Vec<String>, Vec<String>) {
+    match (new_sig, sig_diff) {
+        (Some(new_sig), Some((added, removed))) => {
+            let suggested = generate_suggested_call(usage, new_sig, added);
+            let new_args: Vec<String> = added
+                .iter()
+                .map(|p| format!("{}: {}", p.name, p.typ))
+                .collect();
+            let removed_args: Vec<String> = removed.iter().map(|p| p.name.clone()).collect();
+            (Some(suggested), new_args, removed_args)
+        }
+        _ => (None, vec![], vec![]),
+    }
+}
+
+/// Build an Edit by scanning the caller's snippet for the usage, for
+/// dependents without recorded call sites.
 fn build_edit(
     graph: &CodeGraph,
     target_symbol: &str,
@@ -189,21 +265,10 @@ fn build_edit(
     let actual_line = dep.line + line_offset;
 
     // Get context from the file if possible
-    let context = get_context_lines(&dep.file, actual_line, 2);
+    let context = get_context_lines(&dep.file, &dep.range, actual_line, 2);
 
     // Generate suggested fix if we have signature diff
-    let (suggested, new_args, removed_args) = match (new_sig, sig_diff) {
-        (Some(new_sig), Some((added, removed))) => {
-            let suggested = generate_suggested_call(usage_expr, new_sig, added);
-            let new_args: Vec<String> = added
-                .iter()
-                .map(|p| format!("{}: {}", p.name, p.typ))
-                .collect();
-            let removed_args: Vec<String> = removed.iter().map(|p| p.name.clone()).collect();
-            (Some(suggested), new_args, removed_args)
-        }
-        _ => (None, vec![], vec![]),
-    };
+    let (suggested, new_args, removed_args) = suggest(usage_expr, new_sig, sig_diff);
 
     Some(Edit {
         file: dep.file.to_string_lossy().to_string(),
@@ -342,24 +407,44 @@ fn extract_call_expression(code: &str) -> Option<String> {
 }
 
 /// Read context lines from a file around a specific line.
-fn get_context_lines(file_path: &Path, line: usize, context_size: usize) -> Vec<String> {
-    let Ok(content) = fs::read_to_string(file_path) else {
-        return vec![];
+///
+/// When the enclosing symbol's range is known only its span is read,
+/// and the context stays within it.
+fn get_context_lines(
+    file_path: &Path,
+    range: &SourceRange,
+    line: usize,
+    context_size: usize,
+) -> Vec<String> {
+    let (content, first_line) = if range.is_empty() {
+        match fs::read_to_string(file_path) {
+            Ok(content) => (content, 1),
+            Err(_) => return vec![],
+        }
+    } else {
+        match read_span(file_path, range) {
+            Ok(content) => (
+                String::from_utf8_lossy(&content).into_owned(),
+                range.start.line,
+            ),
+            Err(_) => return vec![],
+        }
     };
 
     let lines: Vec<&str> = content.lines().collect();
-    if line == 0 || line > lines.len() {
+    if line < first_line || line >= first_line + lines.len() {
         return vec![];
     }
+    let line_idx = line - first_line;
 
-    let start = line.saturating_sub(context_size + 1);
-    let end = (line + context_size).min(lines.len());
+    let start = line_idx.saturating_sub(context_size);
+    let end = (line_idx + context_size + 1).min(lines.len());
 
     lines[start..end]
         .iter()
         .enumerate()
         .map(|(i, l)| {
-            let line_num = start + i + 1;
+            let line_num = first_line + start + i;
             if line_num == line {
                 format!(">{:4}| {}", line_num, l)
             } else {
@@ -369,6 +454,17 @@ fn get_context_lines(file_path: &Path, line: usize, context_size: usize) -> Vec<
         .collect()
 }
 
+/// The whole lines covered by `range`, read without loading the rest of the file.
+fn read_span(file_path: &Path, range: &SourceRange) -> std::io::Result<Vec<u8>> {
+    let line_start = range.start.byte - range.start.column;
+    let mut file = fs::File::open(file_path)?;
+    file.seek(SeekFrom::Start(line_start as u64))?;
+    let mut bytes = Vec::with_capacity(range.end.byte - line_start);
+    file.take((range.end.byte - line_start) as u64)
+        .read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
 /// Create intent: find similar patterns to follow.
 fn create(
     graph: &CodeGraph,
@@ -603,4 +699,68 @@ fn process(input: &str) {
         // Nested calls
         assert_eq!(extract_call_args("foo(bar(x), y)"), vec!["bar(x)", "y"]);
     }
+
+    #[test]
+    fn test_context_lines_stay_within_symbol_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        let source = "use a;\nfn caller() {\n    one();\n    two();\n}\nfn other() {}\n";
+        fs::write(&path, source).unwrap();
+
+        let extraction = parser::extract_file(&path, source).unwrap();
+        let caller = extraction
+            .symbols
+            .iter()
+            .find(|s| s.name == "caller")
+            .unwrap();
+        assert_eq!(
+            get_context_lines(&path, &caller.range, 3, 2),
+            vec![
+                "    2| fn caller() {",
+                ">   3|     one();",
+                "    4|     two();",
+                "    5| }"
+            ]
+        );
+        assert_eq!(
+            get_context_lines(&path, &SourceRange::default(), 1, 0),
+            vec![">   1| use a;"]
+        );
+        assert!(get_context_lines(&path, &caller.range, 6, 2).is_empty());
+    }
+
+    #[test]
+    fn test_change_edits_come_from_call_sites() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("lib.rs");
+        let source = "fn validate(input: &str) -> bool { true }\n\
+                      fn run() {\n    let a = validate(\"x\");\n    if validate(&a.to_string()) {}\n}\n";
+        fs::write(&path, source).unwrap();
+        let mut graph = CodeGraph::new();
+        graph.build_from_extractions(vec![parser::extract_file(&path, source).unwrap()]);
+
+        let response = get_context_for_change(
+            &graph,
+            "validate",
+            "change",
+            Some("validate(input: &str, strict: bool) -> bool"),
+        );
+        let usages: Vec<_> = response
+            .edits
+            .iter()
+            .map(|e| (e.line, e.usage.as_str()))
+            .collect();
+        assert_eq!(
+            usages,
+            vec![(3, "validate(\"x\")"), (4, "validate(&a.to_string())")]
+        );
+        assert_eq!(
+            response.edits[1].line_content,
+            "if validate(&a.to_string()) {}"
+        );
+        assert_eq!(
+            response.edits[1].suggested.as_deref(),
+            Some("validate(&a.to_string(), <strict>)")
+        );
+    }
 }
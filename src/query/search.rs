@@ -17,7 +17,15 @@ pub fn anchor_search(graph: &CodeGraph, query: Query) -> SearchResponse {
     let mut results = graph.search(name, limit);
 
     // Apply optional filters for structured queries
-    if let Query::Structured { kind, file, .. } = &query {
+    if let Query::Structured {
+        kind,
+        file,
+        visibility,
+        modifier,
+        decorator,
+        ..
+    } = &query
+    {
         if let Some(kind_filter) = kind {
             let kind_lower = kind_filter.to_lowercase();
             results.retain(|r| r.kind.to_string() == kind_lower);
@@ -25,6 +33,20 @@ pub fn anchor_search(graph: &CodeGraph, query: Query) -> SearchResponse {
         if let Some(file_filter) = file {
             results.retain(|r| r.file.to_string_lossy().contains(file_filter.as_str()));
         }
+        if let Some(visibility_filter) = visibility {
+            let visibility_lower = visibility_filter.to_lowercase();
+            results.retain(|r| {
+                r.metadata
+                    .visibility
+                    .is_some_and(|v| v.to_string() == visibility_lower)
+            });
+        }
+        if let Some(modifier_filter) = modifier {
+            results.retain(|r| r.metadata.has_modifier(modifier_filter));
+        }
+        if let Some(decorator_filter) = decorator {
+            results.retain(|r| r.metadata.has_decorator(decorator_filter));
+        }
     }
 
     SearchResponse {
@@ -64,6 +86,8 @@ pub fn anchor_file_symbols(graph: &CodeGraph, file_path: &str) -> FileSymbolsRes
             kind: node.kind.to_string(),
             line_start: node.line_start,
             line_end: node.line_end,
+            range: node.range,
+            name_range: node.name_range,
             code: node.code_snippet.clone(),
         })
         .collect();
@@ -79,3 +103,50 @@ pub fn anchor_file_symbols(graph: &CodeGraph, file_path: &str) -> FileSymbolsRes
 pub fn graph_search(graph: &CodeGraph, query: &str, depth: usize) -> GraphSearchResult {
     graph.search_graph(query, depth)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::build_graph;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_structured_search_filters_on_metadata() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("lib.rs"),
+            "#[deprecated(note = \"use add2\")]\npub async fn add(a: i32, b: i32) -> i32 {\n    a + b\n}\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("service.py"),
+            "class Service:\n    @staticmethod\n    def run(job):\n        return True\n",
+        )
+        .unwrap();
+
+        let graph = build_graph(root).unwrap();
+        let structured = |symbol: &str,
+                          visibility: Option<&str>,
+                          modifier: Option<&str>,
+                          decorator: Option<&str>| {
+            let query = Query::Structured {
+                symbol: symbol.to_string(),
+                kind: None,
+                file: None,
+                visibility: visibility.map(str::to_string),
+                modifier: modifier.map(str::to_string),
+                decorator: decorator.map(str::to_string),
+            };
+            anchor_search(&graph, query).count
+        };
+        assert_eq!(
+            structured("add", Some("public"), Some("async"), Some("deprecated")),
+            1
+        );
+        assert_eq!(structured("add", Some("private"), None, None), 0);
+        assert_eq!(structured("run", None, None, Some("staticmethod")), 1);
+        assert_eq!(structured("run", None, None, Some("classmethod")), 0);
+    }
+}
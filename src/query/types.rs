@@ -4,7 +4,9 @@
 
 use serde::{Deserialize, Serialize};
 
-use crate::graph::{DependencyInfo, GraphStats, SearchResult};
+use crate::graph::{
+    CallSite, DependencyInfo, GraphStats, NodeId, ReferenceSite, SearchResult, SourceRange,
+};
 
 /// Query input — supports both simple string and structured queries.
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +19,15 @@ pub enum Query {
         symbol: String,
         kind: Option<String>,
         file: Option<String>,
+        /// `public`, `internal`, `protected` or `private`
+        #[serde(default)]
+        visibility: Option<String>,
+        /// A modifier the symbol must carry, e.g. `async` or `static`
+        #[serde(default)]
+        modifier: Option<String>,
+        /// A decorator, annotation or attribute the symbol must carry
+        #[serde(default)]
+        decorator: Option<String>,
     },
 }
 
@@ -69,6 +80,10 @@ pub struct FileSymbolEntry {
     pub kind: String,
     pub line_start: usize,
     pub line_end: usize,
+    #[serde(default, skip_serializing_if = "SourceRange::is_empty")]
+    pub range: SourceRange,
+    #[serde(default, skip_serializing_if = "SourceRange::is_empty")]
+    pub name_range: SourceRange,
     pub code: String,
 }
 
@@ -138,21 +153,48 @@ impl Default for ContextResponse {
 /// A code symbol with its location and source.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Symbol {
+    /// Stable node id — survives rebuilds, usable as a durable handle.
+    pub id: NodeId,
     pub name: String,
     pub kind: String,
     pub file: String,
     pub line: usize,
+    /// Exact span of the definition (byte offsets, UTF-8/UTF-16 columns)
+    #[serde(default, skip_serializing_if = "SourceRange::is_empty")]
+    pub range: SourceRange,
+    /// Span of the identifier naming it
+    #[serde(default, skip_serializing_if = "SourceRange::is_empty")]
+    pub name_range: SourceRange,
     pub code: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub visibility: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub modifiers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub docstring: Option<String>,
+    /// Decorators, annotations or attributes, without `@`/`#[...]`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub decorators: Vec<String>,
 }
 
 impl Symbol {
     pub fn from_search_result(r: &SearchResult) -> Self {
         Self {
+            id: r.id,
             name: r.symbol.clone(),
             kind: r.kind.to_string(),
             file: r.file.to_string_lossy().to_string(),
             line: r.line_start,
+            range: r.range,
+            name_range: r.name_range,
             code: r.code.clone(),
+            signature: r.metadata.signature.clone(),
+            visibility: r.metadata.visibility.map(|v| v.to_string()),
+            modifiers: r.metadata.modifiers.clone(),
+            docstring: r.metadata.docstring.clone(),
+            decorators: r.metadata.decorators.clone(),
         }
     }
 }
@@ -160,22 +202,32 @@ impl Symbol {
 /// A reference to another symbol (lighter than full Symbol).
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Reference {
+    pub id: NodeId,
     pub name: String,
     pub kind: String,
     pub file: String,
     pub line: usize,
     /// How it's related: "calls", "imports", "references"
     pub relationship: String,
+    /// For calls, each place the call happens
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub call_sites: Vec<CallSite>,
+    /// For references, each read, write or type mention
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub reference_sites: Vec<ReferenceSite>,
 }
 
 impl Reference {
     pub fn from_dep(dep: &DependencyInfo) -> Self {
         Self {
+            id: dep.id,
             name: dep.symbol.clone(),
             kind: dep.kind.to_string(),
             file: dep.file.to_string_lossy().to_string(),
             line: dep.line,
             relationship: dep.relationship.to_string(),
+            call_sites: dep.call_sites.clone(),
+            reference_sites: dep.reference_sites.clone(),
         }
     }
 }
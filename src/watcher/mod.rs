@@ -1,32 +1,26 @@
 //! File watcher module — real-time incremental graph updates.
 //!
 //! Watches the project directory for file changes and incrementally
-//! updates the code graph without requiring a full rebuild.
+//! updates the code graph without requiring a full rebuild. Uses the same
+//! `SourceFilter` as the graph builder, and rebuilds it when an ignore
+//! file or `.anchor/config.toml` changes. Changed files are re-extracted
+//! from their last parse, kept in a `TreeCache` that outlives filter
+//! reloads.
 
 use notify_debouncer_mini::{new_debouncer, DebouncedEventKind};
+use std::cmp::Ordering;
+use std::collections::{BTreeSet, HashSet};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 use std::time::Duration;
 use tracing::{debug, info, warn};
 
-use crate::graph::builder::rebuild_file;
+use crate::config::{CONFIG_FILE, QUERIES_DIR};
+use crate::graph::builder::{extract_files, project_filter};
 use crate::graph::engine::CodeGraph;
-use crate::parser::SupportedLanguage;
-
-/// Default debounce duration for file events.
-const DEFAULT_DEBOUNCE_MS: u64 = 200;
-
-/// Directories to always ignore.
-const IGNORED_DIRS: &[&str] = &[
-    ".git",
-    "target",
-    "node_modules",
-    ".anchor",
-    "__pycache__",
-    ".venv",
-    "dist",
-    "build",
-];
+use crate::graph::filter::SourceFilter;
+use crate::graph::types::{FileExtractions, GraphUpdate, NodeKind};
+use crate::parser::TreeCache;
 
 /// Start watching a directory for file changes, updating the graph in real-time.
 ///
@@ -35,26 +29,32 @@ const IGNORED_DIRS: &[&str] = &[
 /// # Arguments
 /// * `root` - The directory to watch recursively
 /// * `graph` - Shared graph to update on changes
-/// * `debounce_ms` - Debounce duration in milliseconds (0 = use default 200ms)
+/// * `debounce_ms` - Debounce duration in milliseconds (0 = use `watcher.debounce_ms`
+///   from the config, 200ms by default)
+///
+/// Fails if the config or a query override is invalid, or the watcher
+/// can't be set up.
 pub fn start_watching(
     root: &Path,
     graph: Arc<RwLock<CodeGraph>>,
     debounce_ms: u64,
-) -> Result<WatcherHandle, notify::Error> {
+) -> crate::error::Result<WatcherHandle> {
+    let root_owned = root.to_path_buf();
+    let mut filter = project_filter(root)?;
+    let trees = TreeCache::default();
+
     let debounce = if debounce_ms == 0 {
-        Duration::from_millis(DEFAULT_DEBOUNCE_MS)
+        Duration::from_millis(filter.config().watcher.debounce_ms)
     } else {
         Duration::from_millis(debounce_ms)
     };
 
-    let root_owned = root.to_path_buf();
-
     let mut debouncer = new_debouncer(
         debounce,
         move |result: Result<Vec<notify_debouncer_mini::DebouncedEvent>, notify::Error>| {
             match result {
                 Ok(events) => {
-                    handle_events(&events, &graph, &root_owned);
+                    handle_events(&events, &graph, &mut filter, &trees, &root_owned);
                 }
                 Err(e) => {
                     warn!(error = %e, "file watcher error");
@@ -75,82 +75,270 @@ pub fn start_watching(
 }
 
 /// Handle debounced file events.
+///
+/// Works in three phases so the graph write lock is held only briefly:
+/// 1. plan — classify paths against a snapshot of indexed files (read lock)
+/// 2. extract — parse every changed file in parallel (no lock)
+/// 3. apply — swap the results in with one `apply_update` (write lock)
 fn handle_events(
     events: &[notify_debouncer_mini::DebouncedEvent],
     graph: &Arc<RwLock<CodeGraph>>,
-    _root: &Path,
+    filter: &mut SourceFilter,
+    trees: &TreeCache,
+    root: &Path,
 ) {
-    // Deduplicate: collect unique paths and their last event kind
-    let mut paths: std::collections::HashMap<PathBuf, DebouncedEventKind> =
-        std::collections::HashMap::new();
-
-    for event in events {
-        let path = &event.path;
-
-        // Skip ignored directories
-        if should_ignore(path) {
-            continue;
+    // Ignore rules, config or queries changed: recompile the filter and
+    // resync the graph first. Config and query changes can alter how any
+    // file is extracted, so those re-extract everything.
+    let settings_changed = events.iter().any(|e| is_settings_file(&e.path));
+    if settings_changed || events.iter().any(|e| filter.is_ignore_file(&e.path)) {
+        match project_filter(root) {
+            Ok(new_filter) => {
+                info!("ignore rules or settings changed — reloading filter");
+                *filter = new_filter;
+                resync(graph, filter, trees, settings_changed);
+            }
+            Err(e) => warn!(error = %e, "invalid config or query — keeping previous settings"),
         }
+    }
 
-        // Only process source files
-        if SupportedLanguage::from_path(path).is_none() {
-            continue;
+    // Deduplicate; ongoing writes are skipped until they settle
+    let mut touched: BTreeSet<PathBuf> = BTreeSet::new();
+    for event in events {
+        match event.kind {
+            DebouncedEventKind::AnyContinuous => {
+                debug!(file = %event.path.display(), "skipping continuous write");
+            }
+            _ => {
+                touched.insert(event.path.clone());
+            }
         }
-
-        paths.insert(path.clone(), event.kind);
+    }
+    if touched.is_empty() {
+        return;
     }
 
-    if paths.is_empty() {
+    let plan = {
+        let graph = match graph.read() {
+            Ok(g) => g,
+            Err(e) => {
+                warn!(error = %e, "failed to acquire graph read lock");
+                return;
+            }
+        };
+        plan_batch(&touched, &graph, filter)
+    };
+    if plan.is_empty() {
         return;
     }
 
-    debug!(count = paths.len(), "processing file events");
+    debug!(
+        changed = plan.changed.len(),
+        gone = plan.gone.len(),
+        "processing file events"
+    );
+    let update = build_update(plan, filter, trees);
+    apply(graph, update);
+}
 
-    let mut graph = match graph.write() {
-        Ok(g) => g,
-        Err(e) => {
-            warn!(error = %e, "failed to acquire graph write lock");
-            return;
+/// What a batch of events means for the graph, before any parsing.
+#[derive(Default)]
+struct BatchPlan {
+    /// Files that exist and need (re-)extraction.
+    changed: Vec<PathBuf>,
+    /// Indexed files that disappeared, with their symbol signature
+    /// (used to recognise them again if they reappear elsewhere).
+    gone: Vec<(PathBuf, Vec<(NodeKind, String)>)>,
+    /// Files already in the graph (so new vs. modified can be told apart).
+    indexed: HashSet<PathBuf>,
+}
+
+impl BatchPlan {
+    fn is_empty(&self) -> bool {
+        self.changed.is_empty() && self.gone.is_empty()
+    }
+}
+
+/// Classify touched paths. A path may be a file or a whole directory
+/// (moves and deletes of directories arrive as a single event).
+fn plan_batch(touched: &BTreeSet<PathBuf>, graph: &CodeGraph, filter: &SourceFilter) -> BatchPlan {
+    let indexed: HashSet<PathBuf> = graph.all_files().into_iter().collect();
+    let mut changed: BTreeSet<PathBuf> = BTreeSet::new();
+    let mut gone: BTreeSet<PathBuf> = BTreeSet::new();
+
+    for path in touched {
+        if path.is_dir() {
+            changed.extend(filter.source_files_under(path));
+        } else if path.is_file() {
+            if filter.accepts(path) {
+                changed.insert(path.clone());
+            }
+        } else {
+            // Deleted: the file itself, or everything under a deleted directory
+            gone.extend(indexed.iter().filter(|p| p.starts_with(path)).cloned());
         }
+    }
+
+    BatchPlan {
+        changed: changed.into_iter().collect(),
+        gone: gone
+            .into_iter()
+            .map(|p| {
+                let sig = graph.file_signature(&p);
+                (p, sig)
+            })
+            .collect(),
+        indexed,
+    }
+}
+
+/// Extract changed files (in parallel, without holding the graph lock)
+/// and pair new files with vanished ones that define the same symbols.
+fn build_update(plan: BatchPlan, filter: &SourceFilter, trees: &TreeCache) -> GraphUpdate {
+    let (extractions, failed) = extract_files(&plan.changed, filter, trees);
+    for path in &failed {
+        warn!(file = %path.display(), "re-extraction failed — dropping from graph");
+    }
+
+    let mut gone = plan.gone;
+    let mut update = GraphUpdate {
+        removed: failed,
+        ..GraphUpdate::default()
     };
 
-    for (path, kind) in &paths {
-        match kind {
-            DebouncedEventKind::Any => {
-                if path.exists() {
-                    // File was created or modified — rebuild
-                    debug!(file = %path.display(), "rebuilding changed file");
-                    if let Err(e) = rebuild_file(&mut graph, path) {
-                        warn!(file = %path.display(), error = %e, "rebuild failed");
-                    }
-                } else {
-                    // File was deleted — remove
-                    debug!(file = %path.display(), "removing deleted file");
-                    graph.remove_file(path);
-                }
-            }
-            DebouncedEventKind::AnyContinuous => {
-                // Ongoing writes — skip until settled
-                debug!(file = %path.display(), "skipping continuous write");
-            }
-            _ => {
-                debug!(file = %path.display(), "unhandled event kind");
+    for ext in extractions {
+        if plan.indexed.contains(&ext.file_path) {
+            update.changed.push(ext);
+            continue;
+        }
+        match find_rename_source(&ext, &gone) {
+            Some(i) => {
+                let (old, _) = gone.remove(i);
+                debug!(from = %old.display(), to = %ext.file_path.display(), "detected rename");
+                update.renamed.push((old, ext));
             }
+            None => update.changed.push(ext),
         }
     }
+
+    update.removed.extend(gone.into_iter().map(|(p, _)| p));
+    for path in &update.removed {
+        trees.forget(path);
+    }
+    update
 }
 
-/// Check if a path should be ignored (hidden dirs, build dirs, etc.).
-fn should_ignore(path: &Path) -> bool {
-    for component in path.components() {
-        if let std::path::Component::Normal(name) = component {
-            let name = name.to_string_lossy();
-            if IGNORED_DIRS.contains(&name.as_ref()) {
-                return true;
+/// Share of symbols a new file must have in common with a vanished one
+/// to count as renamed from it, like git's default rename similarity.
+const RENAME_SIMILARITY: f64 = 0.5;
+
+/// Find the vanished file a new file was most likely renamed from: the
+/// one sharing the largest share of its symbols, if that reaches
+/// `RENAME_SIMILARITY`, preferring the same file name (directory moves)
+/// on a tie.
+fn find_rename_source(
+    ext: &FileExtractions,
+    gone: &[(PathBuf, Vec<(NodeKind, String)>)],
+) -> Option<usize> {
+    let sig = ext.signature();
+    if sig.is_empty() {
+        return None;
+    }
+    let same_name = |i: usize| gone[i].0.file_name() == ext.file_path.file_name();
+    (0..gone.len())
+        .map(|i| (i, similarity(&sig, &gone[i].1)))
+        .filter(|&(_, score)| score >= RENAME_SIMILARITY)
+        .max_by(|&(a, score_a), &(b, score_b)| {
+            score_a
+                .total_cmp(&score_b)
+                .then(same_name(a).cmp(&same_name(b)))
+                .then(b.cmp(&a))
+        })
+        .map(|(i, _)| i)
+}
+
+/// Symbols two sorted signatures have in common, as a share of all the
+/// symbols in either.
+fn similarity(a: &[(NodeKind, String)], b: &[(NodeKind, String)]) -> f64 {
+    let (mut i, mut j, mut common) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            Ordering::Less => i += 1,
+            Ordering::Greater => j += 1,
+            Ordering::Equal => {
+                common += 1;
+                i += 1;
+                j += 1;
             }
         }
     }
-    false
+    let total = a.len() + b.len() - common;
+    if total == 0 {
+        return 0.0;
+    }
+    common as f64 / total as f64
+}
+
+/// Swap an update into the shared graph under a single write lock.
+fn apply(graph: &Arc<RwLock<CodeGraph>>, update: GraphUpdate) {
+    if update.is_empty() {
+        return;
+    }
+    match graph.write() {
+        Ok(mut g) => g.apply_update(update),
+        Err(e) => warn!(error = %e, "failed to acquire graph write lock"),
+    }
+}
+
+/// Whether `path` is the config file or a tag query override.
+fn is_settings_file(path: &Path) -> bool {
+    let is_query = path.extension().is_some_and(|ext| ext == "scm")
+        && path.parent().is_some_and(|dir| dir.ends_with(QUERIES_DIR));
+    path.ends_with(CONFIG_FILE) || is_query
+}
+
+/// Bring the graph in line with a new filter: drop files that are now
+/// ignored and index files that are no longer ignored. With `reextract`
+/// every accepted file is extracted again, not just the new ones.
+fn resync(
+    graph: &Arc<RwLock<CodeGraph>>,
+    filter: &SourceFilter,
+    trees: &TreeCache,
+    reextract: bool,
+) {
+    let indexed: HashSet<PathBuf> = match graph.read() {
+        Ok(g) => g.all_files().into_iter().collect(),
+        Err(e) => {
+            warn!(error = %e, "failed to acquire graph read lock");
+            return;
+        }
+    };
+
+    let removed: Vec<PathBuf> = indexed
+        .iter()
+        .filter(|p| !filter.accepts(p))
+        .cloned()
+        .collect();
+    let added: Vec<PathBuf> = filter
+        .source_files()
+        .into_iter()
+        .filter(|p| reextract || !indexed.contains(p))
+        .collect();
+    debug!(
+        removed = removed.len(),
+        added = added.len(),
+        "resyncing graph with filter"
+    );
+
+    let (changed, failed) = extract_files(&added, filter, trees);
+    apply(
+        graph,
+        GraphUpdate {
+            removed: removed.into_iter().chain(failed).collect(),
+            changed,
+            renamed: Vec::new(),
+        },
+    );
 }
 
 /// Handle that keeps the file watcher alive.
@@ -158,3 +346,143 @@ fn should_ignore(path: &Path) -> bool {
 pub struct WatcherHandle {
     _debouncer: notify_debouncer_mini::Debouncer<notify::RecommendedWatcher>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::graph::build_graph;
+    use std::fs;
+    use tempfile::tempdir;
+
+    fn shared(root: &Path) -> Arc<RwLock<CodeGraph>> {
+        Arc::new(RwLock::new(build_graph(root).unwrap()))
+    }
+
+    fn process(graph: &Arc<RwLock<CodeGraph>>, root: &Path, touched: &[PathBuf]) {
+        let filter = project_filter(root).unwrap();
+        let touched: BTreeSet<PathBuf> = touched.iter().cloned().collect();
+        let plan = plan_batch(&touched, &graph.read().unwrap(), &filter);
+        apply(graph, build_update(plan, &filter, &TreeCache::default()));
+    }
+
+    #[test]
+    fn test_resync_follows_ignore_changes() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("src")).unwrap();
+        fs::create_dir_all(root.join("gen")).unwrap();
+        fs::write(root.join("src/main.rs"), "fn main() {}").unwrap();
+        fs::write(root.join("gen/out.rs"), "fn generated() {}").unwrap();
+
+        let graph = shared(root);
+        let trees = TreeCache::default();
+        assert_eq!(graph.read().unwrap().search("generated", 1).len(), 1);
+
+        // Ignoring gen/ drops its files
+        fs::write(root.join(".anchorignore"), "gen/\n").unwrap();
+        resync(&graph, &project_filter(root).unwrap(), &trees, true);
+        assert!(graph.read().unwrap().search("generated", 1).is_empty());
+        assert_eq!(graph.read().unwrap().search("main", 1).len(), 1);
+        assert!(trees.contains(&root.join("src/main.rs")));
+
+        // Un-ignoring brings them back; parses outlive the filter reload
+        fs::remove_file(root.join(".anchorignore")).unwrap();
+        resync(&graph, &project_filter(root).unwrap(), &trees, false);
+        assert_eq!(graph.read().unwrap().search("generated", 1).len(), 1);
+        assert!(trees.contains(&root.join("src/main.rs")));
+        assert!(trees.contains(&root.join("gen/out.rs")));
+    }
+
+    #[test]
+    fn test_rename_preserves_symbol_ids() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(root.join("old.rs"), "fn moved() {}\nfn other() {}").unwrap();
+
+        let graph = shared(root);
+        let id = graph.read().unwrap().search("moved", 1)[0].id;
+
+        fs::rename(root.join("old.rs"), root.join("new.rs")).unwrap();
+        process(&graph, root, &[root.join("old.rs"), root.join("new.rs")]);
+
+        let g = graph.read().unwrap();
+        let results = g.search("moved", 1);
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].file, root.join("new.rs"));
+        assert_eq!(results[0].id, id);
+        assert_eq!(g.stats().file_count, 1);
+    }
+
+    #[test]
+    fn test_rename_with_edits_preserves_symbol_ids() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("old.rs"),
+            "fn moved() {}\nfn other() {}\nfn third() {}",
+        )
+        .unwrap();
+        fs::write(root.join("gone.rs"), "fn unrelated() {}").unwrap();
+
+        let graph = shared(root);
+        let id = graph.read().unwrap().search("moved", 1)[0].id;
+        let unrelated = graph.read().unwrap().search("unrelated", 1)[0].id;
+
+        // Renamed and edited in one go: three of four symbols in common
+        fs::remove_file(root.join("old.rs")).unwrap();
+        fs::write(
+            root.join("new.rs"),
+            "fn moved() {}\nfn other() {}\nfn third() {}\nfn added() {}",
+        )
+        .unwrap();
+        // Replaced by a file with nothing in common
+        fs::remove_file(root.join("gone.rs")).unwrap();
+        fs::write(root.join("fresh.rs"), "fn unrelated_too() {}").unwrap();
+        let touched = ["old.rs", "new.rs", "gone.rs", "fresh.rs"].map(|f| root.join(f));
+        process(&graph, root, &touched);
+
+        let g = graph.read().unwrap();
+        assert_eq!(g.search("moved", 1)[0].file, root.join("new.rs"));
+        assert_eq!(g.search("moved", 1)[0].id, id);
+        assert!(g.node_by_id(unrelated).is_none());
+        assert_eq!(g.stats().file_count, 2);
+    }
+
+    #[test]
+    fn test_directory_move_is_one_batch() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("a/nested")).unwrap();
+        fs::write(root.join("a/one.rs"), "fn one() {}").unwrap();
+        fs::write(root.join("a/nested/two.rs"), "fn two() {}").unwrap();
+
+        let graph = shared(root);
+        let id = graph.read().unwrap().search("two", 1)[0].id;
+
+        // A directory move arrives as events for the two directory paths
+        fs::rename(root.join("a"), root.join("b")).unwrap();
+        process(&graph, root, &[root.join("a"), root.join("b")]);
+
+        let g = graph.read().unwrap();
+        assert_eq!(g.stats().file_count, 2);
+        assert_eq!(g.search("one", 1)[0].file, root.join("b/one.rs"));
+        assert_eq!(g.search("two", 1)[0].id, id);
+    }
+
+    #[test]
+    fn test_directory_delete_removes_contents() {
+        let dir = tempdir().unwrap();
+        let root = dir.path();
+        fs::create_dir_all(root.join("gone")).unwrap();
+        fs::write(root.join("gone/x.rs"), "fn x_fn() {}").unwrap();
+        fs::write(root.join("keep.rs"), "fn keep() {}").unwrap();
+
+        let graph = shared(root);
+        fs::remove_dir_all(root.join("gone")).unwrap();
+        process(&graph, root, &[root.join("gone")]);
+
+        let g = graph.read().unwrap();
+        assert!(g.search("x_fn", 1).is_empty());
+        assert_eq!(g.stats().file_count, 1);
+    }
+}